@@ -3,21 +3,14 @@ import { resolve, dirname } from 'path'
 import { fileURLToPath } from 'url'
 import { writeFileSync, mkdirSync } from 'fs'
 
-import type { ThemeInput, TransformOutput, Diagnostic } from 'typewritingclass-compiler'
+import type { Compiler, CompilerOptions, ThemeInput, Diagnostic } from 'typewritingclass-compiler'
 import { loadThemeSync } from 'typewritingclass-compiler/loadTheme'
 
 // Load the native addon from typewritingclass-compiler
 const require = createRequire(import.meta.url)
 const __dirname = dirname(fileURLToPath(import.meta.url))
 const native: {
-  transform(
-    code: string,
-    filename: string,
-    layerOffset: number,
-    themeInput: ThemeInput,
-    strict?: boolean | null,
-  ): TransformOutput
-  generateCss(rulesJson: string): string
+  Compiler: new (themeInput: ThemeInput, options?: CompilerOptions | null) => Compiler
 } = require(resolve(__dirname, '..', '..', 'typewritingclass-compiler', 'index.node'))
 
 export interface TwcBabelPluginOptions {
@@ -43,9 +36,9 @@ export interface TwcBabelPluginOptions {
  * ```
  */
 export default function twcBabelPlugin() {
-  let layer = 0
-  const fileRules = new Map<string, string[]>()
-  let cachedTheme: ThemeInput | null = null
+  // One compiler per distinct set of options, so files configured with a
+  // different theme, strictness or output file get their own stylesheet
+  const compilers = new Map<string, Compiler>()
 
   return {
     visitor: {
@@ -69,29 +62,22 @@ export default function twcBabelPlugin() {
         const strict = opts.strict ?? true
         const outputFile = opts.outputFile ?? 'twc.css'
 
-        // Load theme once: use provided theme or auto-load from typewritingclass package
-        if (!cachedTheme) {
-          cachedTheme = opts.theme ?? loadThemeSync()
-        }
-        const themeInput = cachedTheme
-
         try {
-          const result = native.transform(code, filename, layer, themeInput, strict)
-          layer = result.nextLayer
+          // Load the theme once per options: use the provided theme or
+          // auto-load it from the typewritingclass package
+          const key = JSON.stringify([opts.theme ?? null, strict, outputFile])
+          let compiler = compilers.get(key)
+          if (!compiler) {
+            compiler = new native.Compiler(opts.theme ?? loadThemeSync(), { strict })
+            compilers.set(key, compiler)
+          }
+          const result = compiler.transform(code, filename)
 
           // Emit diagnostics as console warnings/errors
           emitDiagnostics(result.diagnostics, filename)
 
-          // Collect extracted CSS rules for this file
-          if (result.rules.length > 0) {
-            fileRules.set(
-              filename,
-              result.rules.map((r) => r.cssText),
-            )
-          }
-
           // Write combined CSS to the output file
-          writeCssFile(outputFile, fileRules)
+          writeCssFile(outputFile, compiler)
 
           // Replace the program source with the transformed code.
           // We use Babel's internal parse to replace the entire AST.
@@ -130,18 +116,12 @@ function emitDiagnostics(diagnostics: Diagnostic[], filename: string): void {
 }
 
 /**
- * Collect all CSS rules from all processed files and write them to a single
- * output file. Uses the native `generateCss` function to produce well-ordered CSS.
+ * Write the rules of every processed file to a single output file, using the
+ * compiler's stylesheet so the CSS is well-ordered.
  */
-function writeCssFile(outputFile: string, fileRules: Map<string, string[]>): void {
-  const allRules: string[] = []
-  for (const rules of fileRules.values()) {
-    allRules.push(...rules)
-  }
-
-  if (allRules.length === 0) return
-
-  const css = native.generateCss(JSON.stringify(allRules))
+function writeCssFile(outputFile: string, compiler: Compiler): void {
+  const css = compiler.stylesheet()
+  if (!css) return
 
   // Ensure the output directory exists
   const outputDir = dirname(resolve(outputFile))
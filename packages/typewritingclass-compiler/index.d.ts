@@ -1,21 +1,181 @@
 /* auto-generated by NAPI-RS */
 /* eslint-disable */
+/**
+ * A compiler for one build: the theme is parsed once, and the rules of
+ * every transformed file are kept until the file changes or is removed, so
+ * `stylesheet()` always reflects the current sources.
+ */
+export declare class Compiler {
+  constructor(themeInput: ThemeInput, options?: CompilerOptions | undefined | null)
+  /** Problems with the theme input, unless `themeErrors` is "throw" */
+  get themeWarnings(): Array<ThemeIssue>
+  /**
+   * Transform a file and keep its rules in place of those of its previous
+   * version. `resolve` maps an import to a file whose exported style
+   * constants should be inlined, like `TransformOptions.resolve`.
+   */
+  transform(code: string, filename: string, resolve?: ((arg0: string, arg1: string) => string | null | undefined) | undefined | null): TransformOutput
+  /**
+   * Forget a deleted file: its rules leave the stylesheet and modules
+   * evaluated from it are dropped. Returns whether it had rules.
+   */
+  removeFile(filename: string): boolean
+  /** Drop the cached exports of modules evaluated from a changed file */
+  invalidate(path: string): void
+  /** The CSS of every file's rules, in layer order with each class once */
+  stylesheet(): string
+  /** The stylesheet, plus any class name shared by rules with different CSS */
+  buildStylesheet(): StylesheetOutput
+}
+
+/**
+ * Assemble a stylesheet from extracted rules, e.g. those of several
+ * `transform` calls. Rules are deduplicated by class name; a class name
+ * whose rules render different CSS is reported in `collisions`.
+ */
+export declare function buildStylesheet(rules: Array<ExtractedRule>, options?: StylesheetOptions | undefined | null): StylesheetOutput
+
+/** Two rules that share a class name but render different CSS */
+export interface ClassCollision {
+  className: string
+  /** CSS of the rule that was kept */
+  cssText: string
+  /** CSS of the rule that was dropped */
+  otherCssText: string
+}
+
+/** Settings for a `Compiler` */
+export interface CompilerOptions {
+  /** Report styles that can't be evaluated statically as errors (default: true) */
+  strict?: boolean
+  /** Render a code frame for every diagnostic (default: true) */
+  codeFrame?: boolean
+  /**
+   * Extract from the parts of a file that parsed when it contains
+   * recoverable syntax errors (default: false)
+   */
+  recover?: boolean
+  /** Generate a v3 source map for the transformed code (default: false) */
+  sourceMap?: boolean
+  /** Embed the original source in the map's `sourcesContent` (default: true) */
+  sourcesContent?: boolean
+  /**
+   * Project root. Relative imports of files inside it are followed so their
+   * exported style constants can be inlined.
+   */
+  root?: string
+  /**
+   * What to do with a malformed theme: "warn" (default), which drops the
+   * bad values and reports them in `themeWarnings`, or "throw"
+   */
+  themeErrors?: string
+  /**
+   * Cascade layer order of the stylesheet (default: reset, base,
+   * components, utilities, overrides). Rules are only layered once one is
+   * given a named layer; empty to emit them unlayered regardless.
+   */
+  layerOrder?: Array<string>
+  /**
+   * How rules are ordered within a layer: "source" (default), in the order
+   * they were compiled, or "deterministic", by the shape of each rule so
+   * the output doesn't depend on the order files were transformed in
+   */
+  order?: string
+  /**
+   * Emit rules sharing the same `@media`, `@supports` and `@container`
+   * context in one block, with min-width queries ascending and max-width
+   * queries descending. Rules only move when that can't change which
+   * declaration wins. (default: false)
+   */
+  groupAtRules?: boolean
+  /**
+   * Emit minified CSS: no whitespace, short hex colors and zero lengths,
+   * no final semicolons, and the selectors of rules with identical
+   * declarations merged. Class names are unchanged. (default: false)
+   */
+  minify?: boolean
+}
+
+export interface Declaration {
+  property: string
+  value: string
+}
+
 export interface Diagnostic {
   message: string
+  /** 1-based line of the start of the span */
   line: number
+  /** 0-based UTF-16 column of the start of the span */
   column: number
+  /** 1-based line of the end of the span */
+  endLine: number
+  /** 0-based UTF-16 column of the end of the span */
+  endColumn: number
+  /** Byte offset of the start of the span */
+  start: number
+  /** Byte offset of the end of the span */
+  end: number
   severity: string
+  /** Pre-rendered code frame highlighting the span */
+  codeFrame?: string
 }
 
 export interface ExtractedRule {
   className: string
   cssText: string
   layer: number
+  /**
+   * The rule's place among those of its file, so rules of the same shape
+   * keep their file order in deterministic order; its layer when omitted
+   */
+  position?: number
+  /** Named cascade layer set with `layer('name')` */
+  cascadeLayer?: string
+  /** Explicit priority set with `layer(n)` */
+  priority?: number
+  declarations: Array<Declaration>
+  selectors: Array<string>
+  selectorTemplate?: string
+  mediaQueries: Array<string>
+  supportsQueries: Array<string>
+  containerQueries: Array<string>
+  /** Extra CSS emitted alongside the rule, e.g. `@keyframes` */
+  extraCss: Array<string>
 }
 
-export declare function generateCss(rulesJson: string): string
+/**
+ * Assemble a stylesheet from a JSON array of rules, either CSS strings
+ * (layered in array order) or `ExtractedRule` objects. Duplicate rules are
+ * emitted once; JSON that isn't an array throws.
+ */
+export declare function generateCss(rulesJson: string, options?: StylesheetOptions | undefined | null): string
+
+/** How `generateCss` assembles a stylesheet */
+export interface StylesheetOptions {
+  /**
+   * Cascade layer order (default: reset, base, components, utilities,
+   * overrides). Rules are only layered once one is given a named layer;
+   * empty to emit them unlayered regardless.
+   */
+  layerOrder?: Array<string>
+  /** "source" (default) or "deterministic"; see `CompilerOptions.order` */
+  order?: string
+  /** See `CompilerOptions.groupAtRules` (default: false) */
+  groupAtRules?: boolean
+  /** See `CompilerOptions.minify` (default: false) */
+  minify?: boolean
+}
 
 /** Theme data passed from the Vite plugin (loaded from the TS theme package) */
+export interface StylesheetOutput {
+  css: string
+  /**
+   * Class names shared by rules with different CSS; the first rule of
+   * each is kept
+   */
+  collisions: Array<ClassCollision>
+}
+
 export interface ThemeInput {
   /** JSON: { "blue": { "50": "#eff6ff", ... }, ... } */
   colors: string
@@ -29,6 +189,10 @@ export interface ThemeInput {
   fontWeights: string
   /** JSON: { "sans": "ui-sans-serif, system-ui, ...", ... } */
   fontFamilies: string
+  /** JSON: { "tight": "-0.025em", "wide": "0.025em", ... } */
+  tracking?: string
+  /** JSON: { "snug": "1.375", "6": "1.5rem", ... } */
+  leading?: string
   /** JSON: { "none": "0px", "sm": "0.125rem", "DEFAULT": "0.25rem", ... } */
   radii: string
   /** JSON: { "sm": "0 1px 2px ...", "DEFAULT": "0 1px 3px ...", ... } */
@@ -39,18 +203,86 @@ export interface ThemeInput {
   animations: string
   /** JSON: { "spin": "@keyframes spin { ... }", ... } */
   keyframes: string
+  /** JSON: { "sm": "24rem", "md": "28rem", ... } */
+  containers?: string
+  /** JSON: { "sm": "640px", "md": "768px", ... } (default: the runtime's breakpoints) */
+  breakpoints?: string
+  /** How `dark` applies: "media" (default), "class" or "attr" */
+  darkMode?: string
+  /** Selector for the "class" and "attr" dark modes (default: `.dark` / `[data-theme="dark"]`) */
+  darkSelector?: string
   /** Default border-radius value */
   defaultRadius: string
   /** Default shadow value */
   defaultShadow: string
 }
 
-export declare function transform(code: string, filename: string, layerOffset: number, themeInput: ThemeInput, strict?: boolean | undefined | null): TransformOutput
+/** A malformed value in the theme input */
+export interface ThemeIssue {
+  /** The `ThemeInput` field, e.g. `textSizes` */
+  field: string
+  /** JSON path of the value within the field, e.g. `$.blue["500"]` */
+  path: string
+  reason: string
+}
+
+export declare function transform(code: string, filename: string, layerOffset: number, themeInput: ThemeInput, strict?: boolean | undefined | null, options?: TransformOptions | undefined | null): TransformOutput
+
+/** Optional transform settings */
+export interface TransformOptions {
+  /** Render a code frame for every diagnostic (default: true) */
+  codeFrame?: boolean
+  /**
+   * Extract from the parts of a file that parsed when it contains
+   * recoverable syntax errors (default: false)
+   */
+  recover?: boolean
+  /**
+   * Resolve an import to an absolute file path whose exported style
+   * constants should be inlined. Return `null` to fall back to `root`
+   * resolution (or leave the import to the runtime).
+   */
+  resolve?: (arg0: string, arg1: string) => string | null | undefined
+  /**
+   * Project root. Relative imports of files inside it are followed so their
+   * exported style constants can be inlined.
+   */
+  root?: string
+  /** Generate a v3 source map for the transformed code (default: false) */
+  sourceMap?: boolean
+  /** Embed the original source in the map's `sourcesContent` (default: true) */
+  sourcesContent?: boolean
+  /**
+   * What to do with a malformed theme: "warn" (default), which drops the
+   * bad values and reports them in `themeWarnings`, or "throw"
+   */
+  themeErrors?: string
+}
 
 export interface TransformOutput {
   code: string
+  /** v3 source map (JSON) for `code`, when `sourceMap` is enabled */
+  map?: string
   rules: Array<ExtractedRule>
   nextLayer: number
   hasDynamic: boolean
   diagnostics: Array<Diagnostic>
+  /** Problems with the theme input, unless `themeErrors` is "throw" */
+  themeWarnings: Array<ThemeIssue>
+  /**
+   * Absolute paths of the imported files whose exports were evaluated.
+   * The file should be transformed again when any of them changes.
+   */
+  dependencies: Array<string>
+  /**
+   * Set by `Compiler.transform`: whether the file's rules differ from
+   * those of its previous version, i.e. the stylesheet changed
+   */
+  rulesChanged?: boolean
 }
+
+/**
+ * Every problem with a theme input. An empty list means `transform` will
+ * accept it.
+ */
+export declare function validateTheme(themeInput: ThemeInput): Array<ThemeIssue>
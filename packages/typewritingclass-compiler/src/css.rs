@@ -1,7 +1,24 @@
-use crate::style_rule::StyleRule;
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+
+use crate::minify::{minify_css, minify_prelude, minify_value};
+use crate::style_rule::{is_pseudo_element, StyleRule};
 
 /// Render a single rule to CSS text (without @layer wrapper)
 pub fn render_rule(class_name: &str, rule: &StyleRule) -> String {
+    let css = wrap_at_rules(render_block(class_name, rule), rule);
+
+    // Prepend extra CSS blocks (e.g. @keyframes)
+    if !rule.extra_css.is_empty() {
+        let extra = rule.extra_css.join("\n");
+        return format!("{}\n{}", extra, css);
+    }
+
+    css
+}
+
+/// The rule's selector and declarations, without at-rules or extra CSS
+fn render_block(class_name: &str, rule: &StyleRule) -> String {
     let decls: String = rule
         .declarations
         .iter()
@@ -9,35 +26,609 @@ pub fn render_rule(class_name: &str, rule: &StyleRule) -> String {
         .collect::<Vec<_>>()
         .join("\n");
 
-    let selector = if let Some(tmpl) = &rule.selector_template {
-        tmpl.replace("&", &format!(".{}", class_name))
-    } else {
-        let mut s = format!(".{}", class_name);
-        for sel in &rule.selectors {
-            s.push_str(sel);
-        }
-        s
+    format!("{} {{\n{}\n}}", render_selector(class_name, rule), decls)
+}
+
+fn render_selector(class_name: &str, rule: &StyleRule) -> String {
+    // Compound selectors attach to the class; the template supplies the
+    // surrounding context; pseudo-elements end the whole selector
+    let (pseudo_elements, compound): (Vec<&String>, Vec<&String>) =
+        rule.selectors.iter().partition(|s| is_pseudo_element(s));
+    let mut subject = format!(".{}", class_name);
+    for sel in compound {
+        subject.push_str(sel);
+    }
+    let mut selector = match &rule.selector_template {
+        Some(tmpl) => tmpl.replace('&', &subject),
+        None => subject,
     };
+    for sel in pseudo_elements {
+        selector.push_str(sel);
+    }
+    selector
+}
 
-    let mut css = format!("{} {{\n{}\n}}", selector, decls);
+/// Wrap CSS in the rule's at-rules
+fn wrap_at_rules(mut css: String, rule: &StyleRule) -> String {
+    // Innermost to outermost: @container, @supports, @media, matching the TS renderer
+    for cq in &rule.container_queries {
+        css = format!("@container {} {{\n{}\n}}", cq, css);
+    }
+
+    for sq in &rule.supports_queries {
+        css = format!("@supports {} {{\n{}\n}}", sq, css);
+    }
 
     for mq in &rule.media_queries {
         css = format!("@media {} {{\n{}\n}}", mq, css);
     }
 
-    // Prepend extra CSS blocks (e.g. @keyframes)
-    if !rule.extra_css.is_empty() {
-        let extra = rule.extra_css.join("\n");
-        css = format!("{}\n{}", extra, css);
+    css
+}
+
+/// The cascade layer order used when none is configured. Rules without a
+/// named layer go in `utilities`. Like the runtime, a stylesheet is only
+/// layered once some rule names a layer: unlayered CSS beats every layer, so
+/// layering by default would let any stray unlayered rule override utilities.
+pub const DEFAULT_LAYER_ORDER: &[&str] = &["reset", "base", "components", "utilities", "overrides"];
+
+/// The layer of rules that weren't given one with `layer('name')`
+pub const DEFAULT_LAYER: &str = "utilities";
+
+/// How rules are ordered within their cascade layer
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum RuleOrder {
+    /// By numeric layer, i.e. the order the rules were extracted in
+    #[default]
+    Source,
+    /// By the shape of each rule alone, so the stylesheet doesn't depend on
+    /// the order files were compiled in: unconditional rules first, then
+    /// min-width queries ascending and max-width queries descending; within
+    /// those, fewer variants first and shorthands before longhands, then
+    /// in the order of their file
+    Deterministic,
+}
+
+impl RuleOrder {
+    pub fn parse(order: &str) -> Option<Self> {
+        match order {
+            "source" => Some(RuleOrder::Source),
+            "deterministic" => Some(RuleOrder::Deterministic),
+            _ => None,
+        }
+    }
+}
+
+/// How a stylesheet is assembled from its rules
+pub struct StylesheetOptions {
+    /// Cascade layer order, used once a rule names a layer; empty to emit the
+    /// rules unlayered regardless
+    pub layer_order: Vec<String>,
+    pub order: RuleOrder,
+    /// Emit rules sharing the same `@media`/`@supports`/`@container` context
+    /// in one block, mobile first
+    pub group_at_rules: bool,
+    /// Emit minified CSS, with the selectors of rules that have identical
+    /// declarations merged
+    pub minify: bool,
+}
+
+impl Default for StylesheetOptions {
+    fn default() -> Self {
+        Self {
+            layer_order: DEFAULT_LAYER_ORDER.iter().map(|name| name.to_string()).collect(),
+            order: RuleOrder::default(),
+            group_at_rules: false,
+            minify: false,
+        }
+    }
+}
+
+/// A rendered rule ready to be placed in a stylesheet
+pub struct LayeredRule<'a> {
+    pub class_name: &'a str,
+    pub css: &'a str,
+    /// Numeric ordering layer: later rules override earlier ones
+    pub layer: u32,
+    /// The rule's place among those of its file: its layer less the file's
+    /// layer offset
+    pub position: u32,
+    /// The rule `css` was rendered from
+    pub rule: &'a StyleRule,
+}
+
+impl LayeredRule<'_> {
+    /// Explicit `layer(n)` priorities come before any other ordering:
+    /// `layer(0)` ahead of the rules without one, higher priorities after
+    fn priority(&self) -> (u32, bool) {
+        (self.rule.priority.unwrap_or(0), self.rule.priority.is_none())
+    }
+
+    fn cascade_layer(&self) -> &str {
+        self.rule.layer.as_deref().unwrap_or(DEFAULT_LAYER)
+    }
+}
+
+/// Two rules that share a class name but render different CSS. Class names
+/// are content hashes, so this means a hash collision or rules extracted by
+/// different compiler versions.
+#[derive(Debug, PartialEq)]
+pub struct ClassCollision {
+    pub class_name: String,
+    /// CSS of the rule that was kept
+    pub css: String,
+    /// CSS of the rule that was dropped
+    pub other_css: String,
+}
+
+/// Keep the first rule of each class name, reporting every other CSS seen
+/// for the same class once. Rules without a class name are deduplicated by
+/// their CSS.
+pub fn dedupe_rules<'a>(rules: Vec<LayeredRule<'a>>) -> (Vec<LayeredRule<'a>>, Vec<ClassCollision>) {
+    let mut kept: HashMap<&str, &str> = HashMap::new();
+    let mut reported = HashSet::new();
+    let mut unique = vec![];
+    let mut collisions = vec![];
+    for rule in rules {
+        let key = if rule.class_name.is_empty() { rule.css } else { rule.class_name };
+        match kept.get(key) {
+            None => {
+                kept.insert(key, rule.css);
+                unique.push(rule);
+            }
+            Some(&css) if css != rule.css && reported.insert((key, rule.css)) => {
+                collisions.push(ClassCollision {
+                    class_name: key.to_string(),
+                    css: css.to_string(),
+                    other_css: rule.css.to_string(),
+                });
+            }
+            Some(_) => {}
+        }
+    }
+    (unique, collisions)
+}
+
+/// Build a stylesheet: an `@layer` statement fixing the order of
+/// `options.layer_order` (followed by any other named layer, alphabetically),
+/// then one block per layer holding its rules in `options.order`. Rules are
+/// emitted unlayered when none names a layer or the layer order is empty.
+pub fn render_stylesheet(rules: &[LayeredRule], options: &StylesheetOptions) -> String {
+    let mut sorted: Vec<&LayeredRule> = rules.iter().collect();
+    match options.order {
+        RuleOrder::Source => sorted.sort_by_key(|rule| (rule.priority(), rule.layer)),
+        RuleOrder::Deterministic => sorted.sort_by(|a, b| compare_rules(a, b)),
     }
+    let render = |rules: Vec<&LayeredRule>| {
+        if options.minify {
+            render_minified(&rules, options.group_at_rules)
+        } else if options.group_at_rules {
+            render_grouped(&rules)
+        } else {
+            join_rules(rules.iter().map(|rule| rule.css))
+        }
+    };
+    if options.layer_order.is_empty() || !sorted.iter().any(|rule| rule.rule.layer.is_some()) {
+        return render(sorted);
+    }
+
+    let mut order: Vec<&str> = options.layer_order.iter().map(String::as_str).collect();
+    let mut extra: Vec<&str> = sorted
+        .iter()
+        .map(|rule| rule.cascade_layer())
+        .filter(|name| !order.contains(name))
+        .collect();
+    extra.sort();
+    extra.dedup();
+    order.extend(extra);
+
+    let separator = if options.minify { "," } else { ", " };
+    let mut blocks = vec![format!("@layer {};", order.join(separator))];
+    for name in &order {
+        let css = render(sorted.iter().copied().filter(|rule| rule.cascade_layer() == *name).collect());
+        if css.is_empty() {
+            continue;
+        }
+        if options.minify {
+            blocks.push(format!("@layer {}{{{}}}", name, css));
+        } else {
+            blocks.push(format!("@layer {} {{\n{}\n}}", name, indent(&css)));
+        }
+    }
+    blocks.join(if options.minify { "" } else { "\n\n" })
+}
+
+/// The `RuleOrder::Deterministic` comparison. Rules of the same shape keep
+/// their order within their file, so the later of two utilities setting the
+/// same property still wins; remaining ties are broken by class name and CSS
+/// so equal inputs always sort the same way.
+fn compare_rules(a: &LayeredRule, b: &LayeredRule) -> Ordering {
+    let (a_min, a_max) = viewport_range(a.rule);
+    let (b_min, b_max) = viewport_range(b.rule);
+    a.priority()
+        .cmp(&b.priority())
+        .then(a_min.total_cmp(&b_min))
+        .then(b_max.total_cmp(&a_max))
+        .then(variant_count(a.rule).cmp(&variant_count(b.rule)))
+        .then(property_depth(a.rule).cmp(&property_depth(b.rule)))
+        .then(b.rule.declarations.len().cmp(&a.rule.declarations.len()))
+        .then(a.position.cmp(&b.position))
+        .then(a.class_name.cmp(b.class_name))
+        .then(a.css.cmp(b.css))
+}
+
+/// The widest `min-width` (0 when unset) and narrowest `max-width` (infinite
+/// when unset) of a rule's media queries, in pixels
+fn viewport_range(rule: &StyleRule) -> (f64, f64) {
+    let mut min = 0.0_f64;
+    let mut max = f64::INFINITY;
+    for query in &rule.media_queries {
+        if let Some(width) = media_width(query, "min-width") {
+            min = min.max(width);
+        }
+        if let Some(width) = media_width(query, "max-width") {
+            max = max.min(width);
+        }
+    }
+    (min, max)
+}
 
+/// The value of a `(feature: <length>)` in a media query, in pixels. Only
+/// `px`, `rem` and `em` lengths are understood.
+fn media_width(query: &str, feature: &str) -> Option<f64> {
+    let start = query.find(&format!("({}:", feature))? + feature.len() + 2;
+    let value = query[start..].split(')').next()?.trim();
+    let (number, scale) = if let Some(px) = value.strip_suffix("px") {
+        (px, 1.0)
+    } else if let Some(rem) = value.strip_suffix("rem") {
+        (rem, 16.0)
+    } else {
+        (value.strip_suffix("em")?, 16.0)
+    };
+    number.trim().parse::<f64>().ok().map(|n| n * scale)
+}
+
+/// Modifiers other than viewport widths: selectors, group/peer templates,
+/// feature, container and other media queries
+fn variant_count(rule: &StyleRule) -> usize {
+    let other_media = rule
+        .media_queries
+        .iter()
+        .filter(|query| media_width(query, "min-width").is_none() && media_width(query, "max-width").is_none())
+        .count();
+    rule.selectors.len()
+        + usize::from(rule.selector_template.is_some())
+        + rule.supports_queries.len()
+        + rule.container_queries.len()
+        + other_media
+}
+
+/// How specific the rule's most general property is: `margin` is 0,
+/// `margin-top` 1, `margin-inline-start` 2
+fn property_depth(rule: &StyleRule) -> usize {
+    rule.declarations
+        .iter()
+        .map(|(property, _)| property.trim_start_matches('-').matches('-').count())
+        .min()
+        .unwrap_or(0)
+}
+
+/// Render rules with one block per run of rules sharing the same at-rule
+/// context, after moving them into `mobile_first` order. Extra CSS such as
+/// `@keyframes` comes first, each block once.
+fn render_grouped(rules: &[&LayeredRule]) -> String {
+    let mut extra: Vec<&str> = vec![];
+    let mut blocks: Vec<String> = vec![];
+    let mut group: Vec<String> = vec![];
+    let mut group_rule: Option<&StyleRule> = None;
+    for index in mobile_first(rules) {
+        let rule = rules[index];
+        if (group_rule.is_some_and(|current| at_rules(current) != at_rules(rule.rule)) || is_opaque(rule))
+            && let Some(current) = group_rule.take()
+        {
+            blocks.push(wrap_at_rules(join_rules(group.iter().map(String::as_str)), current));
+            group.clear();
+        }
+        if is_opaque(rule) {
+            blocks.push(rule.css.to_string());
+            continue;
+        }
+        for css in &rule.rule.extra_css {
+            if !extra.contains(&css.as_str()) {
+                extra.push(css);
+            }
+        }
+        group.push(render_block(rule.class_name, rule.rule));
+        group_rule = Some(rule.rule);
+    }
+    if let Some(current) = group_rule {
+        blocks.push(wrap_at_rules(join_rules(group.iter().map(String::as_str)), current));
+    }
+    join_rules(extra.into_iter().chain(blocks.iter().map(String::as_str)))
+}
+
+/// Rules with identical declarations in the same context, emitted as one
+/// rule with their selectors joined
+struct MinifiedRule<'a> {
+    /// Empty for an opaque rule
+    selectors: Vec<String>,
+    /// The declaration block, or the whole CSS of an opaque rule
+    declarations: String,
+    rules: Vec<&'a LayeredRule<'a>>,
+}
+
+/// Minified counterpart of `render_grouped` (or of joining the rules when
+/// `group` is false). A rule's selector joins an earlier rule with the same
+/// declarations and context unless a rule in between sets a related
+/// property, so merging never changes which declaration wins.
+fn render_minified(rules: &[&LayeredRule], group: bool) -> String {
+    let order: Vec<usize> = if group { mobile_first(rules) } else { (0..rules.len()).collect() };
+    let mut extra: Vec<String> = vec![];
+    let mut merged: Vec<MinifiedRule> = vec![];
+    for index in order {
+        let rule = rules[index];
+        if is_opaque(rule) {
+            merged.push(MinifiedRule {
+                selectors: vec![],
+                declarations: minify_css(rule.css),
+                rules: vec![rule],
+            });
+            continue;
+        }
+        for css in &rule.rule.extra_css {
+            let css = minify_css(css);
+            if !extra.contains(&css) {
+                extra.push(css);
+            }
+        }
+        let selector = minify_prelude(&render_selector(rule.class_name, rule.rule));
+        let declarations = rule
+            .rule
+            .declarations
+            .iter()
+            .map(|(property, value)| format!("{}:{}", property, minify_value(property, value)))
+            .collect::<Vec<_>>()
+            .join(";");
+        let mut target = None;
+        for (position, earlier) in merged.iter().enumerate().rev() {
+            let first = earlier.rules[0];
+            if !earlier.selectors.is_empty()
+                && earlier.declarations == declarations
+                && at_rules(first.rule) == at_rules(rule.rule)
+                && can_share_rule(&selector)
+                && can_share_rule(&earlier.selectors[0])
+            {
+                target = Some(position);
+                break;
+            }
+            if earlier.rules.iter().any(|other| conflicts(other, rule)) {
+                break;
+            }
+        }
+        match target {
+            Some(position) => {
+                merged[position].selectors.push(selector);
+                merged[position].rules.push(rule);
+            }
+            None => merged.push(MinifiedRule {
+                selectors: vec![selector],
+                declarations,
+                rules: vec![rule],
+            }),
+        }
+    }
+
+    let mut out = extra.concat();
+    let mut index = 0;
+    while index < merged.len() {
+        if merged[index].selectors.is_empty() {
+            out.push_str(&merged[index].declarations);
+            index += 1;
+            continue;
+        }
+        // With `group`, consecutive rules of one context share its at-rules
+        let context = merged[index].rules[0].rule;
+        let mut body = String::new();
+        while let Some(entry) = merged.get(index) {
+            let joins = group && !entry.selectors.is_empty() && at_rules(entry.rules[0].rule) == at_rules(context);
+            if !body.is_empty() && !joins {
+                break;
+            }
+            body.push_str(&format!("{}{{{}}}", entry.selectors.join(","), entry.declarations));
+            index += 1;
+        }
+        out.push_str(&wrap_at_rules_minified(body, context));
+    }
+    out
+}
+
+/// Pseudo-classes and pseudo-elements every browser understands. A browser
+/// drops a whole selector list when it doesn't support one of its selectors,
+/// so newer ones (`:has()`, `:focus-visible`, `::backdrop`...) and vendor
+/// prefixed ones never share a rule.
+const SHAREABLE_PSEUDOS: &[&str] = &[
+    ":hover", ":focus", ":active", ":visited", ":link", ":target",
+    ":disabled", ":enabled", ":checked", ":indeterminate", ":default",
+    ":required", ":optional", ":valid", ":invalid", ":in-range", ":out-of-range",
+    ":read-only", ":read-write", ":empty",
+    ":first-child", ":last-child", ":only-child", ":first-of-type", ":last-of-type", ":only-of-type",
+    "::before", "::after", "::first-line", "::first-letter",
+];
+
+/// `SHAREABLE_PSEUDOS` taking an `An+B` argument
+const SHAREABLE_NTH: &[&str] = &[":nth-child", ":nth-last-child", ":nth-of-type", ":nth-last-of-type"];
+
+/// Whether a selector can be joined with others: it's made of plain classes,
+/// combinators and `SHAREABLE_PSEUDOS` alone
+fn can_share_rule(selector: &str) -> bool {
+    let bytes = selector.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b' ' | b'>' | b'~' | b'+' => i += 1,
+            b'.' => {
+                let end = ident_end(bytes, i + 1);
+                if end == i + 1 {
+                    return false;
+                }
+                i = end;
+            }
+            b':' => {
+                let name_start = if bytes.get(i + 1) == Some(&b':') { i + 2 } else { i + 1 };
+                let end = ident_end(bytes, name_start);
+                let pseudo = &selector[i..end];
+                if bytes.get(end) == Some(&b'(') {
+                    let Some(close) = selector[end..].find(')').map(|offset| end + offset) else {
+                        return false;
+                    };
+                    let argument = &selector[end + 1..close];
+                    let is_an_b = argument == "odd"
+                        || argument == "even"
+                        || argument.bytes().all(|b| b.is_ascii_digit() || matches!(b, b'n' | b'+' | b'-' | b' '));
+                    if !SHAREABLE_NTH.contains(&pseudo) || !is_an_b {
+                        return false;
+                    }
+                    i = close + 1;
+                } else if SHAREABLE_PSEUDOS.contains(&pseudo) {
+                    i = end;
+                } else {
+                    return false;
+                }
+            }
+            _ => return false,
+        }
+    }
+    true
+}
+
+/// The end of the identifier starting at `start`, escapes included
+fn ident_end(bytes: &[u8], start: usize) -> usize {
+    let mut i = start;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 2,
+            b if b.is_ascii_alphanumeric() || b == b'-' || b == b'_' || !b.is_ascii() => i += 1,
+            _ => break,
+        }
+    }
+    i.min(bytes.len())
+}
+
+fn wrap_at_rules_minified(mut css: String, rule: &StyleRule) -> String {
+    for cq in &rule.container_queries {
+        css = format!("@container {}{{{}}}", minify_prelude(cq), css);
+    }
+    for sq in &rule.supports_queries {
+        css = format!("@supports {}{{{}}}", minify_prelude(sq), css);
+    }
+    for mq in &rule.media_queries {
+        css = format!("@media {}{{{}}}", minify_prelude(mq), css);
+    }
     css
 }
 
-/// Join multiple CSS rules into a single stylesheet.
-pub fn wrap_in_layer(rules_css: &[String]) -> String {
-    if rules_css.is_empty() {
-        return String::new();
+/// The `@media`, `@supports` and `@container` context of a rule
+fn at_rules(rule: &StyleRule) -> (&[String], &[String], &[String]) {
+    (&rule.media_queries, &rule.supports_queries, &rule.container_queries)
+}
+
+/// A rule known only by its CSS, e.g. a string passed to `generate_css`
+fn is_opaque(rule: &LayeredRule) -> bool {
+    rule.rule.declarations.is_empty()
+}
+
+/// The indices of `rules` reordered so rules without at-rules come first,
+/// then min-width queries ascending and max-width queries descending, with
+/// rules of the same context together. A rule never moves past one that sets
+/// a related property, so the cascade resolves exactly as in the input order.
+fn mobile_first(rules: &[&LayeredRule]) -> Vec<usize> {
+    let n = rules.len();
+    // Contexts other than the unconditional one rank by first appearance
+    let mut context_rank = vec![0; n];
+    for i in 0..n {
+        let context = at_rules(rules[i].rule);
+        if context != (&[], &[], &[]) {
+            context_rank[i] = 1 + (0..i).find(|&j| at_rules(rules[j].rule) == context).unwrap_or(i);
+        }
+    }
+    let ranges: Vec<(f64, f64)> = rules.iter().map(|rule| viewport_range(rule.rule)).collect();
+    let compare = |a: usize, b: usize| {
+        ranges[a]
+            .0
+            .total_cmp(&ranges[b].0)
+            .then(ranges[b].1.total_cmp(&ranges[a].1))
+            .then(context_rank[a].cmp(&context_rank[b]))
+            .then(a.cmp(&b))
+    };
+
+    // Rules that must stay in input order: `blockers[i]` counts the earlier
+    // rules that conflict with rule i and haven't been placed yet
+    let mut blockers = vec![0; n];
+    let mut blocked: Vec<Vec<usize>> = vec![vec![]; n];
+    for j in 0..n {
+        for i in 0..j {
+            if conflicts(rules[i], rules[j]) {
+                blockers[j] += 1;
+                blocked[i].push(j);
+            }
+        }
+    }
+
+    let mut ready: Vec<usize> = (0..n).filter(|&i| blockers[i] == 0).collect();
+    let mut order = Vec::with_capacity(n);
+    while let Some(position) = (0..ready.len()).min_by(|&a, &b| compare(ready[a], ready[b])) {
+        let next = ready.swap_remove(position);
+        order.push(next);
+        for &j in &blocked[next] {
+            blockers[j] -= 1;
+            if blockers[j] == 0 {
+                ready.push(j);
+            }
+        }
+    }
+    order
+}
+
+/// Whether two rules set a related property, so their order matters
+fn conflicts(a: &LayeredRule, b: &LayeredRule) -> bool {
+    if is_opaque(a) || is_opaque(b) {
+        return true;
     }
-    rules_css.join("\n\n")
+    a.rule.declarations.iter().any(|(pa, _)| {
+        let family = property_family(pa);
+        b.rule.declarations.iter().any(|(pb, _)| property_family(pb) == family)
+    })
+}
+
+/// A coarse group of properties that can override each other: `padding`
+/// and `padding-top` are both `padding`, `top` and `inset` are both `inset`.
+/// Over-grouping only keeps more rules in place.
+fn property_family(property: &str) -> &str {
+    if property.starts_with("--") {
+        return property;
+    }
+    let property = ["-webkit-", "-moz-", "-ms-"]
+        .iter()
+        .find_map(|prefix| property.strip_prefix(prefix))
+        .unwrap_or(property);
+    match property {
+        "top" | "right" | "bottom" | "left" => "inset",
+        "gap" | "row-gap" | "column-gap" => "gap",
+        _ => match property.split('-').next().unwrap_or(property) {
+            "align" | "justify" | "place" => "place",
+            family => family,
+        },
+    }
+}
+
+fn join_rules<'a>(rules: impl Iterator<Item = &'a str>) -> String {
+    rules.collect::<Vec<_>>().join("\n\n")
+}
+
+/// Indent every non-empty line by two spaces
+fn indent(css: &str) -> String {
+    css.lines()
+        .map(|line| if line.is_empty() { String::new() } else { format!("  {}", line) })
+        .collect::<Vec<_>>()
+        .join("\n")
 }
@@ -0,0 +1,277 @@
+use oxc_diagnostics::{OxcDiagnostic, Severity};
+use oxc_span::Span;
+
+/// A compiler diagnostic with both byte-span and human-readable positions.
+/// Lines are 1-based; columns are 0-based UTF-16 code unit offsets (matching
+/// what editors, esbuild and Rollup expect in `loc.column`).
+#[derive(Debug, Clone)]
+pub struct DiagnosticInfo {
+    pub message: String,
+    pub severity: String, // "error" | "warning"
+    /// Byte offset of the start of the span in the original source
+    pub start: u32,
+    /// Byte offset of the end of the span in the original source
+    pub end: u32,
+    pub line: u32,
+    pub column: u32,
+    pub end_line: u32,
+    pub end_column: u32,
+    /// Pre-rendered code frame pointing at the span (when requested)
+    pub code_frame: Option<String>,
+}
+
+impl DiagnosticInfo {
+    /// Create a diagnostic for a span. Line/column information is filled in
+    /// later by `locate`, once per file.
+    pub fn new(severity: &str, message: impl Into<String>, span: Span) -> Self {
+        Self {
+            message: message.into(),
+            severity: severity.to_string(),
+            start: span.start,
+            end: span.end,
+            line: 0,
+            column: 0,
+            end_line: 0,
+            end_column: 0,
+            code_frame: None,
+        }
+    }
+
+    pub fn error(message: impl Into<String>, span: Span) -> Self {
+        Self::new("error", message, span)
+    }
+
+    pub fn warning(message: impl Into<String>, span: Span) -> Self {
+        Self::new("warning", message, span)
+    }
+
+    /// Convert a parser diagnostic, positioned at its primary label
+    pub fn from_parse_error(err: &OxcDiagnostic) -> Self {
+        let span = err
+            .labels
+            .as_ref()
+            .and_then(|labels| labels.iter().find(|l| l.primary()).or(labels.first()))
+            .map(|l| Span::new(l.offset() as u32, (l.offset() + l.len()) as u32))
+            .unwrap_or_default();
+        let severity = match err.severity {
+            Severity::Error => "error",
+            Severity::Warning | Severity::Advice => "warning",
+        };
+        let message = match &err.help {
+            Some(help) => format!("{}\nhelp: {}", err.message, help),
+            None => err.message.to_string(),
+        };
+        Self::new(severity, message, span)
+    }
+}
+
+/// Maps byte offsets in a source file to line/column positions.
+pub struct LineIndex<'a> {
+    source: &'a str,
+    /// Byte offset at which each line starts
+    line_starts: Vec<usize>,
+}
+
+impl<'a> LineIndex<'a> {
+    pub fn new(source: &'a str) -> Self {
+        let mut line_starts = vec![0];
+        let bytes = source.as_bytes();
+        let mut i = 0;
+        while i < bytes.len() {
+            match bytes[i] {
+                b'\n' => line_starts.push(i + 1),
+                b'\r' => {
+                    // Treat \r\n as a single line break
+                    if bytes.get(i + 1) == Some(&b'\n') {
+                        i += 1;
+                    }
+                    line_starts.push(i + 1);
+                }
+                _ => {}
+            }
+            i += 1;
+        }
+        Self { source, line_starts }
+    }
+
+    /// Convert a byte offset into a (1-based line, 0-based UTF-16 column) pair.
+    /// Offsets past the end of the source are clamped; offsets inside a
+    /// multi-byte character are snapped back to its start.
+    pub fn line_col(&self, offset: u32) -> (u32, u32) {
+        let offset = self.clamp(offset as usize);
+        let line = self.line_of(offset);
+        let line_start = self.line_starts[line];
+        let column: usize = self.source[line_start..offset]
+            .chars()
+            .map(char::len_utf16)
+            .sum();
+        (line as u32 + 1, column as u32)
+    }
+
+    /// Text of a 1-based line, without its line terminator
+    pub fn line_text(&self, line: u32) -> Option<&'a str> {
+        let idx = (line as usize).checked_sub(1)?;
+        let start = *self.line_starts.get(idx)?;
+        let end = self
+            .line_starts
+            .get(idx + 1)
+            .copied()
+            .unwrap_or(self.source.len());
+        Some(self.source[start..end].trim_end_matches(['\n', '\r']))
+    }
+
+    pub fn line_count(&self) -> u32 {
+        self.line_starts.len() as u32
+    }
+
+    fn line_of(&self, offset: usize) -> usize {
+        match self.line_starts.binary_search(&offset) {
+            Ok(i) => i,
+            Err(i) => i - 1,
+        }
+    }
+
+    fn clamp(&self, offset: usize) -> usize {
+        let mut offset = offset.min(self.source.len());
+        while !self.source.is_char_boundary(offset) {
+            offset -= 1;
+        }
+        offset
+    }
+}
+
+/// Number of context lines shown above and below the highlighted span
+const FRAME_CONTEXT_LINES: u32 = 2;
+
+/// Render a Babel-style code frame for the given span:
+///
+/// ```text
+///   1 | import { cx } from 'typewritingclass'
+/// > 2 | const a = cx(p(x))
+///     |           ^^^^^^^
+///   3 |
+/// ```
+pub fn render_code_frame(index: &LineIndex, start: u32, end: u32) -> String {
+    let (line, _) = index.line_col(start);
+    let (end_line, _) = index.line_col(end);
+    let first = line.saturating_sub(FRAME_CONTEXT_LINES).max(1);
+    let last = (end_line + FRAME_CONTEXT_LINES).min(index.line_count());
+    let gutter_width = last.to_string().len();
+
+    let mut out = vec![];
+    for n in first..=last {
+        let text = index.line_text(n).unwrap_or("");
+        let marker = if n >= line && n <= end_line { '>' } else { ' ' };
+        if text.is_empty() {
+            out.push(format!("{} {:>w$} |", marker, n, w = gutter_width));
+        } else {
+            out.push(format!("{} {:>w$} | {}", marker, n, text, w = gutter_width));
+        }
+        if n >= line && n <= end_line {
+            // Underline the part of this line covered by the span, counted in
+            // characters so the carets line up in a terminal.
+            let line_start = index.line_starts[n as usize - 1] as u32;
+            let from = if n == line { start.saturating_sub(line_start) as usize } else { 0 };
+            let to = if n == end_line {
+                end.saturating_sub(line_start) as usize
+            } else {
+                text.len()
+            };
+            let from_chars = char_count(text, from);
+            let to_chars = char_count(text, to).max(from_chars + 1);
+            out.push(format!(
+                "  {:>w$} | {}{}",
+                "",
+                " ".repeat(from_chars),
+                "^".repeat(to_chars - from_chars),
+                w = gutter_width
+            ));
+        }
+    }
+    out.join("\n")
+}
+
+/// Number of characters in the first `byte_len` bytes of `text` (clamped)
+fn char_count(text: &str, byte_len: usize) -> usize {
+    let mut end = byte_len.min(text.len());
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    text[..end].chars().count()
+}
+
+/// Fill in line/column positions (and optionally code frames) for every
+/// diagnostic, based on the source the spans refer to.
+pub fn locate(diagnostics: &mut [DiagnosticInfo], source: &str, code_frame: bool) {
+    if diagnostics.is_empty() {
+        return;
+    }
+    let index = LineIndex::new(source);
+    for d in diagnostics.iter_mut() {
+        let (line, column) = index.line_col(d.start);
+        let (end_line, end_column) = index.line_col(d.end);
+        d.line = line;
+        d.column = column;
+        d.end_line = end_line;
+        d.end_column = end_column;
+        if code_frame {
+            d.code_frame = Some(render_code_frame(&index, d.start, d.end));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_line_col_first_line() {
+        let index = LineIndex::new("abc\ndef");
+        assert_eq!(index.line_col(0), (1, 0));
+        assert_eq!(index.line_col(2), (1, 2));
+    }
+
+    #[test]
+    fn test_line_col_after_newline() {
+        let index = LineIndex::new("abc\ndef\r\nghi");
+        assert_eq!(index.line_col(4), (2, 0));
+        assert_eq!(index.line_col(6), (2, 2));
+        assert_eq!(index.line_col(9), (3, 0));
+    }
+
+    #[test]
+    fn test_line_col_counts_utf16_units() {
+        // "é" is 2 bytes / 1 UTF-16 unit, "😀" is 4 bytes / 2 UTF-16 units
+        let src = "é😀x";
+        let index = LineIndex::new(src);
+        assert_eq!(index.line_col(src.find('x').unwrap() as u32), (1, 3));
+    }
+
+    #[test]
+    fn test_line_col_clamps_past_end() {
+        let index = LineIndex::new("ab\n");
+        assert_eq!(index.line_col(100), (2, 0));
+    }
+
+    #[test]
+    fn test_code_frame() {
+        let src = "const a = 1\nconst b = cx(x)\nconst c = 3";
+        let index = LineIndex::new(src);
+        let start = src.find("cx").unwrap() as u32;
+        let frame = render_code_frame(&index, start, start + 5);
+        assert_eq!(
+            frame,
+            "  1 | const a = 1\n> 2 | const b = cx(x)\n    |           ^^^^^\n  3 | const c = 3"
+        );
+    }
+
+    #[test]
+    fn test_locate_fills_positions() {
+        let src = "a\n  bc";
+        let mut diags = vec![DiagnosticInfo::error("oops", Span::new(4, 6))];
+        locate(&mut diags, src, false);
+        assert_eq!((diags[0].line, diags[0].column), (2, 2));
+        assert_eq!((diags[0].end_line, diags[0].end_column), (2, 4));
+        assert!(diags[0].code_frame.is_none());
+    }
+}
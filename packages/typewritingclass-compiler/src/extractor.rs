@@ -3,23 +3,33 @@ use std::collections::HashMap;
 use oxc_allocator::Allocator;
 use oxc_ast::ast::*;
 use oxc_parser::Parser;
-use oxc_span::SourceType;
+use oxc_span::{GetSpan, SourceType, Span};
 
 use crate::css;
+use crate::diagnostics::{self, DiagnosticInfo};
+use crate::fold;
 use crate::hash;
-use crate::modifiers;
+use crate::modifiers::{self, Modifier};
+use crate::modules::ModuleLoader;
+use crate::sourcemap::SplicedSource;
 use crate::style_rule::StyleRule;
 use crate::theme::ThemeData;
 use crate::tokens;
 use crate::utilities::{self, Value};
+use crate::visit::visit_expressions;
+use crate::scope::{self, Escape, LocalConst};
 
 /// What a local name is bound to after import resolution
 #[derive(Debug, Clone)]
-enum Binding {
+pub enum Binding {
     /// A utility function (e.g., bg, p, rounded)
     Utility(String),
-    /// A modifier (e.g., hover, focus, dark, sm)
-    Modifier(String),
+    /// A modifier (e.g., hover, focus, dark, sm, or a constant `data('state=open')`)
+    Modifier(Modifier),
+    /// A modifier factory (e.g., data, aria, supports)
+    ModifierFactory(String),
+    /// A named group/peer marker (namedGroup, namedPeer)
+    Marker(String),
     /// cx() core function
     Cx,
     /// tw chain entry point
@@ -38,22 +48,49 @@ enum Binding {
     TextSize(String, String),
     /// A namespace import (e.g., `import * as shadows from '...'`)
     Namespace(String),
+    /// A file-local constant holding a literal or theme value (e.g., `const gap = 4`)
+    Const(Value),
+    /// A file-local constant holding a single style rule (e.g., `const pad = p(4)`)
+    Rule(StyleRule),
+    /// A file-local constant whose compiled value is a class string
+    /// (e.g., `const base = tw.p(4).rounded.lg`)
+    ClassNames(String),
+    /// A tw chain or cx() call exported from another module, kept as rules
+    /// so each importing file renders its own CSS
+    Styles(Vec<StyleRule>),
 }
 
-pub struct DiagnosticInfo {
-    pub message: String,
-    pub line: u32,
-    pub column: u32,
-    pub severity: String,
+/// Options controlling a single file transform
+pub struct ExtractOptions {
+    /// Report cx() calls that cannot be statically evaluated as errors
+    pub strict: bool,
+    /// Attach a pre-rendered code frame to every diagnostic
+    pub code_frame: bool,
+    /// Keep extracting from files with recoverable syntax errors, using the
+    /// partial AST produced by oxc's error recovery
+    pub recover: bool,
+    /// Generate a source map for the transformed code
+    pub source_map: bool,
+    /// Embed the original source in the source map's `sourcesContent`
+    pub sources_content: bool,
 }
 
+/// An extracted rule: `(class_name, css_text, layer, rule)`, keeping the
+/// source rule for the stylesheet's cascade layers and ordering
+pub type CssRule = (String, String, u32, StyleRule);
+
 /// Result of a single file transform
 pub struct TransformResult {
     pub code: String,
-    pub css_rules: Vec<(String, String, u32)>, // (class_name, css_text, layer)
+    /// v3 source map (JSON) for `code`, when requested
+    pub map: Option<String>,
+    pub css_rules: Vec<CssRule>,
     pub next_layer: u32,
     pub has_dynamic: bool,
     pub diagnostics: Vec<DiagnosticInfo>,
+    /// False when syntax errors kept the file from being read at all, so
+    /// the rules of its last good version still apply
+    pub parsed: bool,
 }
 
 /// All known utility function names exported from typewritingclass.
@@ -96,7 +133,7 @@ const UTILITY_NAMES: &[&str] = &[
     "colSpan", "colStart", "colEnd", "rowSpan", "rowStart", "rowEnd",
     "gridFlow", "autoCols", "autoRows",
     "justifyItems", "justifySelf", "alignContent", "placeContent", "placeItems", "placeSelf",
-    "container",
+    "container", "containerType", "containerName",
     // Borders
     "rounded", "roundedT", "roundedB", "roundedL", "roundedR",
     "roundedTL", "roundedTR", "roundedBR", "roundedBL",
@@ -151,34 +188,37 @@ impl DynCounter {
     }
 }
 
-pub fn transform(source: &str, filename: &str, layer_offset: u32, theme: &ThemeData, strict: bool) -> TransformResult {
+pub fn transform(
+    source: &str,
+    filename: &str,
+    layer_offset: u32,
+    theme: &ThemeData,
+    options: &ExtractOptions,
+    loader: Option<&mut ModuleLoader>,
+) -> TransformResult {
     let allocator = Allocator::default();
     let source_type = SourceType::from_path(filename).unwrap_or_default();
     let ret = Parser::new(&allocator, source, source_type).parse();
 
-    if ret.panicked || !ret.errors.is_empty() {
+    let mut diagnostics: Vec<DiagnosticInfo> =
+        ret.errors.iter().map(DiagnosticInfo::from_parse_error).collect();
+
+    // A panicked parse leaves an empty program. Without recovery, any syntax
+    // error means the AST can't be trusted, so leave the file untouched.
+    if ret.panicked || (!ret.errors.is_empty() && !options.recover) {
         return TransformResult {
-            code: source.to_string(),
-            css_rules: vec![],
-            next_layer: layer_offset,
-            has_dynamic: false,
-            diagnostics: vec![],
+            parsed: false,
+            ..unchanged(source, filename, layer_offset, diagnostics, options)
         };
     }
 
     let program = &ret.program;
 
     // Phase 1: Collect import bindings
-    let bindings = collect_imports(program, theme);
+    let bindings = collect_imports(program, filename, theme, loader);
 
     if bindings.is_empty() {
-        return TransformResult {
-            code: source.to_string(),
-            css_rules: vec![],
-            next_layer: layer_offset,
-            has_dynamic: false,
-            diagnostics: vec![],
-        };
+        return unchanged(source, filename, layer_offset, diagnostics, options);
     }
 
     // Check if there are cx or tw bindings
@@ -188,145 +228,454 @@ pub fn transform(source: &str, filename: &str, layer_offset: u32, theme: &ThemeD
 
     if !has_cx && !has_tw {
         // No cx() or tw usage, just prepend inject import
-        let code = prepend_inject(source);
+        let mut spliced = SplicedSource::new(source, &[]);
+        prepend_inject(&mut spliced, source);
+        let (code, map) = render_output(&spliced, filename, options);
+        diagnostics::locate(&mut diagnostics, source, options.code_frame);
         return TransformResult {
             code,
+            map,
             css_rules: vec![],
             next_layer: layer_offset,
             has_dynamic: false,
-            diagnostics: vec![],
+            diagnostics,
+            parsed: true,
         };
     }
 
-    // Phase 2: Find cx() calls and tw chains, try to extract them
-    let mut layer = layer_offset;
-    let mut css_rules = vec![];
-    let mut replacements: Vec<(u32, u32, String)> = vec![];
-    let mut has_dynamic = false;
-    let mut needs_runtime = false;
-    let mut diagnostics: Vec<DiagnosticInfo> = vec![];
-    let mut dyn_counter = DynCounter::new();
-    // Track spans that have been captured by tw chains to avoid processing sub-expressions
-    let mut tw_captured_spans: Vec<(u32, u32)> = vec![];
+    // Phase 2: Find cx() calls and tw chains, try to extract them. Top-level
+    // constants are bound as their initializers are reached, so later
+    // references to them can be inlined.
+    let constants = scope::analyze(program);
+    let mut extraction = Extraction {
+        source,
+        bindings,
+        theme,
+        strict: options.strict,
+        has_cx,
+        has_tw,
+        has_dynamic_import,
+        layer: layer_offset,
+        css_rules: vec![],
+        replacements: vec![],
+        has_dynamic: false,
+        needs_runtime: false,
+        dyn_counter: DynCounter::new(),
+        tw_captured_spans: vec![],
+        diagnostics,
+    };
 
     visit_expressions(program, &mut |expr| {
-        let expr_span = get_expr_span(expr);
+        let span = expr.span();
 
         // Skip sub-expressions already captured by a tw chain
-        if let Some((start, end)) = expr_span {
-            if tw_captured_spans.iter().any(|(s, e)| start >= *s && end <= *e) {
-                return;
-            }
+        if extraction
+            .tw_captured_spans
+            .iter()
+            .any(|(s, e)| span.start >= *s && span.end <= *e)
+        {
+            return;
         }
 
+        let class_str = extraction.extract(expr);
+        if let Some(constant) = constants.get(&span) {
+            extraction.bind_constant(constant, class_str);
+        }
+    });
+
+    let Extraction {
+        bindings,
+        layer,
+        css_rules,
+        replacements,
+        has_dynamic,
+        needs_runtime,
+        tw_captured_spans,
+        mut diagnostics,
+        ..
+    } = extraction;
+
+    // Phase 3: Apply replacements
+    let mut spliced = SplicedSource::new(source, &replacements);
+
+    // Phase 4: Inject appropriate imports
+    if has_dynamic && !source.contains("typewritingclass/runtime") {
+        spliced.prepend("import { __twcDynamic } from 'typewritingclass/runtime';\n".to_string());
+    }
+
+    let total_extractable = count_cx_calls(program, &bindings) + tw_captured_spans.len();
+    if !has_dynamic && !needs_runtime && replacements.len() == total_extractable {
+        // All calls were statically extracted — no runtime needed
+    } else {
+        prepend_inject(&mut spliced, source);
+    }
+
+    let (code, map) = render_output(&spliced, filename, options);
+    diagnostics::locate(&mut diagnostics, source, options.code_frame);
+
+    TransformResult {
+        code,
+        map,
+        css_rules,
+        next_layer: layer,
+        has_dynamic,
+        diagnostics,
+        parsed: true,
+    }
+}
+
+/// Mutable state of the extraction pass over one file
+struct Extraction<'t> {
+    source: &'t str,
+    bindings: HashMap<String, Binding>,
+    theme: &'t ThemeData,
+    strict: bool,
+    has_cx: bool,
+    has_tw: bool,
+    has_dynamic_import: bool,
+    layer: u32,
+    css_rules: Vec<CssRule>,
+    replacements: Vec<(u32, u32, String)>,
+    has_dynamic: bool,
+    needs_runtime: bool,
+    dyn_counter: DynCounter,
+    /// Spans captured by tw chains, whose sub-expressions must not be processed
+    tw_captured_spans: Vec<(u32, u32)>,
+    diagnostics: Vec<DiagnosticInfo>,
+}
+
+impl Extraction<'_> {
+    /// Try to compile a tw chain or cx() call. Returns the class string when
+    /// the whole expression was replaced by a static one.
+    fn extract<'a>(&mut self, expr: &'a Expression<'a>) -> Option<String> {
+        let expr_span = get_expr_span(expr);
+
         // Try tw chain extraction
-        if has_tw {
-            if let Some(steps) = flatten_tw_chain(expr, &bindings) {
-                if !steps.is_empty() {
-                    if let Some((start, end)) = expr_span {
-                        // Mark this span as captured immediately so sub-expressions
-                        // of this tw chain are never independently compiled.
-                        // Without this, a chain like tw.p(4).fontFamily(runtimeFn())
-                        // would fail full extraction, then the sub-expression
-                        // tw.p(4).fontFamily would partially compile, leaving
-                        // (runtimeFn()) dangling as a broken call.
-                        tw_captured_spans.push((start, end));
-
-                        match process_tw_steps(&steps, &bindings, theme, &mut dyn_counter) {
-                            Some(tw_rules) if !tw_rules.is_empty() => {
-                                let mut class_names = vec![];
-                                for rule in tw_rules {
-                                    let l = layer;
-                                    layer += 1;
-                                    let class_name = hash::generate_hash(&rule, l);
-                                    let css_text = css::render_rule(&class_name, &rule);
-                                    class_names.push(class_name.clone());
-                                    css_rules.push((class_name, css_text, l));
-                                }
-                                let class_str = class_names.join(" ");
-                                replacements.push((start, end, format!("'{}'", class_str)));
-                                return;
+        if self.has_tw
+            && let Some(steps) = flatten_tw_chain(expr, &self.bindings)
+            && !steps.is_empty()
+            && let Some((start, end)) = expr_span
+        {
+            // Mark this span as captured immediately so sub-expressions
+            // of this tw chain are never independently compiled.
+            // Without this, a chain like tw.p(4).fontFamily(runtimeFn())
+            // would fail full extraction, then the sub-expression
+            // tw.p(4).fontFamily would partially compile, leaving
+            // (runtimeFn()) dangling as a broken call.
+            self.tw_captured_spans.push((start, end));
+
+            match process_tw_steps(&steps, &self.bindings, self.theme, &mut self.dyn_counter) {
+                Some(tw_items) if !tw_items.is_empty() => {
+                    let mut class_names = vec![];
+                    for item in tw_items {
+                        let rule = match item {
+                            TwItem::Rule(rule) => rule,
+                            TwItem::Class(class_name) => {
+                                class_names.push(class_name);
+                                continue;
                             }
-                            _ => {
-                                // Chain recognized but can't be fully compiled (runtime args).
-                                // Append .toString() so the runtime Proxy coerces to a string,
-                                // which React requires for className attributes.
-                                replacements.push((end, end, ".toString()".to_string()));
-                                needs_runtime = true;
-                            }
-                        }
+                        };
+                        let l = self.layer;
+                        self.layer += 1;
+                        let class_name = hash::generate_hash(&rule, l);
+                        let css_text = css::render_rule(&class_name, &rule);
+                        class_names.push(class_name.clone());
+                        self.css_rules.push((class_name, css_text, l, rule));
                     }
+                    let class_str = class_names.join(" ");
+                    self.replacements.push((start, end, format!("'{}'", class_str)));
+                    return Some(class_str);
+                }
+                _ => {
+                    // The runtime would read an unknown token as a raw class name
+                    if let Some(message) = unknown_tw_token(&steps, self.theme) {
+                        let span = Span::new(start, end);
+                        self.diagnostics.push(if self.strict {
+                            DiagnosticInfo::error(message, span)
+                        } else {
+                            DiagnosticInfo::warning(message, span)
+                        });
+                    }
+                    // Chain recognized but can't be fully compiled (runtime args).
+                    // Append .toString() so the runtime Proxy coerces to a string,
+                    // which React requires for className attributes.
+                    self.replacements.push((end, end, ".toString()".to_string()));
+                    self.needs_runtime = true;
                 }
             }
         }
 
         // Try cx() call extraction
-        if has_cx {
-            if let Expression::CallExpression(call) = expr {
-                if is_cx_call(call, &bindings) {
-                    match try_extract_cx(call, &bindings, &mut layer, theme, &mut dyn_counter) {
-                        Some(ExtractedCx::Static(class_str, rules)) => {
-                            let span = call.span;
-                            replacements.push((span.start, span.end, format!("'{}'", class_str)));
-                            css_rules.extend(rules);
-                        }
-                        Some(ExtractedCx::Dynamic(class_str, rules, dyn_bindings)) => {
-                            has_dynamic = true;
-                            let span = call.span;
-                            let bindings_obj = format_bindings_object(&dyn_bindings);
-                            replacements.push((
-                                span.start,
-                                span.end,
-                                format!("__twcDynamic('{}', {})", class_str, bindings_obj),
-                            ));
-                            css_rules.extend(rules);
-                        }
-                        None => {
-                            if strict && !has_dynamic_import {
-                                let span = call.span;
-                                diagnostics.push(DiagnosticInfo {
-                                    message: format!(
-                                        "cx() call could not be statically evaluated. Wrap runtime values with dynamic() or disable strict mode."
-                                    ),
-                                    line: span.start,
-                                    column: 0,
-                                    severity: "error".to_string(),
-                                });
-                            }
-                        }
+        if self.has_cx
+            && let Expression::CallExpression(call) = expr
+            && is_cx_call(call, &self.bindings)
+        {
+            match try_extract_cx(call, self.source, &self.bindings, &mut self.layer, self.theme, &mut self.dyn_counter) {
+                Some(extracted) if extracted.dynamic_bindings.is_empty() => {
+                    let span = call.span;
+                    self.replacements.push((span.start, span.end, extracted.class_expression()));
+                    self.css_rules.extend(extracted.rules.iter().cloned());
+                    return extracted.static_classes();
+                }
+                Some(extracted) => {
+                    self.has_dynamic = true;
+                    let span = call.span;
+                    let bindings_obj = format_bindings_object(&extracted.dynamic_bindings);
+                    self.replacements.push((
+                        span.start,
+                        span.end,
+                        format!("__twcDynamic({}, {})", extracted.class_expression(), bindings_obj),
+                    ));
+                    self.css_rules.extend(extracted.rules);
+                }
+                None => {
+                    if self.strict && !self.has_dynamic_import {
+                        self.diagnostics.push(DiagnosticInfo::error(
+                            "cx() call could not be statically evaluated. Wrap runtime values with dynamic() or disable strict mode.",
+                            call.span,
+                        ));
                     }
                 }
             }
         }
-    });
 
-    // Phase 3: Apply replacements (reverse order to preserve spans)
-    let mut code = source.to_string();
-    replacements.sort_by(|a, b| b.0.cmp(&a.0));
-    for (start, end, replacement) in &replacements {
-        code.replace_range(*start as usize..*end as usize, replacement);
+        None
     }
 
-    // Phase 4: Inject appropriate imports
-    if has_dynamic {
-        if !code.contains("typewritingclass/runtime") {
-            code = format!("import {{ __twcDynamic }} from 'typewritingclass/runtime';\n{}", code);
+    /// Bind a top-level constant once its initializer has been reached, so
+    /// later references are inlined. `class_str` is the compiled initializer
+    /// when it was a tw chain or cx() call.
+    fn bind_constant(&mut self, constant: &LocalConst, class_str: Option<String>) {
+        let binding = match class_str {
+            Some(class_str) => Binding::ClassNames(class_str),
+            None => match evaluate_constant(constant.init, &self.bindings, self.theme) {
+                Some(binding) => binding,
+                None => return,
+            },
+        };
+
+        if !constant.reassignments.is_empty() {
+            for span in &constant.reassignments {
+                self.diagnostics.push(DiagnosticInfo::error(
+                    format!(
+                        "'{}' is a constant and cannot be reassigned. Styles that use it are evaluated at runtime.",
+                        constant.name
+                    ),
+                    *span,
+                ));
+            }
+            return;
+        }
+
+        let escapes = escape_spans(constant, &binding, &self.bindings);
+        if !escapes.is_empty() {
+            for span in escapes {
+                self.diagnostics.push(DiagnosticInfo::warning(
+                    format!(
+                        "'{}' may be modified here, so styles that use it are evaluated at runtime.",
+                        constant.name
+                    ),
+                    span,
+                ));
+            }
+            return;
         }
+
+        self.bindings.insert(constant.name.to_string(), binding);
     }
+}
 
-    let total_extractable = count_cx_calls(program, &bindings) + tw_captured_spans.len();
-    if !has_dynamic && !needs_runtime && replacements.len() == total_extractable {
-        // All calls were statically extracted — no runtime needed
+/// Evaluate a constant's initializer: an alias of another binding, a literal
+/// or theme value, a single style rule, or a tw chain / cx() call kept as
+/// rules. Anything involving dynamic() is left to the runtime.
+fn evaluate_constant<'a>(
+    init: &'a Expression<'a>,
+    bindings: &HashMap<String, Binding>,
+    theme: &ThemeData,
+) -> Option<Binding> {
+    if let Expression::Identifier(id) = init {
+        return bindings.get(id.name.as_str()).cloned();
+    }
+    // A scratch counter keeps the file's dynamic() ids unaffected
+    let mut scratch = DynCounter::new();
+    if let Some(value) = evaluate_value(init, bindings, theme, &mut scratch) {
+        return match value {
+            Value::Dynamic(..) => None,
+            value => Some(Binding::Const(value)),
+        };
+    }
+
+    if let Some(modifier) = evaluate_modifier(init, bindings, theme) {
+        return Some(Binding::Modifier(modifier));
+    }
+
+    let rules = if let Some(steps) = flatten_tw_chain(init, bindings).filter(|s| !s.is_empty()) {
+        let mut rules = vec![];
+        for item in process_tw_steps(&steps, bindings, theme, &mut scratch)? {
+            match item {
+                TwItem::Rule(rule) => rules.push(rule),
+                TwItem::Class(_) => return None,
+            }
+        }
+        rules
     } else {
-        code = prepend_inject(&code);
+        let Expression::CallExpression(call) = init else {
+            return None;
+        };
+        if !is_cx_call(call, bindings) {
+            return match evaluate_cx_arg(init, bindings, theme, &mut scratch)? {
+                CxArg::Rule(rule) if rule.dynamic_bindings.is_empty() => Some(Binding::Rule(rule)),
+                _ => None,
+            };
+        }
+        let mut rules = vec![];
+        for arg in &call.arguments {
+            match evaluate_cx_arg(arg.as_expression()?, bindings, theme, &mut scratch)? {
+                CxArg::Rule(rule) => rules.push(rule),
+                CxArg::Rules(more) => rules.extend(more),
+                CxArg::ClassName(_) => return None,
+            }
+        }
+        rules
+    };
+    if rules.is_empty() || rules.iter().any(|r| !r.dynamic_bindings.is_empty()) {
+        return None;
+    }
+    Some(Binding::Styles(rules))
+}
+
+/// Places where a constant's value may be modified. Only style objects can
+/// be changed behind our back; strings and numbers are immutable.
+fn escape_spans(constant: &LocalConst, binding: &Binding, bindings: &HashMap<String, Binding>) -> Vec<Span> {
+    if !matches!(binding, Binding::Rule(_) | Binding::Styles(_)) {
+        return vec![];
+    }
+    constant
+        .escapes
+        .iter()
+        .filter_map(|escape| match escape {
+            Escape::Mutated(span) => Some(*span),
+            Escape::Argument { span, callee } => (!is_style_callee(callee, bindings)).then_some(*span),
+        })
+        .collect()
+}
+
+/// Evaluate the exported constants of an imported module. Only values the
+/// compiler can inline are returned; everything else stays a runtime import.
+pub fn evaluate_module(
+    source: &str,
+    filename: &str,
+    theme: &ThemeData,
+    loader: &mut ModuleLoader,
+) -> HashMap<String, Binding> {
+    let allocator = Allocator::default();
+    let source_type = SourceType::from_path(filename).unwrap_or_default();
+    let ret = Parser::new(&allocator, source, source_type).parse();
+    if ret.panicked || !ret.errors.is_empty() {
+        return HashMap::new();
+    }
+    let program = &ret.program;
+
+    let mut bindings = collect_imports(program, filename, theme, Some(&mut *loader));
+    let analyzed = scope::analyze(program);
+    let mut constants: Vec<&LocalConst> = analyzed.values().collect();
+    constants.sort_by_key(|c| c.init.span().start);
+    for constant in constants {
+        if !constant.reassignments.is_empty() {
+            continue;
+        }
+        let Some(binding) = evaluate_constant(constant.init, &bindings, theme) else {
+            continue;
+        };
+        if escape_spans(constant, &binding, &bindings).is_empty() {
+            bindings.insert(constant.name.to_string(), binding);
+        }
     }
 
+    let mut exports = HashMap::new();
+    for stmt in &program.body {
+        match stmt {
+            Statement::ExportNamedDeclaration(export) => {
+                if let Some(Declaration::VariableDeclaration(decl)) = &export.declaration {
+                    for declarator in &decl.declarations {
+                        if let BindingPattern::BindingIdentifier(id) = &declarator.id
+                            && let Some(binding) = bindings.get(id.name.as_str())
+                        {
+                            exports.insert(id.name.to_string(), binding.clone());
+                        }
+                    }
+                }
+                // `export { a as b }` and `export { a } from './other'`
+                let reexported = match &export.source {
+                    Some(src) => match loader.exports(src.value.as_str(), filename, theme) {
+                        Some(other) => other,
+                        None => continue,
+                    },
+                    None => HashMap::new(),
+                };
+                for spec in &export.specifiers {
+                    let local = spec.local.name();
+                    let binding = if export.source.is_some() {
+                        reexported.get(local.as_str())
+                    } else {
+                        bindings.get(local.as_str())
+                    };
+                    if let Some(binding) = binding {
+                        exports.insert(spec.exported.name().to_string(), binding.clone());
+                    }
+                }
+            }
+            Statement::ExportAllDeclaration(export) if export.exported.is_none() => {
+                if let Some(other) = loader.exports(export.source.value.as_str(), filename, theme) {
+                    for (name, binding) in other {
+                        if name != "default" {
+                            exports.entry(name).or_insert(binding);
+                        }
+                    }
+                }
+            }
+            Statement::ExportDefaultDeclaration(export) => {
+                if let Some(expr) = export.declaration.as_expression()
+                    && let Some(binding) = evaluate_constant(expr, &bindings, theme)
+                {
+                    exports.insert("default".to_string(), binding);
+                }
+            }
+            _ => {}
+        }
+    }
+    exports
+}
+
+/// Whether passing a value to `callee` keeps it within typewritingclass,
+/// which never mutates its arguments
+fn is_style_callee(callee: &Expression, bindings: &HashMap<String, Binding>) -> bool {
+    match callee {
+        Expression::Identifier(id) => bindings.contains_key(id.name.as_str()),
+        Expression::CallExpression(call) => is_style_callee(&call.callee, bindings),
+        Expression::StaticMemberExpression(member) => is_style_callee(&member.object, bindings),
+        _ => false,
+    }
+}
+
+/// Result for a file that is returned as-is (nothing to compile, or unparseable)
+fn unchanged(
+    source: &str,
+    filename: &str,
+    layer_offset: u32,
+    mut diagnostics: Vec<DiagnosticInfo>,
+    options: &ExtractOptions,
+) -> TransformResult {
+    let (code, map) = render_output(&SplicedSource::new(source, &[]), filename, options);
+    diagnostics::locate(&mut diagnostics, source, options.code_frame);
     TransformResult {
         code,
-        css_rules,
-        next_layer: layer,
-        has_dynamic,
+        map,
+        css_rules: vec![],
+        next_layer: layer_offset,
+        has_dynamic: false,
         diagnostics,
+        parsed: true,
     }
 }
 
@@ -341,25 +690,40 @@ fn format_bindings_object(bindings: &[(String, String)]) -> String {
 fn count_cx_calls(program: &Program, bindings: &HashMap<String, Binding>) -> usize {
     let mut count = 0;
     visit_expressions(program, &mut |expr| {
-        if let Expression::CallExpression(call) = expr {
-            if is_cx_call(call, bindings) {
-                count += 1;
-            }
+        if let Expression::CallExpression(call) = expr
+            && is_cx_call(call, bindings)
+        {
+            count += 1;
         }
     });
     count
 }
 
-fn prepend_inject(source: &str) -> String {
-    if source.contains("typewritingclass/inject") {
-        source.to_string()
-    } else {
-        format!("import 'typewritingclass/inject';\n{}", source)
+fn prepend_inject(spliced: &mut SplicedSource, source: &str) {
+    if !source.contains("typewritingclass/inject") {
+        spliced.prepend("import 'typewritingclass/inject';\n".to_string());
     }
 }
 
+/// Final code plus its source map (when requested)
+fn render_output(
+    spliced: &SplicedSource,
+    filename: &str,
+    options: &ExtractOptions,
+) -> (String, Option<String>) {
+    let map = options
+        .source_map
+        .then(|| spliced.source_map(filename, options.sources_content));
+    (spliced.code(), map)
+}
+
 /// Collect all imports from typewritingclass modules and resolve them to Bindings
-fn collect_imports(program: &Program, theme: &ThemeData) -> HashMap<String, Binding> {
+fn collect_imports(
+    program: &Program,
+    filename: &str,
+    theme: &ThemeData,
+    mut loader: Option<&mut ModuleLoader>,
+) -> HashMap<String, Binding> {
     let mut bindings = HashMap::new();
 
     for stmt in &program.body {
@@ -368,15 +732,32 @@ fn collect_imports(program: &Program, theme: &ThemeData) -> HashMap<String, Bind
         };
         let source_value = import.source.value.as_str();
 
-        // Only process typewritingclass imports
-        if !source_value.starts_with("typewritingclass") {
-            continue;
-        }
-
         let Some(specifiers) = &import.specifiers else {
             continue;
         };
 
+        // Other modules are followed only when a loader is configured, and
+        // contribute the exported constants it could evaluate
+        if !source_value.starts_with("typewritingclass") {
+            let Some(loader) = loader.as_deref_mut() else {
+                continue;
+            };
+            let Some(exports) = loader.exports(source_value, filename, theme) else {
+                continue;
+            };
+            for spec in specifiers {
+                let imported_name = match spec {
+                    ImportDeclarationSpecifier::ImportSpecifier(named) => named.imported.name(),
+                    ImportDeclarationSpecifier::ImportDefaultSpecifier(_) => "default".into(),
+                    ImportDeclarationSpecifier::ImportNamespaceSpecifier(_) => continue,
+                };
+                if let Some(binding) = exports.get(imported_name.as_str()) {
+                    bindings.insert(spec.local().name.to_string(), binding.clone());
+                }
+            }
+            continue;
+        }
+
         for spec in specifiers {
             match spec {
                 ImportDeclarationSpecifier::ImportSpecifier(named) => {
@@ -427,8 +808,14 @@ fn resolve_import(source: &str, name: &str, theme: &ThemeData) -> Option<Binding
                 return Some(Binding::Dynamic);
             }
             // Modifiers
-            if modifiers::is_modifier(name) {
-                return Some(Binding::Modifier(name.to_string()));
+            if modifiers::is_modifier(name) || modifiers::is_breakpoint_modifier(name, theme) {
+                return Some(Binding::Modifier(Modifier::Named(name.to_string())));
+            }
+            if modifiers::is_modifier_factory(name) {
+                return Some(Binding::ModifierFactory(name.to_string()));
+            }
+            if modifiers::is_marker(name) {
+                return Some(Binding::Marker(name.to_string()));
             }
             // Utilities
             if UTILITY_NAMES.contains(&name) {
@@ -486,66 +873,266 @@ fn resolve_import(source: &str, name: &str, theme: &ThemeData) -> Option<Binding
 
 /// Check if a call expression is a cx() call
 fn is_cx_call(call: &CallExpression, bindings: &HashMap<String, Binding>) -> bool {
-    if let Expression::Identifier(id) = &call.callee {
-        if let Some(Binding::Cx) = bindings.get(id.name.as_str()) {
-            return true;
-        }
+    if let Expression::Identifier(id) = &call.callee
+        && let Some(Binding::Cx) = bindings.get(id.name.as_str())
+    {
+        return true;
     }
     false
 }
 
-enum ExtractedCx {
-    /// All arguments were static — just class names
-    Static(String, Vec<(String, String, u32)>),
-    /// Some arguments had dynamic() — class names + dynamic bindings
-    Dynamic(String, Vec<(String, String, u32)>, Vec<(String, String)>),
+/// A statically extracted cx() call
+struct ExtractedCx {
+    /// Classes contributed by each argument
+    classes: Vec<ClassExpr>,
+    rules: Vec<CssRule>,
+    /// CSS custom properties set from dynamic() values
+    dynamic_bindings: Vec<(String, String)>,
+}
+
+/// The classes a cx() argument contributes
+enum ClassExpr {
+    /// Always the same classes (possibly none)
+    Static(String),
+    /// `test ? consequent : alternate`, with the test kept as source text
+    Conditional {
+        test: String,
+        consequent: Box<ClassExpr>,
+        alternate: Box<ClassExpr>,
+    },
+    /// Several inputs, e.g. an array or object argument
+    List(Vec<ClassExpr>),
+}
+
+impl ExtractedCx {
+    /// The class string, when no argument is conditional
+    fn static_classes(&self) -> Option<String> {
+        let mut names = vec![];
+        for class in flatten_classes(&self.classes) {
+            match class {
+                ClassExpr::Static(s) if s.is_empty() => {}
+                ClassExpr::Static(s) => names.push(s.as_str()),
+                _ => return None,
+            }
+        }
+        Some(names.join(" "))
+    }
+
+    /// A JS expression evaluating to the class string. Static classes are
+    /// merged into one literal; conditional inputs pick between
+    /// precomputed strings.
+    fn class_expression(&self) -> String {
+        render_classes(&self.classes, false)
+    }
+}
+
+/// `classes` with nested lists expanded in place
+fn flatten_classes(classes: &[ClassExpr]) -> Vec<&ClassExpr> {
+    let mut flat = vec![];
+    for class in classes {
+        match class {
+            ClassExpr::List(items) => flat.extend(flatten_classes(items)),
+            _ => flat.push(class),
+        }
+    }
+    flat
+}
+
+/// Render classes as a JS string expression. `any` is whether output before
+/// them may already hold a class, in which case each one gets a leading space.
+fn render_classes(classes: &[ClassExpr], mut any: bool) -> String {
+    let mut pieces = vec![];
+    let mut pending = String::new();
+    for class in flatten_classes(classes) {
+        match class {
+            ClassExpr::Static(s) if s.is_empty() => {}
+            ClassExpr::Static(s) => {
+                if any {
+                    pending.push(' ');
+                }
+                pending.push_str(s);
+                any = true;
+            }
+            ClassExpr::Conditional { test, consequent, alternate } => {
+                if !pending.is_empty() {
+                    pieces.push(js_string(&std::mem::take(&mut pending)));
+                }
+                pieces.push(format!(
+                    "({} ? {} : {})",
+                    test,
+                    render_classes(std::slice::from_ref(consequent), any),
+                    render_classes(std::slice::from_ref(alternate), any)
+                ));
+                any = true;
+            }
+            ClassExpr::List(_) => unreachable!("lists are flattened"),
+        }
+    }
+    if !pending.is_empty() || pieces.is_empty() {
+        pieces.push(js_string(&pending));
+    }
+    pieces.join(" + ")
+}
+
+/// Quote a string as a single-quoted JS literal
+fn js_string(s: &str) -> String {
+    format!("'{}'", s.replace('\\', "\\\\").replace('\'', "\\'"))
 }
 
 /// Try to statically extract a cx() call.
 /// Returns None if any argument can't be evaluated (falls back to runtime).
 fn try_extract_cx(
     call: &CallExpression,
+    source: &str,
     bindings: &HashMap<String, Binding>,
     layer: &mut u32,
     theme: &ThemeData,
     dyn_counter: &mut DynCounter,
 ) -> Option<ExtractedCx> {
-    let mut class_names = vec![];
-    let mut rules = vec![];
-    let mut all_dynamic_bindings: Vec<(String, String)> = vec![];
-
+    let mut extracted = ExtractedCx {
+        classes: vec![],
+        rules: vec![],
+        dynamic_bindings: vec![],
+    };
     for arg in &call.arguments {
         let expr = arg.as_expression()?;
-        match evaluate_cx_arg(expr, bindings, theme, dyn_counter)? {
-            CxArg::Rule(rule) => {
+        let class = evaluate_class_expr(expr, source, bindings, layer, theme, dyn_counter, &mut extracted)?;
+        extracted.classes.push(class);
+    }
+    Some(extracted)
+}
+
+/// Evaluate a cx() argument to the classes it contributes. Accepts the
+/// clsx input forms: ternaries and `&&` keep their condition and compile each
+/// branch, arrays nest, object keys are included when their value is truthy,
+/// non-zero numbers are class names and other literals contribute nothing.
+fn evaluate_class_expr(
+    expr: &Expression,
+    source: &str,
+    bindings: &HashMap<String, Binding>,
+    layer: &mut u32,
+    theme: &ThemeData,
+    dyn_counter: &mut DynCounter,
+    extracted: &mut ExtractedCx,
+) -> Option<ClassExpr> {
+    let mut eval = |e: &Expression, extracted: &mut ExtractedCx| {
+        evaluate_class_expr(e, source, bindings, layer, theme, dyn_counter, extracted)
+    };
+    match expr {
+        Expression::ParenthesizedExpression(paren) => eval(&paren.expression, extracted),
+        Expression::ConditionalExpression(cond) => {
+            let consequent = eval(&cond.consequent, extracted)?;
+            let alternate = eval(&cond.alternate, extracted)?;
+            Some(conditional(&cond.test, source, bindings, consequent, alternate))
+        }
+        Expression::LogicalExpression(logical) if logical.operator == LogicalOperator::And => {
+            let consequent = eval(&logical.right, extracted)?;
+            Some(conditional(&logical.left, source, bindings, consequent, ClassExpr::Static(String::new())))
+        }
+        Expression::ArrayExpression(arr) => {
+            let mut items = vec![];
+            for elem in &arr.elements {
+                match elem {
+                    ArrayExpressionElement::Elision(_) => {}
+                    ArrayExpressionElement::SpreadElement(_) => return None,
+                    _ => items.push(eval(elem.as_expression()?, extracted)?),
+                }
+            }
+            Some(ClassExpr::List(items))
+        }
+        Expression::ObjectExpression(obj) => {
+            let mut items = vec![];
+            for prop in &obj.properties {
+                let ObjectPropertyKind::ObjectProperty(p) = prop else {
+                    return None; // SpreadProperty
+                };
+                if p.kind != PropertyKind::Init || p.method {
+                    return None;
+                }
+                let key = match &p.key {
+                    _ if p.computed => eval(p.key.as_expression()?, extracted)?,
+                    PropertyKey::StaticIdentifier(id) => ClassExpr::Static(id.name.to_string()),
+                    PropertyKey::StringLiteral(s) => ClassExpr::Static(s.value.to_string()),
+                    _ => return None,
+                };
+                items.push(conditional(&p.value, source, bindings, key, ClassExpr::Static(String::new())));
+            }
+            Some(ClassExpr::List(items))
+        }
+        Expression::NumericLiteral(n) if literal_truthiness(expr, bindings) == Some(true) => {
+            // clsx(1) is "1"
+            Some(ClassExpr::Static(fold::to_js_string(&Value::Num(n.value))?))
+        }
+        _ if literal_truthiness(expr, bindings).is_some() && !matches!(expr, Expression::StringLiteral(_)) => {
+            // true, false, null, undefined, 0: not class names
+            Some(ClassExpr::Static(String::new()))
+        }
+        _ => {
+            let arg_rules = match evaluate_cx_arg(expr, bindings, theme, dyn_counter)? {
+                CxArg::Rule(rule) => vec![rule],
+                CxArg::Rules(arg_rules) => arg_rules,
+                CxArg::ClassName(s) => return Some(ClassExpr::Static(s)),
+            };
+            let mut class_names = vec![];
+            for rule in arg_rules {
                 let l = *layer;
                 *layer += 1;
                 // Collect dynamic bindings before generating hash/css
                 for (var_name, expr_text) in &rule.dynamic_bindings {
-                    all_dynamic_bindings.push((var_name.clone(), expr_text.clone()));
+                    extracted.dynamic_bindings.push((var_name.clone(), expr_text.clone()));
                 }
                 let class_name = hash::generate_hash(&rule, l);
                 let css_text = css::render_rule(&class_name, &rule);
                 class_names.push(class_name.clone());
-                rules.push((class_name, css_text, l));
-            }
-            CxArg::ClassName(s) => {
-                class_names.push(s);
+                extracted.rules.push((class_name, css_text, l, rule));
             }
+            Some(ClassExpr::Static(class_names.join(" ")))
         }
     }
+}
 
-    let class_str = class_names.join(" ");
+/// `test ? consequent : alternate`, resolved now when the test is a literal
+fn conditional(
+    test: &Expression,
+    source: &str,
+    bindings: &HashMap<String, Binding>,
+    consequent: ClassExpr,
+    alternate: ClassExpr,
+) -> ClassExpr {
+    match literal_truthiness(test, bindings) {
+        Some(true) => consequent,
+        Some(false) => alternate,
+        None => ClassExpr::Conditional {
+            test: source_text(source, test.span()),
+            consequent: Box::new(consequent),
+            alternate: Box::new(alternate),
+        },
+    }
+}
 
-    if all_dynamic_bindings.is_empty() {
-        Some(ExtractedCx::Static(class_str, rules))
-    } else {
-        Some(ExtractedCx::Dynamic(class_str, rules, all_dynamic_bindings))
+/// Truthiness of a literal (`true`, `0`, `''`, `null`, `undefined`...)
+fn literal_truthiness(expr: &Expression, bindings: &HashMap<String, Binding>) -> Option<bool> {
+    match expr {
+        Expression::BooleanLiteral(b) => Some(b.value),
+        Expression::NullLiteral(_) => Some(false),
+        Expression::NumericLiteral(n) => Some(n.value != 0.0 && !n.value.is_nan()),
+        Expression::StringLiteral(s) => Some(!s.value.is_empty()),
+        Expression::Identifier(id) if id.name == "undefined" && !bindings.contains_key("undefined") => {
+            Some(false)
+        }
+        _ => None,
     }
 }
 
+/// The original source text of a span
+fn source_text(source: &str, span: Span) -> String {
+    source[span.start as usize..span.end as usize].to_string()
+}
+
 enum CxArg {
     Rule(StyleRule),
+    /// Several rules that each get their own class (an imported tw chain)
+    Rules(Vec<StyleRule>),
     ClassName(String),
 }
 
@@ -559,6 +1146,15 @@ fn evaluate_cx_arg(
     match expr {
         Expression::StringLiteral(s) => Some(CxArg::ClassName(s.value.to_string())),
 
+        // A file-local constant
+        Expression::Identifier(id) => match bindings.get(id.name.as_str())? {
+            Binding::Rule(rule) => Some(CxArg::Rule(rule.clone())),
+            Binding::Styles(rules) => Some(CxArg::Rules(rules.clone())),
+            Binding::ClassNames(class_str) => Some(CxArg::ClassName(class_str.clone())),
+            Binding::Const(Value::Str(class_str)) => Some(CxArg::ClassName(class_str.clone())),
+            _ => None,
+        },
+
         Expression::CallExpression(call) => {
             // Could be: utility call, when(mod)(rules), css({...})
             evaluate_call_as_cx_arg(call, bindings, theme, dyn_counter)
@@ -582,17 +1178,14 @@ fn evaluate_call_as_cx_arg(
                 Binding::Utility(name) => {
                     let args = evaluate_call_args(&call.arguments, bindings, theme, dyn_counter)?;
                     // Special handling for text() with TextSize tokens
-                    if name == "text" && call.arguments.len() == 1 {
-                        if let Some(expr) = call.arguments[0].as_expression() {
-                            if let Expression::Identifier(arg_id) = expr {
-                                if let Some(Binding::TextSize(fs, lh)) =
-                                    bindings.get(arg_id.name.as_str())
-                                {
-                                    let rule = utilities::evaluate_text_with_size(fs, lh);
-                                    return Some(CxArg::Rule(rule));
-                                }
-                            }
-                        }
+                    if name == "text"
+                        && call.arguments.len() == 1
+                        && let Some(expr) = call.arguments[0].as_expression()
+                        && let Expression::Identifier(arg_id) = expr
+                        && let Some(Binding::TextSize(fs, lh)) = bindings.get(arg_id.name.as_str())
+                    {
+                        let rule = utilities::evaluate_text_with_size(fs, lh);
+                        return Some(CxArg::Rule(rule));
                     }
                     let rule = utilities::evaluate(name, &args, theme)?;
                     Some(CxArg::Rule(rule))
@@ -602,14 +1195,26 @@ fn evaluate_call_as_cx_arg(
                     let rule = evaluate_css_call(call, bindings)?;
                     Some(CxArg::Rule(rule))
                 }
+                Binding::Marker(name) => {
+                    // namedGroup('sidebar') → 'group/sidebar'
+                    let args = evaluate_static_args(&call.arguments, bindings, theme)?;
+                    Some(CxArg::ClassName(modifiers::marker_class(name, &args)?))
+                }
                 _ => None,
             }
         }
-        // when(modifier)(rules) pattern: callee is a CallExpression
+        // when(modifier)(rules) or layer('name')(rules) pattern: callee is a CallExpression
         Expression::CallExpression(inner_call) => {
             if let Expression::Identifier(id) = &inner_call.callee {
-                if let Some(Binding::When) = bindings.get(id.name.as_str()) {
-                    return evaluate_when_call(inner_call, call, bindings, theme, dyn_counter);
+                match bindings.get(id.name.as_str()) {
+                    Some(Binding::When) => {
+                        return evaluate_when_call(inner_call, call, bindings, theme, dyn_counter);
+                    }
+                    Some(Binding::ModifierFactory(name)) if name == "layer" => {
+                        let modifier = evaluate_modifier(&call.callee, bindings, theme)?;
+                        return evaluate_modified_rules(vec![modifier], call, bindings, theme, dyn_counter);
+                    }
+                    _ => {}
                 }
             }
             None
@@ -626,40 +1231,75 @@ fn evaluate_when_call(
     theme: &ThemeData,
     dyn_counter: &mut DynCounter,
 ) -> Option<CxArg> {
-    // Collect modifier names
-    let mut modifier_names = vec![];
+    // Collect modifiers
+    let mut modifiers = vec![];
     for arg in &when_call.arguments {
-        let expr = arg.as_expression()?;
-        if let Expression::Identifier(id) = expr {
-            if let Some(Binding::Modifier(name)) = bindings.get(id.name.as_str()) {
-                modifier_names.push(name.clone());
-            } else {
-                return None;
-            }
-        } else {
-            return None;
-        }
+        modifiers.push(evaluate_modifier(arg.as_expression()?, bindings, theme)?);
     }
+    evaluate_modified_rules(modifiers, outer_call, bindings, theme, dyn_counter)
+}
 
+/// Merge the rules passed to `outer_call` and apply `modifiers` to them
+fn evaluate_modified_rules(
+    modifiers: Vec<Modifier>,
+    outer_call: &CallExpression,
+    bindings: &HashMap<String, Binding>,
+    theme: &ThemeData,
+    dyn_counter: &mut DynCounter,
+) -> Option<CxArg> {
     // Evaluate the rules (outer call arguments)
     let mut style_rules = vec![];
     for arg in &outer_call.arguments {
         let expr = arg.as_expression()?;
         match evaluate_cx_arg(expr, bindings, theme, dyn_counter)? {
             CxArg::Rule(rule) => style_rules.push(rule),
-            _ => return None,
+            CxArg::Rules(rules) => style_rules.extend(rules),
+            CxArg::ClassName(_) => return None,
         }
     }
 
-    // Combine rules
-    let mut combined = StyleRule::merge(&style_rules);
+    // Combine rules, one per selector context and cascade layer
+    let mut combined = vec![];
+    for mut rule in StyleRule::merge_by_context(&style_rules) {
+        // Apply modifiers in reverse order (matching TS reduceRight)
+        for modifier in modifiers.iter().rev() {
+            rule = modifier.apply(rule, theme)?;
+        }
+        combined.push(rule);
+    }
 
-    // Apply modifiers in reverse order (matching TS reduceRight)
-    for name in modifier_names.iter().rev() {
-        combined = modifiers::apply(name, combined)?;
+    match combined.len() {
+        1 => combined.pop().map(CxArg::Rule),
+        _ => Some(CxArg::Rules(combined)),
     }
+}
 
-    Some(CxArg::Rule(combined))
+/// Resolve a modifier expression: `hover`, a factory call such as
+/// `data('state=open')` with a static argument, a named group/peer variant
+/// (`groupHover('sidebar')`), or a constant holding any of them
+fn evaluate_modifier(
+    expr: &Expression,
+    bindings: &HashMap<String, Binding>,
+    theme: &ThemeData,
+) -> Option<Modifier> {
+    match expr {
+        Expression::Identifier(id) => match bindings.get(id.name.as_str())? {
+            Binding::Modifier(modifier) => Some(modifier.clone()),
+            _ => None,
+        },
+        Expression::CallExpression(call) => {
+            let Expression::Identifier(callee) = &call.callee else {
+                return None;
+            };
+            let name = match bindings.get(callee.name.as_str())? {
+                Binding::ModifierFactory(name) | Binding::Modifier(Modifier::Named(name)) => name,
+                _ => return None,
+            };
+            let args = evaluate_static_args(&call.arguments, bindings, theme)?;
+            Some(Modifier::Factory(name.clone(), args))
+        }
+        _ => None,
+    }
 }
 
 /// Evaluate a css({ key: 'value', ... }) call
@@ -693,9 +1333,13 @@ fn evaluate_css_call(
             declarations,
             selectors: vec![],
             media_queries: vec![],
+            supports_queries: vec![],
+            container_queries: vec![],
             dynamic_bindings: vec![],
             extra_css: vec![],
             selector_template: None,
+            layer: None,
+            priority: None,
         })
     } else {
         None
@@ -717,6 +1361,19 @@ fn evaluate_call_args(
     Some(values)
 }
 
+/// Resolve call arguments that must be known at compile time (no dynamic())
+fn evaluate_static_args(
+    args: &oxc_allocator::Vec<Argument>,
+    bindings: &HashMap<String, Binding>,
+    theme: &ThemeData,
+) -> Option<Vec<Value>> {
+    let mut values = vec![];
+    for arg in args {
+        values.push(evaluate_static_value(arg.as_expression()?, bindings, theme)?);
+    }
+    Some(values)
+}
+
 /// Evaluate an expression to a Value (string, number, or dynamic)
 fn evaluate_value(
     expr: &Expression,
@@ -728,6 +1385,31 @@ fn evaluate_value(
         Expression::StringLiteral(s) => Some(Value::Str(s.value.to_string())),
         Expression::NumericLiteral(n) => Some(Value::Num(n.value)),
 
+        // Constant folding: -2, 2 * 4, '1px ' + 'solid', `${n * 4}rem`
+        Expression::ParenthesizedExpression(paren) => {
+            evaluate_value(&paren.expression, bindings, theme, dyn_counter)
+        }
+        Expression::UnaryExpression(unary) => {
+            let value = evaluate_static_value(&unary.argument, bindings, theme)?;
+            fold::unary(unary.operator, &value)
+        }
+        Expression::BinaryExpression(bin) => {
+            let left = evaluate_static_value(&bin.left, bindings, theme)?;
+            let right = evaluate_static_value(&bin.right, bindings, theme)?;
+            fold::binary(bin.operator, &left, &right)
+        }
+        Expression::TemplateLiteral(tmpl) => {
+            let mut out = String::new();
+            for (i, quasi) in tmpl.quasis.iter().enumerate() {
+                out.push_str(quasi.value.cooked.as_ref()?.as_str());
+                if let Some(expr) = tmpl.expressions.get(i) {
+                    let value = evaluate_static_value(expr, bindings, theme)?;
+                    out.push_str(&fold::to_js_string(&value)?);
+                }
+            }
+            Some(Value::Str(out))
+        }
+
         Expression::Identifier(id) => {
             match bindings.get(id.name.as_str())? {
                 Binding::ResolvedValue(val) => Some(Value::Str(val.clone())),
@@ -736,22 +1418,23 @@ fn evaluate_value(
                     // just return the fontSize as a string
                     Some(Value::Str(fs.clone()))
                 }
+                Binding::Const(value) => Some(value.clone()),
                 _ => None,
             }
         }
 
         // dynamic(expr) pattern
         Expression::CallExpression(call) => {
-            if let Expression::Identifier(callee_id) = &call.callee {
-                if let Some(Binding::Dynamic) = bindings.get(callee_id.name.as_str()) {
-                    // dynamic() call — generate a CSS custom property ID
-                    if call.arguments.len() == 1 {
-                        let inner_expr = call.arguments[0].as_expression()?;
-                        let id = dyn_counter.next_id();
-                        // Get the source text of the inner expression
-                        let expr_text = extract_source_text(inner_expr);
-                        return Some(Value::Dynamic(id, expr_text));
-                    }
+            if let Expression::Identifier(callee_id) = &call.callee
+                && let Some(Binding::Dynamic) = bindings.get(callee_id.name.as_str())
+            {
+                // dynamic() call — generate a CSS custom property ID
+                if call.arguments.len() == 1 {
+                    let inner_expr = call.arguments[0].as_expression()?;
+                    let id = dyn_counter.next_id();
+                    // Get the source text of the inner expression
+                    let expr_text = extract_source_text(inner_expr);
+                    return Some(Value::Dynamic(id, expr_text));
                 }
             }
             None
@@ -759,16 +1442,14 @@ fn evaluate_value(
 
         // blue[500] pattern — computed member expression
         Expression::ComputedMemberExpression(computed) => {
-            if let Expression::Identifier(obj_id) = &computed.object {
-                if let Some(Binding::ColorScale(color_name)) =
-                    bindings.get(obj_id.name.as_str())
-                {
-                    // The property should be a numeric literal (shade)
-                    if let Expression::NumericLiteral(n) = &computed.expression {
-                        let shade = format!("{}", n.value as u32);
-                        if let Some(hex) = theme.resolve_color(color_name, &shade) {
-                            return Some(Value::Str(hex.to_string()));
-                        }
+            if let Expression::Identifier(obj_id) = &computed.object
+                && let Some(Binding::ColorScale(color_name)) = bindings.get(obj_id.name.as_str())
+            {
+                // The property should be a numeric literal (shade)
+                if let Expression::NumericLiteral(n) = &computed.expression {
+                    let shade = format!("{}", n.value as u32);
+                    if let Some(hex) = theme.resolve_color(color_name, &shade) {
+                        return Some(Value::Str(hex.to_string()));
                     }
                 }
             }
@@ -790,6 +1471,19 @@ fn evaluate_value(
     }
 }
 
+/// Evaluate an operand of a folded expression. dynamic() can't take part,
+/// so a scratch counter keeps the file's ids untouched.
+fn evaluate_static_value(
+    expr: &Expression,
+    bindings: &HashMap<String, Binding>,
+    theme: &ThemeData,
+) -> Option<Value> {
+    match evaluate_value(expr, bindings, theme, &mut DynCounter::new())? {
+        Value::Dynamic(..) => None,
+        value => Some(value),
+    }
+}
+
 /// Extract the source text representation of an expression for use in generated code
 fn extract_source_text(expr: &Expression) -> String {
     match expr {
@@ -900,6 +1594,14 @@ enum TwStep<'a> {
     MethodCall(String, &'a oxc_allocator::Vec<'a, Argument<'a>>),
 }
 
+/// What a tw chain contributes, in chain order
+enum TwItem {
+    /// A compiled rule that gets its own class
+    Rule(StyleRule),
+    /// A raw class name (e.g., .group, .namedPeer('email'))
+    Class(String),
+}
+
 /// Try to flatten an expression into a sequence of tw chain steps.
 /// Returns None if the expression is not a tw chain.
 /// Returns Some(vec![]) if the expression is just the bare `tw` identifier.
@@ -948,7 +1650,56 @@ fn flatten_tw_chain_inner<'a>(
     }
 }
 
-/// Process flattened tw chain steps into a list of StyleRules.
+/// The modifier a tw chain step names, including breakpoints defined by the
+/// theme. After `.group('sidebar')` or `.peer('sidebar')`, `hover` names the
+/// named variant of `groupHover`.
+fn tw_modifier(name: &str, named: Option<&(String, String)>, theme: &ThemeData) -> Option<Modifier> {
+    let Some((kind, group)) = named else {
+        let known = modifiers::is_modifier(name) || modifiers::is_breakpoint_modifier(name, theme);
+        return known.then(|| Modifier::Named(name.to_string()));
+    };
+    let mut chars = name.chars();
+    let first = chars.next()?;
+    let full = format!("{}{}{}", kind, first.to_ascii_uppercase(), chars.as_str());
+    modifiers::is_modifier(&full).then(|| Modifier::Factory(full, vec![Value::Str(group.clone())]))
+}
+
+/// A token access on a theme-backed utility that names no token of the
+/// theme, e.g. `.rounded.pill`, as a message listing the valid names
+fn unknown_tw_token(steps: &[TwStep], theme: &ThemeData) -> Option<String> {
+    steps.windows(2).find_map(|pair| {
+        let TwStep::Property(utility) = &pair[0] else {
+            return None;
+        };
+        let (TwStep::Property(token) | TwStep::MethodCall(token, _)) = &pair[1];
+        if !tokens::has_token_support(utility)
+            || !UTILITY_NAMES.contains(&utility.as_str())
+            || tokens::resolve_token(utility, token, theme).is_some()
+            || is_tw_member(token, theme)
+        {
+            return None;
+        }
+        let names = tokens::token_names(utility, theme)?;
+        Some(if names.is_empty() {
+            format!("Unknown {} token '{}': the theme defines no {} tokens.", utility, token, utility)
+        } else {
+            format!("Unknown {} token '{}'. Valid tokens: {}.", utility, token, names.join(", "))
+        })
+    })
+}
+
+/// Whether a tw chain step name is a utility, modifier or accessor rather
+/// than a token
+fn is_tw_member(name: &str, theme: &ThemeData) -> bool {
+    UTILITY_NAMES.contains(&name)
+        || modifiers::is_modifier(name)
+        || modifiers::is_breakpoint_modifier(name, theme)
+        || modifiers::is_marker(name)
+        || modifiers::tw_factory(name).is_some()
+        || matches!(name, "group" | "peer" | "value" | "className" | "toString")
+}
+
+/// Process flattened tw chain steps into a list of StyleRules and raw classes.
 /// Handles modifiers (property accesses like .hover) that apply to the next utility.
 /// Supports token resolution: `.bg.blue500` resolves to `bg("blue-500")`.
 fn process_tw_steps(
@@ -956,10 +1707,12 @@ fn process_tw_steps(
     bindings: &HashMap<String, Binding>,
     theme: &ThemeData,
     dyn_counter: &mut DynCounter,
-) -> Option<Vec<StyleRule>> {
-    let mut rules: Vec<StyleRule> = Vec::new();
-    let mut pending_mods: Vec<String> = Vec::new();
+) -> Option<Vec<TwItem>> {
+    let mut items: Vec<TwItem> = Vec::new();
+    let mut pending_mods: Vec<Modifier> = Vec::new();
     let mut pending_util: Option<String> = None;
+    // (kind, name) after `.group('name')` / `.peer('name')`
+    let mut pending_group: Option<(String, String)> = None;
 
     for step in steps {
         // If we have a pending utility, try to resolve the current step as a token
@@ -969,33 +1722,33 @@ fn process_tw_steps(
                     if let Some(token_val) = tokens::resolve_token(util_name, name, theme) {
                         // Token resolved! Evaluate the utility with the token value
                         let mut rule = utilities::evaluate(util_name, &[Value::Str(token_val)], theme)?;
-                        for mod_name in pending_mods.iter().rev() {
-                            rule = modifiers::apply(mod_name, rule)?;
+                        for modifier in pending_mods.iter().rev() {
+                            rule = modifier.apply(rule, theme)?;
                         }
                         pending_mods.clear();
                         pending_util = None;
-                        rules.push(rule);
+                        items.push(TwItem::Rule(rule));
                         continue;
                     }
                     // Not a token — flush pending util with no args, fall through
                 }
                 TwStep::MethodCall(name, args) => {
-                    if tokens::supports_opacity(util_name) {
-                        if let Some(token_val) = tokens::resolve_token(util_name, name, theme) {
-                            // Color token with opacity: .bg.blue500(50)
-                            let values = evaluate_call_args(args, bindings, theme, dyn_counter)?;
-                            if let Some(opacity) = values.first().and_then(|v| v.as_num()) {
-                                if let Some(resolved) = tokens::resolve_color_with_opacity(&token_val, opacity, theme) {
-                                    let mut rule = utilities::evaluate(util_name, &[Value::Str(resolved)], theme)?;
-                                    for mod_name in pending_mods.iter().rev() {
-                                        rule = modifiers::apply(mod_name, rule)?;
-                                    }
-                                    pending_mods.clear();
-                                    pending_util = None;
-                                    rules.push(rule);
-                                    continue;
-                                }
+                    if tokens::supports_opacity(util_name)
+                        && let Some(token_val) = tokens::resolve_token(util_name, name, theme)
+                    {
+                        // Color token with opacity: .bg.blue500(50)
+                        let values = evaluate_call_args(args, bindings, theme, dyn_counter)?;
+                        if let Some(opacity) = values.first().and_then(|v| v.as_num())
+                            && let Some(resolved) = tokens::resolve_color_with_opacity(&token_val, opacity, theme)
+                        {
+                            let mut rule = utilities::evaluate(util_name, &[Value::Str(resolved)], theme)?;
+                            for modifier in pending_mods.iter().rev() {
+                                rule = modifier.apply(rule, theme)?;
                             }
+                            pending_mods.clear();
+                            pending_util = None;
+                            items.push(TwItem::Rule(rule));
+                            continue;
                         }
                     }
                     // Not a token call — flush pending util, fall through
@@ -1004,20 +1757,27 @@ fn process_tw_steps(
             // Flush pending util with no args
             let flushed_name = pending_util.take().unwrap();
             if let Some(mut rule) = utilities::evaluate(&flushed_name, &[], theme) {
-                for mod_name in pending_mods.iter().rev() {
-                    rule = modifiers::apply(mod_name, rule)?;
+                for modifier in pending_mods.iter().rev() {
+                    rule = modifier.apply(rule, theme)?;
                 }
                 pending_mods.clear();
-                rules.push(rule);
+                items.push(TwItem::Rule(rule));
             }
             // Fall through to process current step normally
         }
 
         match step {
             TwStep::Property(name) => {
-                if modifiers::is_modifier(name) {
+                let named = pending_group.take();
+                if let Some(modifier) = tw_modifier(name, named.as_ref(), theme) {
                     // Modifier — accumulate to apply to next utility
-                    pending_mods.push(name.clone());
+                    pending_mods.push(modifier);
+                } else if named.is_some() {
+                    // Not a group/peer state
+                    return None;
+                } else if name == "group" || name == "peer" {
+                    // Raw class name marking the element
+                    items.push(TwItem::Class(name.clone()));
                 } else if tokens::has_token_support(name) && UTILITY_NAMES.contains(&name.as_str()) {
                     // Utility with token support — defer to see if next step is a token
                     pending_util = Some(name.clone());
@@ -1025,11 +1785,11 @@ fn process_tw_steps(
                     // Valueless utility (like flex, absolute, relative, etc.)
                     if let Some(mut rule) = utilities::evaluate(name, &[], theme) {
                         // Apply pending modifiers (right-to-left, matching TS reduceRight)
-                        for mod_name in pending_mods.iter().rev() {
-                            rule = modifiers::apply(mod_name, rule)?;
+                        for modifier in pending_mods.iter().rev() {
+                            rule = modifier.apply(rule, theme)?;
                         }
                         pending_mods.clear();
-                        rules.push(rule);
+                        items.push(TwItem::Rule(rule));
                     } else {
                         // Utility is recognized but not compilable — bail to runtime
                         // to avoid silently dropping styles.
@@ -1043,49 +1803,82 @@ fn process_tw_steps(
                 }
             }
             TwStep::MethodCall(name, args) => {
-                if name == "toString" && args.is_empty() {
+                let named = pending_group.take();
+                if let Some((kind, group)) = &named
+                    && name == "has"
+                {
+                    // .group('sidebar').has('img') → groupHas('img', 'sidebar')
+                    let mut values = evaluate_static_args(args, bindings, theme)?;
+                    values.push(Value::Str(group.clone()));
+                    pending_mods.push(Modifier::Factory(format!("{}Has", kind), values));
+                    continue;
+                }
+                if name == "toString" && args.is_empty() && named.is_none() {
                     // Terminal .toString() — no-op
                     continue;
                 }
-                if UTILITY_NAMES.contains(&name.as_str()) {
+                if named.is_none() && UTILITY_NAMES.contains(&name.as_str()) {
                     let values = evaluate_call_args(args, bindings, theme, dyn_counter)?;
                     if let Some(mut rule) = utilities::evaluate(name, &values, theme) {
                         // Apply pending modifiers
-                        for mod_name in pending_mods.iter().rev() {
-                            rule = modifiers::apply(mod_name, rule)?;
+                        for modifier in pending_mods.iter().rev() {
+                            rule = modifier.apply(rule, theme)?;
                         }
                         pending_mods.clear();
-                        rules.push(rule);
+                        items.push(TwItem::Rule(rule));
                     } else {
                         return None;
                     }
-                } else if modifiers::is_modifier(name) {
+                } else if let Some(modifier) = tw_modifier(name, named.as_ref(), theme) {
                     // Modifier as method call: .hover(tw.bg('red').shadow('md'))
                     // Try to process the inner tw chain argument
                     let mut processed = None;
-                    if args.len() == 1 {
-                        if let Some(inner_expr) = args[0].as_expression() {
-                            if let Some(inner_steps) = flatten_tw_chain(inner_expr, bindings) {
-                                if let Some(inner_rules) = process_tw_steps(&inner_steps, bindings, theme, dyn_counter) {
-                                    if !inner_rules.is_empty() {
-                                        processed = Some(StyleRule::merge(&inner_rules));
-                                    }
-                                }
-                            }
+                    if args.len() == 1
+                        && let Some(inner_expr) = args[0].as_expression()
+                        && let Some(inner_steps) = flatten_tw_chain(inner_expr, bindings)
+                        && let Some(inner_items) = process_tw_steps(&inner_steps, bindings, theme, dyn_counter)
+                    {
+                        // Raw classes can't take a modifier
+                        let inner_rules: Option<Vec<StyleRule>> = inner_items
+                            .into_iter()
+                            .map(|item| match item {
+                                TwItem::Rule(rule) => Some(rule),
+                                TwItem::Class(_) => None,
+                            })
+                            .collect();
+                        if let Some(inner_rules) = inner_rules.filter(|r| !r.is_empty()) {
+                            processed = StyleRule::merge(&inner_rules);
                         }
                     }
                     if let Some(mut combined) = processed {
                         // Apply pending modifiers first
-                        for mod_name in pending_mods.iter().rev() {
-                            combined = modifiers::apply(mod_name, combined)?;
+                        for modifier in pending_mods.iter().rev() {
+                            combined = modifier.apply(combined, theme)?;
                         }
                         pending_mods.clear();
                         // Apply this modifier
-                        combined = modifiers::apply(name, combined)?;
-                        rules.push(combined);
+                        combined = modifier.apply(combined, theme)?;
+                        items.push(TwItem::Rule(combined));
                     } else {
                         return None;
                     }
+                } else if named.is_some() {
+                    return None;
+                } else if name == "group" || name == "peer" {
+                    // Named context for the next modifier: .group('sidebar').hover
+                    let values = evaluate_static_args(args, bindings, theme)?;
+                    let [Value::Str(group)] = values.as_slice() else {
+                        return None;
+                    };
+                    pending_group = Some((name.clone(), group.clone()));
+                } else if modifiers::is_marker(name) {
+                    // .namedGroup('sidebar') → 'group/sidebar'
+                    let values = evaluate_static_args(args, bindings, theme)?;
+                    items.push(TwItem::Class(modifiers::marker_class(name, &values)?));
+                } else if let Some(factory) = modifiers::tw_factory(name) {
+                    // Parameterized modifier: .data('state=open').p(4)
+                    let values = evaluate_static_args(args, bindings, theme)?;
+                    pending_mods.push(Modifier::Factory(factory.to_string(), values));
                 } else {
                     return None;
                 }
@@ -1094,257 +1887,713 @@ fn process_tw_steps(
     }
 
     // End of steps: flush any remaining pending_util
-    if let Some(util_name) = pending_util {
-        if let Some(mut rule) = utilities::evaluate(&util_name, &[], theme) {
-            for mod_name in pending_mods.iter().rev() {
-                rule = modifiers::apply(mod_name, rule)?;
-            }
-            pending_mods.clear();
-            rules.push(rule);
-        }
-        // Don't bail if the flush fails — the utility may not support 0-arg calls
+    if let Some(util_name) = pending_util
+        && let Some(mut rule) = utilities::evaluate(&util_name, &[], theme)
+    {
+        for modifier in pending_mods.iter().rev() {
+            rule = modifier.apply(rule, theme)?;
+        }
+        pending_mods.clear();
+        items.push(TwItem::Rule(rule));
     }
+    // Don't bail if the flush fails — the utility may not support 0-arg calls
 
     // If there are pending modifiers that were never applied to a utility,
     // the chain is malformed or uses a pattern we can't compile. Bail to
     // runtime rather than silently losing the modifiers.
-    if !pending_mods.is_empty() {
+    if !pending_mods.is_empty() || pending_group.is_some() {
         return None;
     }
 
-    Some(rules)
+    Some(items)
 }
 
-/// Visit all expressions in a program (simple recursive walker)
-fn visit_expressions<'a>(program: &'a Program, visitor: &mut dyn FnMut(&'a Expression<'a>)) {
-    for stmt in &program.body {
-        visit_statement(stmt, visitor);
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::{FsResolver, ModuleCache};
+
+    fn test_theme() -> ThemeData {
+        let mut spacing = HashMap::new();
+        spacing.insert("2".to_string(), "0.5rem".to_string());
+        spacing.insert("4".to_string(), "1rem".to_string());
+        let mut blue = HashMap::new();
+        blue.insert("500".to_string(), "#3b82f6".to_string());
+        let mut colors = HashMap::new();
+        colors.insert("blue".to_string(), blue);
+        let mut named_colors = HashMap::new();
+        named_colors.insert("white".to_string(), "#ffffff".to_string());
+        let mut containers = HashMap::new();
+        containers.insert("md".to_string(), "28rem".to_string());
+        ThemeData {
+            spacing,
+            colors,
+            named_colors,
+            containers,
+            breakpoints: crate::theme::default_breakpoints(),
+            ..Default::default()
+        }
     }
-}
 
-fn visit_statement<'a>(stmt: &'a Statement<'a>, visitor: &mut dyn FnMut(&'a Expression<'a>)) {
-    match stmt {
-        Statement::ExpressionStatement(expr_stmt) => {
-            visit_expr(&expr_stmt.expression, visitor);
-        }
-        Statement::VariableDeclaration(var_decl) => {
-            for decl in &var_decl.declarations {
-                if let Some(init) = &decl.init {
-                    visit_expr(init, visitor);
-                }
-            }
-        }
-        Statement::ReturnStatement(ret) => {
-            if let Some(arg) = &ret.argument {
-                visit_expr(arg, visitor);
-            }
-        }
-        Statement::IfStatement(if_stmt) => {
-            visit_expr(&if_stmt.test, visitor);
-            visit_statement(&if_stmt.consequent, visitor);
-            if let Some(alt) = &if_stmt.alternate {
-                visit_statement(alt, visitor);
-            }
-        }
-        Statement::BlockStatement(block) => {
-            for s in &block.body {
-                visit_statement(s, visitor);
-            }
-        }
-        Statement::ForStatement(f) => {
-            if let Some(body) = Some(&f.body) {
-                visit_statement(body, visitor);
-            }
-        }
-        Statement::ForInStatement(f) => {
-            visit_statement(&f.body, visitor);
-        }
-        Statement::ForOfStatement(f) => {
-            visit_statement(&f.body, visitor);
-        }
-        Statement::WhileStatement(w) => {
-            visit_expr(&w.test, visitor);
-            visit_statement(&w.body, visitor);
-        }
-        Statement::ExportDefaultDeclaration(def) => {
-            match &def.declaration {
-                ExportDefaultDeclarationKind::FunctionDeclaration(func) => {
-                    if let Some(body) = &func.body {
-                        for s in &body.statements {
-                            visit_statement(s, visitor);
-                        }
-                    }
-                }
-                ExportDefaultDeclarationKind::ClassDeclaration(class) => {
-                    for elem in &class.body.body {
-                        if let ClassElement::MethodDefinition(method) = elem {
-                            if let Some(body) = &method.value.body {
-                                for s in &body.statements {
-                                    visit_statement(s, visitor);
-                                }
-                            }
-                        }
-                        if let ClassElement::PropertyDefinition(prop) = elem {
-                            if let Some(init) = &prop.value {
-                                visit_expr(init, visitor);
-                            }
-                        }
-                    }
-                }
-                _ => {
-                    if let Some(expr) = def.declaration.as_expression() {
-                        visit_expr(expr, visitor);
-                    }
-                }
-            }
+    fn options() -> ExtractOptions {
+        ExtractOptions {
+            strict: true,
+            code_frame: false,
+            recover: false,
+            source_map: false,
+            sources_content: false,
         }
-        Statement::ExportNamedDeclaration(named) => {
-            match &named.declaration {
-                Some(Declaration::VariableDeclaration(var_decl)) => {
-                    for decl in &var_decl.declarations {
-                        if let Some(init) = &decl.init {
-                            visit_expr(init, visitor);
-                        }
-                    }
-                }
-                Some(Declaration::FunctionDeclaration(func)) => {
-                    if let Some(body) = &func.body {
-                        for s in &body.statements {
-                            visit_statement(s, visitor);
-                        }
-                    }
-                }
-                _ => {}
-            }
-        }
-        Statement::FunctionDeclaration(func) => {
-            if let Some(body) = &func.body {
-                for s in &body.statements {
-                    visit_statement(s, visitor);
-                }
-            }
-        }
-        _ => {}
     }
-}
 
-fn visit_expr<'a>(expr: &'a Expression<'a>, visitor: &mut dyn FnMut(&'a Expression<'a>)) {
-    visitor(expr);
+    fn run(source: &str, options: &ExtractOptions) -> TransformResult {
+        transform(source, "test.tsx", 0, &test_theme(), options, None)
+    }
 
-    match expr {
-        Expression::CallExpression(call) => {
-            visit_expr(&call.callee, visitor);
-            for arg in &call.arguments {
-                if let Some(e) = arg.as_expression() {
-                    visit_expr(e, visitor);
-                }
-            }
-        }
-        Expression::AssignmentExpression(assign) => {
-            visit_expr(&assign.right, visitor);
-        }
-        Expression::BinaryExpression(bin) => {
-            visit_expr(&bin.left, visitor);
-            visit_expr(&bin.right, visitor);
-        }
-        Expression::ConditionalExpression(cond) => {
-            visit_expr(&cond.test, visitor);
-            visit_expr(&cond.consequent, visitor);
-            visit_expr(&cond.alternate, visitor);
-        }
-        Expression::SequenceExpression(seq) => {
-            for e in &seq.expressions {
-                visit_expr(e, visitor);
-            }
-        }
-        Expression::TemplateLiteral(tmpl) => {
-            for e in &tmpl.expressions {
-                visit_expr(e, visitor);
-            }
-        }
-        Expression::ArrayExpression(arr) => {
-            for elem in &arr.elements {
-                if let Some(e) = elem.as_expression() {
-                    visit_expr(e, visitor);
-                }
-            }
-        }
-        Expression::ObjectExpression(obj) => {
-            for prop in &obj.properties {
-                if let ObjectPropertyKind::ObjectProperty(p) = prop {
-                    visit_expr(&p.value, visitor);
-                }
-            }
-        }
-        Expression::ArrowFunctionExpression(arrow) => {
-            if arrow.expression {
-                if let Some(stmt) = arrow.body.statements.first() {
-                    if let Statement::ExpressionStatement(es) = stmt {
-                        visit_expr(&es.expression, visitor);
-                    }
-                }
-            } else {
-                for s in &arrow.body.statements {
-                    visit_statement(s, visitor);
-                }
-            }
+    /// A fresh directory under the system temp dir holding `files`
+    fn project(name: &str, files: &[(&str, &str)]) -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!("twc-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        for (file, content) in files {
+            std::fs::write(dir.join(file), content).unwrap();
         }
-        Expression::ParenthesizedExpression(paren) => {
-            visit_expr(&paren.expression, visitor);
-        }
-        Expression::JSXElement(jsx) => {
-            visit_jsx_element(jsx, visitor);
-        }
-        Expression::JSXFragment(fragment) => {
-            for child in &fragment.children {
-                visit_jsx_child(child, visitor);
-            }
-        }
-        _ => {}
+        dir
     }
-}
 
-fn visit_jsx_element<'a>(
-    jsx: &'a JSXElement<'a>,
-    visitor: &mut dyn FnMut(&'a Expression<'a>),
-) {
-    // Visit attribute values
-    for attr in &jsx.opening_element.attributes {
-        if let JSXAttributeItem::Attribute(attr) = attr {
-            if let Some(value) = &attr.value {
-                if let JSXAttributeValue::ExpressionContainer(container) = value {
-                    if let Some(expr) = container.expression.as_expression() {
-                        visit_expr(expr, visitor);
-                    }
-                }
-            }
-        }
-        if let JSXAttributeItem::SpreadAttribute(spread) = attr {
-            visit_expr(&spread.argument, visitor);
-        }
+    fn run_in(dir: &std::path::Path, source: &str, cache: &mut ModuleCache) -> TransformResult {
+        let resolver = FsResolver::new(dir);
+        let theme = test_theme();
+        let mut loader = ModuleLoader::new(&resolver, cache, &theme);
+        let filename = dir.join("App.tsx");
+        transform(source, &filename.to_string_lossy(), 0, &theme, &options(), Some(&mut loader))
     }
-    // Visit children
-    for child in &jsx.children {
-        visit_jsx_child(child, visitor);
+
+    #[test]
+    fn test_strict_diagnostic_position() {
+        let src = "import { cx, p } from 'typewritingclass'\nconst a = cx(p(size))\n";
+        let result = run(src, &options());
+        assert_eq!(result.diagnostics.len(), 1);
+        let d = &result.diagnostics[0];
+        assert_eq!(d.severity, "error");
+        assert_eq!((d.line, d.column), (2, 10));
+        assert_eq!((d.end_line, d.end_column), (2, 21));
+        assert_eq!(&src[d.start as usize..d.end as usize], "cx(p(size))");
     }
-}
 
-fn visit_jsx_child<'a>(
-    child: &'a JSXChild<'a>,
-    visitor: &mut dyn FnMut(&'a Expression<'a>),
-) {
-    match child {
-        JSXChild::Element(el) => visit_jsx_element(el, visitor),
-        JSXChild::ExpressionContainer(container) => {
-            if let Some(expr) = container.expression.as_expression() {
-                visit_expr(expr, visitor);
-            }
-        }
-        JSXChild::Fragment(fragment) => {
-            for c in &fragment.children {
-                visit_jsx_child(c, visitor);
-            }
-        }
-        _ => {}
+    #[test]
+    fn test_parse_error_reported() {
+        let src = "import { cx, p } from 'typewritingclass'\nconst a = cx(p(4)\nconst b = 1\n";
+        let result = run(src, &options());
+        assert_eq!(result.code, src);
+        assert!(result.css_rules.is_empty());
+        assert!(!result.parsed);
+        assert!(!result.diagnostics.is_empty());
+        assert_eq!(result.diagnostics[0].severity, "error");
+        assert!(result.diagnostics[0].line >= 2);
+    }
+
+    #[test]
+    fn test_parse_error_recovery_still_extracts() {
+        // A mismatched JSX closing tag is reported but doesn't stop the parser
+        let src = "import { cx, p } from 'typewritingclass'\nconst el = <div></span>\nconst a = cx(p(4))\n";
+        let result = run(src, &ExtractOptions { recover: true, ..options() });
+        assert!(!result.diagnostics.is_empty());
+        assert!(result.parsed);
+        assert_eq!(result.css_rules.len(), 1);
+        assert!(result.code.contains("const a = '_"));
+    }
+
+    #[test]
+    fn test_source_map_generated() {
+        let src = "import { cx, p } from 'typewritingclass'\nconst a = cx(p(4))\n";
+        let result = run(src, &ExtractOptions { source_map: true, ..options() });
+        let map: serde_json::Value = serde_json::from_str(result.map.as_deref().unwrap()).unwrap();
+        assert_eq!(map["version"], 3);
+        assert_eq!(map["sources"][0], "test.tsx");
+        assert!(map.get("sourcesContent").is_none());
+        // Line 2's replacement maps back to the start of the cx() call (column 10)
+        assert_eq!(map["mappings"], "AAAA;AACA,UAAU,SAAQ;");
+    }
+
+    #[test]
+    fn test_source_map_skips_injected_imports() {
+        let src = "import { tw } from 'typewritingclass'\nconst a = tw.p(size)\n";
+        let result = run(src, &ExtractOptions { source_map: true, sources_content: true, ..options() });
+        assert!(result.code.starts_with("import 'typewritingclass/inject';\n"));
+        assert!(result.code.contains("tw.p(size).toString()"));
+        let map: serde_json::Value = serde_json::from_str(result.map.as_deref().unwrap()).unwrap();
+        assert!(map["mappings"].as_str().unwrap().starts_with(";AAAA;"));
+        assert_eq!(map["sourcesContent"][0], src);
+    }
+
+    #[test]
+    fn test_extracts_from_previously_skipped_positions() {
+        let src = r#"import { cx, p, tw } from 'typewritingclass'
+switch (x) { case 1: a = cx(p(4)) }
+try { b = cx(p(2)) } finally { c = tw.p(4) }
+do { d = cx(p(4)) } while (0)
+const e = ok && cx(p(2))
+const f = tw.p(2) as string
+async function g() { return await cx(p(4)) }
+const h = { m() { return cx(p(2)) } }
+export class K { k = cx(p(4)) }
+for (let i = 0; i < 1; i = tw.p(2)) {}
+const j = new Foo(cx(p(4)), ...[tw.p(2)])
+"#;
+        let result = run(src, &options());
+        assert!(result.diagnostics.is_empty());
+        assert!(!result.code.contains("cx("));
+        assert!(!result.code.contains("tw.p("));
+        assert_eq!(result.css_rules.len(), 12);
+    }
+
+    #[test]
+    fn test_const_number_propagated() {
+        let src = "import { tw } from 'typewritingclass'\nconst gap = 4\nconst a = tw.p(gap)\n";
+        let inline = run("import { tw } from 'typewritingclass'\nconst gap = 4\nconst a = tw.p(4)\n", &options());
+        let result = run(src, &options());
+        assert!(result.diagnostics.is_empty());
+        assert!(!result.code.contains("typewritingclass/inject"));
+        assert_eq!(result.css_rules, inline.css_rules);
+    }
+
+    #[test]
+    fn test_const_chain_inlined_into_cx() {
+        let src = "import { cx, bg, tw } from 'typewritingclass'\nconst base = tw.p(4).rounded\nconst a = cx(base, bg('white'))\n";
+        let result = run(src, &options());
+        assert!(result.diagnostics.is_empty());
+        assert_eq!(result.css_rules.len(), 3);
+        let base_classes = format!("{} {}", result.css_rules[0].0, result.css_rules[1].0);
+        assert!(result.code.contains(&format!("const base = '{}'", base_classes)));
+        assert!(result.code.contains(&format!("const a = '{} {}'", base_classes, result.css_rules[2].0)));
+    }
+
+    #[test]
+    fn test_const_rule_and_theme_token_inlined() {
+        let src = "import { cx, p, bg } from 'typewritingclass'\nimport { blue } from 'typewritingclass/theme/colors'\nconst pad = p(4)\nconst accent = blue[500]\nconst a = cx(pad, bg(accent))\n";
+        let result = run(src, &options());
+        assert!(result.diagnostics.is_empty());
+        assert_eq!(result.css_rules.len(), 2);
+        assert!(result.css_rules[1].1.contains("#3b82f6"));
+    }
+
+    #[test]
+    fn test_shadowed_const_not_propagated() {
+        let src = "import { tw } from 'typewritingclass'\nconst gap = 4\nfunction f(gap) { return tw.p(gap) }\n";
+        let result = run(src, &options());
+        assert!(result.css_rules.is_empty());
+        assert!(result.code.contains("tw.p(gap).toString()"));
+    }
+
+    #[test]
+    fn test_type_parameters_do_not_shadow_const() {
+        let src = "import { tw } from 'typewritingclass'\nconst gap = 4\ntype F<gap> = (gap: number) => void\ninterface I { f(gap: number): void }\nconst a = tw.p(gap)\n";
+        let result = run(src, &options());
+        assert!(result.diagnostics.is_empty());
+        assert_eq!(result.css_rules.len(), 1);
+    }
+
+    #[test]
+    fn test_reassigned_const_reported() {
+        let src = "import { tw } from 'typewritingclass'\nconst gap = 4\ngap = 8\nconst a = tw.p(gap)\n";
+        let result = run(src, &options());
+        assert!(result.css_rules.is_empty());
+        assert_eq!(result.diagnostics.len(), 1);
+        assert_eq!(result.diagnostics[0].severity, "error");
+        assert_eq!(result.diagnostics[0].line, 3);
+
+        // Writes nested in destructuring count too
+        let src = "import { tw } from 'typewritingclass'\nconst gap = 4\n;[{ gap }] = [{ gap: 8 }]\nconst a = tw.p(gap)\n";
+        let result = run(src, &options());
+        assert!(result.css_rules.is_empty());
+        assert_eq!(result.diagnostics.len(), 1);
+    }
+
+    #[test]
+    fn test_mutated_const_rule_reported() {
+        let src = "import { cx, p } from 'typewritingclass'\nconst pad = p(4)\nmutate(pad)\nconst a = cx(pad)\n";
+        let result = run(src, &ExtractOptions { strict: false, ..options() });
+        assert!(result.css_rules.is_empty());
+        assert_eq!(result.diagnostics.len(), 1);
+        assert_eq!(result.diagnostics[0].severity, "warning");
+        assert_eq!((result.diagnostics[0].line, result.diagnostics[0].column), (3, 7));
+
+        // Copies of the value share it
+        let src = "import { cx, p } from 'typewritingclass'\nconst pad = p(4)\nlet copy\ncopy = pad\nconst alias = copy as any\nmutate(alias)\nconst a = cx(pad)\n";
+        let result = run(src, &ExtractOptions { strict: false, ..options() });
+        assert!(result.css_rules.is_empty());
+        assert!(result.diagnostics.iter().any(|d| d.message.starts_with("'pad' may be modified") && d.line == 6));
+    }
+
+    #[test]
+    fn test_imported_constants_inlined() {
+        let dir = project("imports", &[
+            ("styles.ts", "import { tw, p } from 'typewritingclass'\nexport const card = tw.p(4).rounded\nexport const gap = 2\nconst pad = p(2)\nexport { pad as padding }\n"),
+            ("index.ts", "export * from './styles'\n"),
+        ]);
+        let src = "import { cx, bg, tw } from 'typewritingclass'\nimport { card, gap } from './styles'\nimport { padding } from './index.js'\nconst a = cx(card, bg('white'))\nconst b = tw.m(gap)\nconst c = cx(padding)\n";
+        let result = run_in(&dir, src, &mut ModuleCache::default());
+        assert!(result.diagnostics.is_empty());
+        assert!(!result.code.contains("cx("));
+        assert!(!result.code.contains("tw.m("));
+        assert_eq!(result.css_rules.len(), 5);
+        // Imported chains hash to the same classes the defining file emits
+        let styles = run("import { tw } from 'typewritingclass'\nexport const card = tw.p(4).rounded\n", &options());
+        assert_eq!(result.css_rules[0].0, styles.css_rules[0].0);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_imported_constants_cached_until_changed() {
+        let dir = project("cache", &[("tokens.ts", "export const gap = 2\n")]);
+        let src = "import { tw } from 'typewritingclass'\nimport { gap } from './tokens'\nconst a = tw.m(gap)\n";
+        let mut cache = ModuleCache::default();
+        let first = run_in(&dir, src, &mut cache);
+        assert_eq!(run_in(&dir, src, &mut cache).css_rules, first.css_rules);
+
+        std::fs::write(dir.join("tokens.ts"), "export const gap = 4\n").unwrap();
+        let changed = run_in(&dir, src, &mut cache);
+        assert_ne!(changed.css_rules, first.css_rules);
+        assert!(changed.css_rules[0].1.contains("1rem"));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_imported_dependencies_reported() {
+        let dir = project("dependencies", &[
+            ("tokens.ts", "export const gap = 2\n"),
+            ("styles.ts", "import { tw } from 'typewritingclass'\nimport { gap } from './tokens'\nexport const card = tw.m(gap)\n"),
+        ]);
+        let src = "import { cx } from 'typewritingclass'\nimport { card } from './styles'\nimport { gap } from './tokens'\nconst a = cx(card)\n";
+        let resolver = FsResolver::new(&dir);
+        let theme = test_theme();
+        let mut cache = ModuleCache::default();
+        for _ in 0..2 {
+            // Cached modules still report the files they were read from
+            let mut loader = ModuleLoader::new(&resolver, &mut cache, &theme);
+            transform(src, &dir.join("App.tsx").to_string_lossy(), 0, &theme, &options(), Some(&mut loader));
+            let root = dir.canonicalize().unwrap();
+            assert_eq!(loader.dependencies(), vec![root.join("styles.ts"), root.join("tokens.ts")]);
+        }
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_imports_outside_root_ignored() {
+        let dir = project("outside", &[]);
+        let src = "import { tw } from 'typewritingclass'\nimport { gap } from '../../etc/passwd'\nconst a = tw.m(gap)\n";
+        let result = run_in(&dir, src, &mut ModuleCache::default());
+        assert!(result.css_rules.is_empty());
+        assert!(result.code.contains("tw.m(gap).toString()"));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_cx_conditional_args() {
+        let src = "import { cx, p, bg } from 'typewritingclass'\nconst a = cx(p(4), isActive ? bg('blue-500') : bg('white'), disabled && p(2))\n";
+        let result = run(src, &options());
+        assert!(result.diagnostics.is_empty());
+        let classes: Vec<&str> = result.css_rules.iter().map(|r| r.0.as_str()).collect();
+        assert_eq!(classes.len(), 4);
+        let expected = format!(
+            "const a = '{}' + (isActive ? ' {}' : ' {}') + (disabled ? ' {}' : '')",
+            classes[0], classes[1], classes[2], classes[3]
+        );
+        assert!(result.code.contains(&expected), "{}", result.code);
+        assert!(!result.code.contains("typewritingclass/inject"));
+    }
+
+    #[test]
+    fn test_cx_leading_conditional_and_falsy_branch() {
+        let src = "import { cx, p } from 'typewritingclass'\nconst a = cx(open ? (wide ? p(4) : p(2)) : null)\n";
+        let result = run(src, &options());
+        let (c4, c2) = (&result.css_rules[0].0, &result.css_rules[1].0);
+        assert!(result.code.contains(&format!("const a = (open ? (wide ? '{}' : '{}') : '')", c4, c2)), "{}", result.code);
+    }
+
+    #[test]
+    fn test_cx_array_and_object_inputs() {
+        let src = "import { cx, p, bg } from 'typewritingclass'\nconst a = cx([p(4), [p(2), false && 'x']], { [bg('white')]: hasError, 'is-open': open, always: true, never: 0 }, null)\n";
+        let result = run(src, &options());
+        assert!(result.diagnostics.is_empty());
+        let classes: Vec<&str> = result.css_rules.iter().map(|r| r.0.as_str()).collect();
+        assert_eq!(classes.len(), 3);
+        let expected = format!(
+            "const a = '{} {}' + (hasError ? ' {}' : '') + (open ? ' is-open' : '') + ' always'",
+            classes[0], classes[1], classes[2]
+        );
+        assert!(result.code.contains(&expected), "{}", result.code);
+    }
+
+    #[test]
+    fn test_cx_static_array_stays_a_literal() {
+        let src = "import { cx, p } from 'typewritingclass'\nconst a = cx([p(4), 'card'], { active: true })\n";
+        let result = run(src, &options());
+        assert!(result.code.contains(&format!("const a = '{} card active'", result.css_rules[0].0)));
+    }
+
+    #[test]
+    fn test_cx_numbers_like_clsx() {
+        let src = "import { cx, p } from 'typewritingclass'\nconst a = cx(p(4), 0, 1, 2.5, on && 3)\n";
+        let result = run(src, &options());
+        let expected = format!("const a = '{} 1 2.5' + (on ? ' 3' : '')", result.css_rules[0].0);
+        assert!(result.code.contains(&expected), "{}", result.code);
+    }
+
+    #[test]
+    fn test_cx_object_spread_falls_back() {
+        let src = "import { cx, p } from 'typewritingclass'\nconst a = cx({ ...extra, [p(4)]: on })\n";
+        let result = run(src, &options());
+        assert!(result.css_rules.is_empty());
+        assert_eq!(result.diagnostics.len(), 1);
+    }
+
+    #[test]
+    fn test_constant_folding_in_arguments() {
+        let folded = run("import { cx, p, mt, w } from 'typewritingclass'\nconst cols = 2\nconst a = cx(p(2 * 2), mt(-(1 + 1)), w(`${cols * 4}rem`), w('1' + 'px'))\n", &options());
+        let literal = run("import { cx, p, mt, w } from 'typewritingclass'\nconst a = cx(p(4), mt(-2), w('8rem'), w('1px'))\n", &options());
+        assert!(folded.diagnostics.is_empty());
+        assert_eq!(folded.css_rules, literal.css_rules);
+    }
+
+    #[test]
+    fn test_negative_spacing_negates_scale_token() {
+        let src = "import { cx, mt, top, translateX, scrollMargin, insetX } from 'typewritingclass'\nconst a = cx(mt(-4), top(-2), translateX(-2), scrollMargin(-4), insetX(-0))\n";
+        let result = run(src, &options());
+        let css: Vec<&str> = result.css_rules.iter().map(|r| r.1.as_str()).collect();
+        assert!(css[0].contains("margin-top: -1rem;"), "{}", css[0]);
+        assert!(css[1].contains("top: -0.5rem;"), "{}", css[1]);
+        assert!(css[2].contains("transform: translateX(-0.5rem);"), "{}", css[2]);
+        assert!(css[3].contains("scroll-margin: -1rem;"), "{}", css[3]);
+        assert!(!css[4].contains("-0"), "{}", css[4]);
+    }
+
+    #[test]
+    fn test_negative_spacing_with_custom_tokens() {
+        let mut theme = test_theme();
+        theme.spacing.insert("0".to_string(), "0px".to_string());
+        theme.spacing.insert("3".to_string(), "var(--space-3)".to_string());
+        let src = "import { cx, m } from 'typewritingclass'\nconst a = cx(m(-3), m(-0.5 * 0), m(-6))\n";
+        let result = transform(src, "test.tsx", 0, &theme, &options(), None);
+        let css: Vec<&str> = result.css_rules.iter().map(|r| r.1.as_str()).collect();
+        assert!(css[0].contains("margin: calc(var(--space-3) * -1);"), "{}", css[0]);
+        assert!(css[1].contains("margin: 0px;"), "{}", css[1]);
+        assert!(css[2].contains("margin: -1.5rem;"), "{}", css[2]);
+    }
+
+    #[test]
+    fn test_modifier_factories_in_when() {
+        let src = "import { cx, when, p, data, aria, has_, not_, nth, minWidth, hover } from 'typewritingclass'\nconst a = cx(when(data('state=open'))(p(4)), when(aria('busy=\"true\"'), hover)(p(2)), when(has_('img'))(p(2)), when(not_(':first-child'))(p(2)), when(nth(3))(p(2)), when(minWidth(900))(p(4)))\n";
+        let result = run(src, &options());
+        assert!(result.diagnostics.is_empty());
+        let css: Vec<&str> = result.css_rules.iter().map(|r| r.1.as_str()).collect();
+        assert!(css[0].contains("[data-state=open] {"), "{}", css[0]);
+        assert!(css[1].contains(":hover[aria-busy=\"true\"] {"), "{}", css[1]);
+        assert!(css[2].contains(":has(img) {"), "{}", css[2]);
+        assert!(css[3].contains(":not(:first-child) {"), "{}", css[3]);
+        assert!(css[4].contains(":nth-child(3) {"), "{}", css[4]);
+        assert!(css[5].starts_with("@media (min-width: 900px)"), "{}", css[5]);
+    }
+
+    #[test]
+    fn test_modifier_factories_in_tw_chain_and_constants() {
+        let src = "import { tw, cx, when, p, data, maxWidth } from 'typewritingclass'\nconst open = data('state=open')\nconst a = tw.data('state=open').p(4).maxWidth('40em').p(2)\nconst b = cx(when(open)(p(4)), when(maxWidth('40em'))(p(2)))\n";
+        let result = run(src, &options());
+        assert!(result.diagnostics.is_empty());
+        let classes: Vec<&str> = result.css_rules.iter().map(|r| r.0.as_str()).collect();
+        assert_eq!(classes[..2], classes[2..]);
+        assert!(result.css_rules[0].1.contains("[data-state=open] {"));
+        assert!(result.css_rules[1].1.starts_with("@media (max-width: 40em)"));
+    }
+
+    #[test]
+    fn test_supports_nests_inside_media() {
+        let src = "import { cx, when, display, supports, md } from 'typewritingclass'\nconst a = cx(when(md, supports('(display: grid)'))(display('grid')))\n";
+        let result = run(src, &options());
+        assert!(result.diagnostics.is_empty());
+        let (class, css, ..) = &result.css_rules[0];
+        let expected = format!(
+            "@media (min-width: 768px) {{\n@supports (display: grid) {{\n.{} {{\n  display: grid;\n}}\n}}\n}}",
+            class
+        );
+        assert_eq!(css, &expected);
+    }
+
+    #[test]
+    fn test_container_queries() {
+        let src = "import { cx, tw, when, containerType, containerName, containerMin, containerMax, flexRow, p } from 'typewritingclass'\nconst a = cx(containerType(), containerName('sidebar'), when(containerMin('md', 'sidebar'))(flexRow()), when(containerMax(500))(p(2)))\nconst b = tw.containerType.containerMin('md').p(4)\n";
+        let result = run(src, &options());
+        assert!(result.diagnostics.is_empty());
+        let css: Vec<&str> = result.css_rules.iter().map(|r| r.1.as_str()).collect();
+        assert!(css[0].contains("container-type: inline-size;"));
+        assert!(css[1].contains("container-name: sidebar;"));
+        assert!(css[2].starts_with("@container sidebar (min-width: 28rem) {"), "{}", css[2]);
+        assert!(css[3].starts_with("@container (max-width: 500px) {"), "{}", css[3]);
+        assert!(css[5].starts_with("@container (min-width: 28rem) {"), "{}", css[5]);
+    }
+
+    #[test]
+    fn test_container_nests_inside_supports_and_media() {
+        let src = "import { cx, when, display, supports, containerMin, md } from 'typewritingclass'\nconst a = cx(when(containerMin('md'), md, supports('(display: grid)'))(display('grid')))\n";
+        let result = run(src, &options());
+        let (class, css, ..) = &result.css_rules[0];
+        let expected = format!(
+            "@media (min-width: 768px) {{\n@supports (display: grid) {{\n@container (min-width: 28rem) {{\n.{} {{\n  display: grid;\n}}\n}}\n}}\n}}",
+            class
+        );
+        assert_eq!(css, &expected);
+    }
+
+    #[test]
+    fn test_stacked_modifiers_compose_selectors() {
+        let src = "import { tw, cx, when, p, rtl, groupHover, peerChecked, hover, before } from 'typewritingclass'\nconst a = tw.groupHover.hover.bg('white')\nconst b = cx(when(rtl, groupHover)(p(4)), when(peerChecked, hover, before)(p(2)))\n";
+        let result = run(src, &options());
+        assert!(result.diagnostics.is_empty());
+        let selector = |i: usize| {
+            let (class, css, ..) = &result.css_rules[i];
+            css.lines().next().unwrap().replace(class.as_str(), "c")
+        };
+        assert_eq!(selector(0), ".group:hover .c:hover {");
+        assert_eq!(selector(1), "[dir=\"rtl\"] .group:hover .c {");
+        assert_eq!(selector(2), ".peer:checked ~ .c:hover::before {");
+    }
+
+    #[test]
+    fn test_custom_color_tokens_in_tw_chain() {
+        let mut theme = test_theme();
+        let mut accent = HashMap::new();
+        accent.insert("500".to_string(), "#f59e0b".to_string());
+        theme.colors.insert("brand-accent".to_string(), accent);
+        theme.named_colors.insert("primary".to_string(), "#6d28d9".to_string());
+        let src = "import { tw } from 'typewritingclass'\nconst a = tw.bg.brandAccent500.textColor.primary.borderColor.brandAccent500(50)\n";
+        let result = transform(src, "test.tsx", 0, &theme, &options(), None);
+        assert!(result.diagnostics.is_empty());
+        assert_eq!(result.css_rules.len(), 3);
+        assert!(result.css_rules[0].1.contains("background-color: #f59e0b;"));
+        assert!(result.css_rules[1].1.contains("color: #6d28d9;"));
+        assert!(result.css_rules[2].1.contains("border-color: rgb(245 158 11 / 0.5);"));
+    }
+
+    #[test]
+    fn test_scale_tokens_in_tw_chain() {
+        let mut theme = test_theme();
+        theme.radii.insert("pill".to_string(), "999px".to_string());
+        theme.tracking.insert("tight".to_string(), "-0.025em".to_string());
+        theme.leading.insert("6".to_string(), "1.5rem".to_string());
+        let src = "import { tw } from 'typewritingclass'
+const a = tw.rounded.pill.tracking.tight.leading._6
+";
+        let result = transform(src, "test.tsx", 0, &theme, &options(), None);
+        assert!(result.diagnostics.is_empty());
+        assert_eq!(result.css_rules.len(), 3);
+        assert!(result.css_rules[0].1.contains("border-radius: 999px;"));
+        assert!(result.css_rules[1].1.contains("letter-spacing: -0.025em;"));
+        assert!(result.css_rules[2].1.contains("line-height: 1.5rem;"));
+    }
+
+    #[test]
+    fn test_unknown_token_diagnostic() {
+        let mut theme = test_theme();
+        theme.radii.insert("DEFAULT".to_string(), "0.25rem".to_string());
+        theme.radii.insert("lg".to_string(), "0.5rem".to_string());
+        theme.radii.insert("2xl".to_string(), "1rem".to_string());
+        let src = "import { tw } from 'typewritingclass'
+const a = tw.p(4).rounded.pill
+const b = tw.bg.teal500.hover.rounded.lg
+";
+        let result = transform(src, "test.tsx", 0, &theme, &options(), None);
+        assert_eq!(result.diagnostics.len(), 2);
+        let d = &result.diagnostics[0];
+        assert_eq!(d.severity, "error");
+        assert_eq!(d.line, 2);
+        assert_eq!(d.message, "Unknown rounded token 'pill'. Valid tokens: _2xl, lg.");
+        assert!(result.diagnostics[1].message.starts_with("Unknown bg token 'teal500'. Valid tokens: blue<shade>,"));
+        // The chain is left to the runtime
+        assert!(result.code.contains("tw.p(4).rounded.pill.toString()"));
+
+        let lenient = ExtractOptions { strict: false, ..options() };
+        let result = transform(src, "test.tsx", 0, &theme, &lenient, None);
+        assert_eq!(result.diagnostics[0].severity, "warning");
+    }
+
+    #[test]
+    fn test_breakpoints_from_theme() {
+        let mut theme = test_theme();
+        theme.breakpoints = [("sm", "480px"), ("tablet", "960px"), ("wide", "90rem"), ("xl3", "120rem")]
+            .into_iter()
+            .map(|(name, width)| (name.to_string(), width.to_string()))
+            .collect();
+        let src = "import { tw, cx, when, p, sm, maxSm, screen, maxScreen } from 'typewritingclass'\nconst a = tw.sm.p(4).maxSm.p(2).tablet.p(4).maxWide.p(2)\nconst b = cx(when(sm)(p(4)), when(maxSm)(p(2)), when(screen('tablet'))(p(4)), when(maxScreen('wide'))(p(2)))\n";
+        let result = transform(src, "test.tsx", 0, &theme, &options(), None);
+        assert!(result.diagnostics.is_empty());
+        let css: Vec<&str> = result.css_rules.iter().map(|r| r.1.as_str()).collect();
+        assert_eq!(css[..4], css[4..]);
+        assert!(css[0].starts_with("@media (min-width: 480px)"), "{}", css[0]);
+        assert!(css[1].starts_with("@media (max-width: 479px)"), "{}", css[1]);
+        assert!(css[2].starts_with("@media (min-width: 960px)"), "{}", css[2]);
+        assert!(css[3].starts_with("@media (max-width: calc(90rem - 1px))"), "{}", css[3]);
+
+        // Imported modifiers are checked against the theme's breakpoints too
+        let result = transform("import { cx, when, p, xl3 } from 'typewritingclass'\nconst a = cx(when(xl3)(p(4)))\n", "test.tsx", 0, &theme, &options(), None);
+        assert!(result.diagnostics.is_empty());
+        assert!(result.css_rules[0].1.starts_with("@media (min-width: 120rem)"));
+
+        // Names the theme doesn't define are left to the runtime
+        let result = transform("import { cx, when, p, md } from 'typewritingclass'\nconst a = cx(when(md)(p(4)))\n", "test.tsx", 0, &theme, &options(), None);
+        assert!(result.css_rules.is_empty());
+        assert_eq!(result.diagnostics.len(), 1);
+    }
+
+    #[test]
+    fn test_dark_mode_strategy() {
+        let src = "import { cx, when, p, dark, hover } from 'typewritingclass'\nconst a = cx(when(dark, hover)(p(4)))\n";
+        let selector = |dark_mode: crate::theme::DarkMode| {
+            let theme = ThemeData { dark_mode, ..test_theme() };
+            let result = transform(src, "test.tsx", 0, &theme, &options(), None);
+            let (class, css, ..) = &result.css_rules[0];
+            css.lines().next().unwrap().replace(class.as_str(), "c")
+        };
+        assert_eq!(selector(crate::theme::DarkMode::Media), "@media (prefers-color-scheme: dark) {");
+        let class = crate::theme::DarkMode::parse("class", None).unwrap();
+        assert_eq!(selector(class), ".dark .c:hover {");
+        let attr = crate::theme::DarkMode::parse("attr", None).unwrap();
+        assert_eq!(selector(attr), "[data-theme=\"dark\"] .c:hover {");
+        let custom = crate::theme::DarkMode::parse("class", Some(".theme-night")).unwrap();
+        assert_eq!(selector(custom), ".theme-night .c:hover {");
+    }
+
+    #[test]
+    fn test_named_groups_and_peers() {
+        let src = "import { tw, cx, when, p, groupHover, peerChecked, groupHas, namedGroup, namedPeer } from 'typewritingclass'\nconst a = tw.group('sidebar').hover.p(4).peer('email').checked.p(2).group('card').has('img').p(1)\nconst b = cx(when(groupHover('sidebar'))(p(4)), when(peerChecked('email'))(p(2)), when(groupHas('img', 'card'))(p(1)))\nconst c = cx(namedGroup('sidebar'), p(4))\nconst d = tw.namedPeer('email').group.peer\n";
+        let result = run(src, &options());
+        assert!(result.diagnostics.is_empty());
+        let classes: Vec<&str> = result.css_rules.iter().map(|r| r.0.as_str()).collect();
+        assert_eq!(classes[..3], classes[3..6]);
+        let selector = |i: usize| {
+            let (class, css, ..) = &result.css_rules[i];
+            css.lines().next().unwrap().replace(class.as_str(), "c")
+        };
+        assert_eq!(selector(0), ".group\\/sidebar:hover .c {");
+        assert_eq!(selector(1), ".peer\\/email:checked ~ .c {");
+        assert_eq!(selector(2), ".group\\/card:has(img) .c {");
+        assert!(result.code.contains(&format!("'group/sidebar {}'", classes[6])));
+        assert!(result.code.contains("'peer/email group peer'"));
+
+        // Names are escaped like the runtime escapes them
+        let src = "import { cx, when, p, groupHover } from 'typewritingclass'\nconst a = cx(when(groupHover('main.nav:1'))(p(4)))\n";
+        let result = run(src, &options());
+        let (class, css, ..) = &result.css_rules[0];
+        assert!(css.starts_with(&format!(".group\\/main\\.nav\\:1:hover .{} {{", class)), "{}", css);
+    }
+
+    #[test]
+    fn test_named_group_without_modifier_falls_back() {
+        let src = "import { tw } from 'typewritingclass'\nconst a = tw.group('sidebar').p(4)\n";
+        let result = run(src, &options());
+        assert!(result.css_rules.is_empty());
+        assert!(result.code.contains(".toString()"));
+    }
+
+    #[test]
+    fn test_pseudo_element_order_does_not_change_class() {
+        let src = "import { cx, when, p, hover, before } from 'typewritingclass'\nconst a = cx(when(hover, before)(p(2)))\nconst b = cx(when(before, hover)(p(2)))\n";
+        let result = run(src, &options());
+        assert_eq!(result.css_rules[0].0, result.css_rules[1].0);
+        assert!(result.css_rules[0].1.contains(":hover::before {"));
+    }
+
+    #[test]
+    fn test_when_over_different_selector_contexts_splits() {
+        let src = "import { cx, when, p, hover, groupHover } from 'typewritingclass'\nconst a = cx(when(hover)(when(groupHover)(p(2)), p(4)))\n";
+        let result = run(src, &options());
+        assert!(result.diagnostics.is_empty(), "{:?}", result.diagnostics);
+        let templates: Vec<Option<&str>> = result.css_rules.iter().map(|rule| rule.3.selector_template.as_deref()).collect();
+        assert_eq!(templates, vec![Some(".group:hover &"), None]);
+        assert!(result.css_rules.iter().all(|rule| rule.3.selectors == [":hover"]));
+    }
+
+    #[test]
+    fn test_modifier_factory_with_dynamic_argument_falls_back() {
+        let src = "import { cx, when, p, data } from 'typewritingclass'\nconst a = cx(when(data(state))(p(4)))\n";
+        let result = run(src, &options());
+        assert!(result.css_rules.is_empty());
+        assert_eq!(result.diagnostics.len(), 1);
+    }
+
+    #[test]
+    fn test_folding_with_unknown_operand_falls_back() {
+        let src = "import { tw } from 'typewritingclass'\nconst a = tw.p(size * 2)\n";
+        let result = run(src, &options());
+        assert!(result.css_rules.is_empty());
+        assert!(result.code.contains("tw.p(size * 2).toString()"));
+    }
+
+    #[test]
+    fn test_named_cascade_layer() {
+        let src = "import { cx, layer, p } from 'typewritingclass'
+import { tw } from 'typewritingclass'
+const a = cx(layer('components')(p(4)))
+const b = tw.layer('overrides').p(4)
+const c = cx(p(4))
+";
+        let result = run(src, &options());
+        assert!(result.diagnostics.is_empty(), "{:?}", result.diagnostics);
+        let layers: Vec<Option<&str>> = result.css_rules.iter().map(|rule| rule.3.layer.as_deref()).collect();
+        assert_eq!(layers, vec![Some("components"), Some("overrides"), None]);
+        // The same declarations in another layer get their own class
+        let classes: std::collections::HashSet<&str> = result.css_rules.iter().map(|rule| rule.0.as_str()).collect();
+        assert_eq!(classes.len(), 3);
+    }
+
+    #[test]
+    fn test_numeric_layer_priority() {
+        let src = "import { cx, layer, p, m } from 'typewritingclass'
+const a = cx(layer(1000)(p(4), m(2)))
+const b = cx(p(4))
+";
+        let result = run(src, &options());
+        assert!(result.diagnostics.is_empty(), "{:?}", result.diagnostics);
+        let priorities: Vec<Option<u32>> = result.css_rules.iter().map(|rule| rule.3.priority).collect();
+        assert_eq!(priorities, vec![Some(1000), None]);
+        assert!(!result.code.contains("layer("));
+    }
+
+    #[test]
+    fn test_when_splits_selector_contexts_in_order() {
+        let src = "import { cx, when, md, groupHover, p, bg } from 'typewritingclass'
+const a = cx(when(md)(p(4), when(groupHover)(bg('blue-50'))))
+";
+        let result = run(src, &options());
+        assert!(result.diagnostics.is_empty(), "{:?}", result.diagnostics);
+        assert_eq!(result.css_rules.len(), 2);
+        let (plain, grouped) = (&result.css_rules[0], &result.css_rules[1]);
+        assert_eq!(plain.3.selector_template, None);
+        assert_eq!(grouped.3.selector_template.as_deref(), Some(".group:hover &"));
+        assert!(plain.3.media_queries == grouped.3.media_queries && !plain.3.media_queries.is_empty());
+        assert!(result.code.contains(&format!("'{} {}'", plain.0, grouped.0)));
+    }
+
+    #[test]
+    fn test_when_splits_cascade_layers() {
+        let src = "import { cx, when, hover, layer, p, m } from 'typewritingclass'
+const a = cx(when(hover)(layer('components')(p(4)), m(2)))
+";
+        let result = run(src, &options());
+        assert!(result.diagnostics.is_empty(), "{:?}", result.diagnostics);
+        let layers: Vec<_> = result.css_rules.iter().map(|r| r.3.layer.as_deref()).collect();
+        assert_eq!(layers, vec![Some("components"), None]);
+        assert!(result.css_rules.iter().all(|r| r.3.selectors == vec![":hover"]));
+    }
+
+    #[test]
+    fn test_layer_merge_matches_runtime_hash() {
+        // The runtime's layer(1)(...) hashes this rule as _1ix1xvs
+        let src = "import { cx, when, supports, layer, grid, p } from 'typewritingclass'
+const a = cx(layer(1)(when(supports('(display: grid)'))(grid()), p(4)))
+";
+        let result = run(src, &options());
+        assert!(result.diagnostics.is_empty(), "{:?}", result.diagnostics);
+        assert_eq!(result.css_rules.len(), 1);
+        assert_eq!(result.css_rules[0].0, "_1ix1xvs");
     }
 }
@@ -0,0 +1,134 @@
+//! Constant folding of operators over statically known values, following
+//! JavaScript semantics for the cases that appear in style arguments.
+
+use oxc_ast::ast::{BinaryOperator, UnaryOperator};
+
+use crate::utilities::Value;
+
+/// `-x`, `+x`
+pub fn unary(op: UnaryOperator, value: &Value) -> Option<Value> {
+    let n = to_number(value)?;
+    match op {
+        UnaryOperator::UnaryNegation => Some(Value::Num(-n)),
+        UnaryOperator::UnaryPlus => Some(Value::Num(n)),
+        _ => None,
+    }
+}
+
+/// Arithmetic on numbers, and `+` concatenation when either side is a string
+pub fn binary(op: BinaryOperator, left: &Value, right: &Value) -> Option<Value> {
+    if op == BinaryOperator::Addition
+        && (matches!(left, Value::Str(_)) || matches!(right, Value::Str(_)))
+    {
+        return Some(Value::Str(format!("{}{}", to_js_string(left)?, to_js_string(right)?)));
+    }
+    let (l, r) = (to_number(left)?, to_number(right)?);
+    let n = match op {
+        BinaryOperator::Addition => l + r,
+        BinaryOperator::Subtraction => l - r,
+        BinaryOperator::Multiplication => l * r,
+        BinaryOperator::Division => l / r,
+        BinaryOperator::Remainder => l % r,
+        BinaryOperator::Exponential => l.powf(r),
+        _ => return None,
+    };
+    // Infinity and NaN never make valid CSS
+    n.is_finite().then_some(Value::Num(n))
+}
+
+/// `String(value)` for a folded value
+pub fn to_js_string(value: &Value) -> Option<String> {
+    match value {
+        Value::Str(s) => Some(s.clone()),
+        Value::Num(n) => Some(format_number(*n)),
+        Value::Dynamic(..) => None,
+    }
+}
+
+/// Numbers only: strings are never coerced, since `'4' * 2` in a style
+/// argument is almost certainly a mistake
+fn to_number(value: &Value) -> Option<f64> {
+    match value {
+        Value::Num(n) => Some(*n),
+        _ => None,
+    }
+}
+
+/// Format a number exactly as JavaScript's `String(n)` does: the shortest
+/// digits that round-trip, in exponent form below 1e-6 and from 1e21 up,
+/// and `-0` as `0`
+fn format_number(n: f64) -> String {
+    if n == 0.0 {
+        return "0".to_string();
+    }
+    if !n.is_finite() {
+        return if n.is_nan() { "NaN" } else if n > 0.0 { "Infinity" } else { "-Infinity" }.to_string();
+    }
+    // `{:e}` gives the same shortest digits as JS, e.g. `-1.25e-7`
+    let formatted = format!("{:e}", n.abs());
+    let (mantissa, exponent) = formatted.split_once('e').unwrap_or((&formatted, "0"));
+    let digits = mantissa.replace('.', "");
+    let k = digits.len() as i32;
+    // The decimal point sits after the first `point` digits
+    let point = exponent.parse::<i32>().unwrap_or(0) + 1;
+    let body = if k <= point && point <= 21 {
+        format!("{}{}", digits, "0".repeat((point - k) as usize))
+    } else if 0 < point && point <= 21 {
+        format!("{}.{}", &digits[..point as usize], &digits[point as usize..])
+    } else if -6 < point && point <= 0 {
+        format!("0.{}{}", "0".repeat(-point as usize), digits)
+    } else {
+        let sign = if point > 0 { '+' } else { '-' };
+        match digits.split_at(1) {
+            (first, "") => format!("{}e{}{}", first, sign, (point - 1).abs()),
+            (first, rest) => format!("{}.{}e{}{}", first, rest, sign, (point - 1).abs()),
+        }
+    };
+    if n < 0.0 { format!("-{}", body) } else { body }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn num(n: f64) -> Value {
+        Value::Num(n)
+    }
+
+    fn str(s: &str) -> Value {
+        Value::Str(s.to_string())
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        assert_eq!(binary(BinaryOperator::Multiplication, &num(2.0), &num(4.0)).unwrap().as_num(), Some(8.0));
+        assert_eq!(binary(BinaryOperator::Division, &num(1.0), &num(4.0)).unwrap().as_num(), Some(0.25));
+        assert_eq!(unary(UnaryOperator::UnaryNegation, &num(2.0)).unwrap().as_num(), Some(-2.0));
+        assert!(binary(BinaryOperator::Division, &num(1.0), &num(0.0)).is_none());
+    }
+
+    #[test]
+    fn test_string_concatenation() {
+        let v = binary(BinaryOperator::Addition, &str("1px "), &str("solid")).unwrap();
+        assert_eq!(v.as_str(), Some("1px solid"));
+        let v = binary(BinaryOperator::Addition, &num(1.5), &str("rem")).unwrap();
+        assert_eq!(v.as_str(), Some("1.5rem"));
+        assert!(binary(BinaryOperator::Multiplication, &str("4"), &num(2.0)).is_none());
+    }
+
+    #[test]
+    fn test_format_number() {
+        assert_eq!(to_js_string(&num(16.0)).unwrap(), "16");
+        assert_eq!(to_js_string(&num(-0.0)).unwrap(), "0");
+        assert_eq!(to_js_string(&num(0.125)).unwrap(), "0.125");
+        assert_eq!(to_js_string(&num(-1.5)).unwrap(), "-1.5");
+        assert_eq!(to_js_string(&num(123456.789)).unwrap(), "123456.789");
+        assert_eq!(to_js_string(&num(1e20)).unwrap(), "100000000000000000000");
+        assert_eq!(to_js_string(&num(1e21)).unwrap(), "1e+21");
+        assert_eq!(to_js_string(&num(1.5e300)).unwrap(), "1.5e+300");
+        assert_eq!(to_js_string(&num(0.000001)).unwrap(), "0.000001");
+        assert_eq!(to_js_string(&num(1e-7)).unwrap(), "1e-7");
+        assert_eq!(to_js_string(&num(-2.5e-8)).unwrap(), "-2.5e-8");
+        assert_eq!(to_js_string(&num(0.1 + 0.2)).unwrap(), "0.30000000000000004");
+    }
+}
@@ -14,12 +14,21 @@ fn djb2(input: &str) -> u32 {
 /// class name regardless of layer ordering.
 pub fn generate_hash(rule: &StyleRule, _layer: u32) -> String {
     // Build the same string the TS version builds:
-    // JSON.stringify(declarations) + JSON.stringify(selectors) + JSON.stringify(mediaQueries) + JSON.stringify(supportsQueries) + selectorTemplate
+    // JSON.stringify(declarations) + JSON.stringify(selectors) + JSON.stringify(mediaQueries) + JSON.stringify(supportsQueries)
+    //   + (containerQueries only when non-empty, so older hashes are unchanged) + selectorTemplate
+    //   + ("@layer " + cascadeLayer, when set)
     let decl_json = serialize_declarations(&rule.declarations);
     let sel_json = serialize_string_array(&rule.selectors);
     let mq_json = serialize_string_array(&rule.media_queries);
+    let sq_json = serialize_string_array(&rule.supports_queries);
+    let cq_json = if rule.container_queries.is_empty() {
+        String::new()
+    } else {
+        serialize_string_array(&rule.container_queries)
+    };
     let st = rule.selector_template.as_deref().unwrap_or("");
-    let input = format!("{}{}{}{}", decl_json, sel_json, mq_json, st);
+    let layer = rule.layer.as_ref().map(|l| format!("@layer {}", l)).unwrap_or_default();
+    let input = format!("{}{}{}{}{}{}{}", decl_json, sel_json, mq_json, sq_json, cq_json, st, layer);
     let h = djb2(&input);
     format!("_{}", radix_fmt(h, 36))
 }
@@ -104,4 +113,23 @@ mod tests {
         let rule = StyleRule::new(vec![("color", "red")]);
         assert_eq!(generate_hash(&rule, 0), generate_hash(&rule, 1));
     }
+
+    #[test]
+    fn test_hash_matches_runtime() {
+        // Values from the TS generateHash for the same rules
+        let rule = StyleRule::new(vec![("color", "red")]);
+        assert_eq!(generate_hash(&rule, 0), "_1bhu5k1");
+        let rule = StyleRule::new(vec![("display", "grid")])
+            .with_supports_query("(display: grid)")
+            .with_media_query("(min-width: 768px)");
+        assert_eq!(generate_hash(&rule, 0), "_1hxqshn");
+    }
+
+    #[test]
+    fn test_container_queries_only_hashed_when_present() {
+        let rule = StyleRule::new(vec![("color", "red")]);
+        assert_eq!(generate_hash(&rule, 0), "_1bhu5k1");
+        let contained = rule.with_container_query("(min-width: 28rem)");
+        assert_ne!(generate_hash(&contained, 0), "_1bhu5k1");
+    }
 }
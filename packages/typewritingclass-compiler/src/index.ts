@@ -11,35 +11,97 @@ const __dirname = dirname(fileURLToPath(import.meta.url))
 const native: typeof import('../index.d.ts') = require(resolve(__dirname, '..', 'index.node'))
 
 // Re-export the native transform for direct use
-export const { transform: nativeTransform, generateCss } = native
-export type { ThemeInput, TransformOutput, ExtractedRule, Diagnostic } from '../index.d.ts'
+export const { transform: nativeTransform, generateCss, buildStylesheet, validateTheme, Compiler } = native
+export type Compiler = import('../index.d.ts').Compiler
+export type { ThemeInput, ThemeIssue, TransformOutput, ExtractedRule, Diagnostic, CompilerOptions, StylesheetOptions, StylesheetOutput, ClassCollision } from '../index.d.ts'
 
 export interface TwcPluginOptions {
   strict?: boolean
   cloak?: boolean  // default: true — set false to opt out of FOUC prevention
+  /**
+   * Overrides for the loaded theme, e.g. `{ darkMode: 'class' }` or custom
+   * `breakpoints` JSON. Breakpoints and dark mode are also passed to the
+   * runtime, for styles that can't be compiled.
+   */
+  theme?: Partial<import('../index.d.ts').ThemeInput>
+  /** Warn about a malformed theme and drop the bad values (default), or `'throw'` to fail the build */
+  themeErrors?: 'throw' | 'warn'
+  /**
+   * Order of the CSS cascade layers in `virtual:twc.css`
+   * (default: `['reset', 'base', 'components', 'utilities', 'overrides']`).
+   * Assign a style to a layer with `layer('components')` or `tw.layer('overrides')`;
+   * until one is, the stylesheet is unlayered. `[]` never layers it.
+   */
+  layerOrder?: string[]
+  /**
+   * How rules are ordered within a layer. `'source'` (default) follows the
+   * order files were transformed in; `'deterministic'` sorts by the shape of
+   * each rule (breakpoints ascending, fewer variants and shorthands first) so
+   * production builds are reproducible.
+   */
+  order?: 'source' | 'deterministic'
+  /**
+   * Emit rules sharing the same `@media`/`@supports`/`@container` context in
+   * one block, mobile first. Rules only move when that can't change which
+   * declaration wins. Default: `false`
+   */
+  groupAtRules?: boolean
+  /**
+   * Minify the generated stylesheet and merge the selectors of rules with
+   * identical declarations. Class names are unchanged. Default: `false`
+   */
+  minify?: boolean
 }
 
 const VIRTUAL_CSS_ID = 'virtual:twc.css'
 const RESOLVED_VIRTUAL_CSS_ID = '\0' + VIRTUAL_CSS_ID
 const VIRTUAL_CSS_JS_ID = 'virtual:twc-css-inject'
 const RESOLVED_VIRTUAL_CSS_JS_ID = '\0' + VIRTUAL_CSS_JS_ID
+const VIRTUAL_CONFIG_ID = 'virtual:twc-config'
+const RESOLVED_VIRTUAL_CONFIG_ID = '\0' + VIRTUAL_CONFIG_ID
+
+/**
+ * The `configureRuntime()` argument matching the `theme` option's
+ * breakpoints and dark mode, or `null` when the runtime defaults apply
+ */
+function runtimeConfig(theme: TwcPluginOptions['theme']): string | null {
+  const { breakpoints, darkMode, darkSelector } = theme ?? {}
+  if (!breakpoints && !darkMode && !darkSelector) return null
+  let parsed: Record<string, string> | undefined
+  try {
+    parsed = breakpoints ? JSON.parse(breakpoints) : undefined
+  } catch {
+    // Reported by the compiler's theme validation
+  }
+  return JSON.stringify({ breakpoints: parsed, darkMode, darkSelector })
+}
 
 export default function twcPlugin(options?: TwcPluginOptions): Plugin {
   const strict = options?.strict ?? true
   const cloak = options?.cloak ?? true
-
-  let themeInput: import('../index.d.ts').ThemeInput
-  // In dev mode, each file gets a stable layer offset (based on insertion order)
-  // so hashes are deterministic across HMR cycles. In production, layer is
-  // accumulated across all files for proper CSS ordering.
-  let prodLayer = 0
-  const fileLayerOffsets = new Map<string, number>()
-  let nextFileLayer = 0
-  // file path -> extracted CSS rules (css text strings ordered by layer)
-  const fileRules = new Map<string, string[]>()
+  // Styles resolved at runtime must use the same breakpoints and dark mode
+  // as compiled ones, so files that need the runtime import this first
+  const configJson = runtimeConfig(options?.theme)
+
+  // Keeps every file's rules, with a stable layer offset per file so hashes
+  // are deterministic across HMR cycles
+  let compiler: import('../index.d.ts').Compiler | undefined
+  // Relative imports inside the project root are followed so shared style
+  // constants (e.g. `export const card = tw.rounded.lg`) compile statically
+  let root: string | undefined
+  // Imported files whose exports each transformed file inlined
+  const fileDependencies = new Map<string, string[]>()
   let devServer: ViteDevServer | null = null
   let cssInvalidateTimer: ReturnType<typeof setTimeout> | null = null
 
+  function importersOf(file: string): string[] {
+    const importers: string[] = []
+    for (const [importer, dependencies] of fileDependencies) {
+      if (dependencies.includes(file)) importers.push(importer)
+    }
+    return importers
+  }
+
   function scheduleCssUpdate() {
     if (!devServer) return
     if (cssInvalidateTimer) clearTimeout(cssInvalidateTimer)
@@ -91,8 +153,43 @@ export default function twcPlugin(options?: TwcPluginOptions): Plugin {
       ]
     },
 
+    configResolved(config) {
+      root = config.root
+    },
+
     async buildStart() {
-      themeInput = await loadTheme()
+      const themeInput = { ...(await loadTheme()), ...options?.theme }
+      let created: import('../index.d.ts').Compiler
+      try {
+        created = new native.Compiler(themeInput, {
+          strict,
+          sourceMap: true,
+          root,
+          themeErrors: options?.themeErrors,
+          layerOrder: options?.layerOrder,
+          order: options?.order,
+          groupAtRules: options?.groupAtRules,
+          minify: options?.minify,
+        })
+      } catch (err) {
+        this.error(err instanceof Error ? err.message : String(err))
+      }
+      compiler = created
+      const issues = compiler.themeWarnings
+      if (issues.length > 0) {
+        const lines = issues.map((issue) => `  ${issue.field} ${issue.path}: ${issue.reason}`)
+        this.warn(`Invalid theme:\n${lines.join('\n')}`)
+      }
+    },
+
+    watchChange(id, change) {
+      if (!compiler) return
+      if (change.event === 'delete') {
+        fileDependencies.delete(id)
+        if (compiler.removeFile(id)) scheduleCssUpdate()
+      } else {
+        compiler.invalidate(id)
+      }
     },
 
     resolveId(id) {
@@ -106,12 +203,18 @@ export default function twcPlugin(options?: TwcPluginOptions): Plugin {
       if (id === VIRTUAL_CSS_JS_ID) {
         return RESOLVED_VIRTUAL_CSS_JS_ID
       }
+      if (id === VIRTUAL_CONFIG_ID) {
+        return RESOLVED_VIRTUAL_CONFIG_ID
+      }
     },
 
     load(id) {
       if (id === RESOLVED_VIRTUAL_CSS_ID) {
         return generateAllCss()
       }
+      if (id === RESOLVED_VIRTUAL_CONFIG_ID) {
+        return `import { configureRuntime } from 'typewritingclass';\nconfigureRuntime(${configJson});\n`
+      }
       if (id === RESOLVED_VIRTUAL_CSS_JS_ID) {
         // Dev-only JS module: fetches compiled CSS from server endpoint.
         // Uses a fetch() instead of inline CSS to avoid stale caching.
@@ -140,22 +243,25 @@ if (import.meta.hot) {
     async handleHotUpdate(ctx) {
       const { file, read, server, modules } = ctx
       if (!file.match(/\.[jt]sx?$/) || file.includes('node_modules')) return
-      const code = await read()
-      if (!code.includes('typewritingclass')) return
 
-      // Assign stable layer offset
-      if (!fileLayerOffsets.has(file)) {
-        fileLayerOffsets.set(file, nextFileLayer)
-        nextFileLayer += 1000
+      // Files that inlined constants from the changed file must be compiled
+      // again, even when the changed file doesn't use typewritingclass itself
+      const importerModules = importersOf(file).flatMap((importer) => [
+        ...(server.moduleGraph.getModulesByFile(importer) ?? []),
+      ])
+      for (const mod of importerModules) server.moduleGraph.invalidateModule(mod)
+      const affected = [...modules, ...importerModules]
+
+      const code = await read()
+      if (!code.includes('typewritingclass') || !compiler) {
+        return importerModules.length > 0 ? affected : undefined
       }
-      const layerOffset = fileLayerOffsets.get(file)!
 
       try {
-        // Pre-extract rules so fileRules is fresh BEFORE modules are re-loaded.
+        // Pre-extract rules so the stylesheet is fresh BEFORE modules are re-loaded.
         // This eliminates the race condition where the CSS module could be
-        // fetched before the component's transform hook updates fileRules.
-        const result = native.transform(code, file, layerOffset, themeInput, strict)
-        fileRules.set(file, result.rules.map((r) => r.cssText))
+        // fetched before the component's transform hook updates the compiler.
+        compiler.transform(code, file)
       } catch {
         // Extraction failed — transform hook will handle it
       }
@@ -165,54 +271,44 @@ if (import.meta.hot) {
       const cssMod = server.moduleGraph.getModuleById(RESOLVED_VIRTUAL_CSS_ID)
       if (cssMod) {
         server.moduleGraph.invalidateModule(cssMod)
-        return [...modules, cssMod]
+        return [...affected, cssMod]
       }
       // Dev mode uses the JS inject module — notify via custom event
       server.ws.send({ type: 'custom', event: 'twc:css-update', data: {} })
-      return modules
+      return affected
     },
 
     transform(code, id) {
       if (!id.match(/\.[jt]sx?$/) || id.includes('node_modules')) return
-      if (!code.includes('typewritingclass')) return
-
-      // In dev mode, assign a stable layer offset per file so hashes
-      // don't change across HMR cycles. In production, accumulate layers.
-      let layerOffset: number
-      if (devServer) {
-        if (!fileLayerOffsets.has(id)) {
-          fileLayerOffsets.set(id, nextFileLayer)
-          nextFileLayer += 1000
-        }
-        layerOffset = fileLayerOffsets.get(id)!
-      } else {
-        layerOffset = prodLayer
-      }
+      if (!code.includes('typewritingclass') || !compiler) return
 
       try {
-        const result = native.transform(code, id, layerOffset, themeInput, strict)
-        if (!devServer) {
-          prodLayer = result.nextLayer
+        const result = compiler.transform(code, id)
+        // The file's rules replace those of its previous version
+        if (result.rulesChanged) scheduleCssUpdate()
+
+        // Re-run this transform when a file it inlined constants from changes
+        fileDependencies.set(id, result.dependencies)
+        for (const dependency of result.dependencies) {
+          this.addWatchFile(dependency)
         }
 
-        if (result.rules.length > 0) {
-          fileRules.set(id, result.rules.map((r) => r.cssText))
-          scheduleCssUpdate()
+        // The native map already accounts for every replacement and injected
+        // import; prepending the virtual CSS import only shifts it by one line.
+        const map = JSON.parse(result.map!)
+        let prelude = result.code.includes(VIRTUAL_CSS_ID) ? '' : `import '${VIRTUAL_CSS_ID}';`
+        if (configJson && result.code.includes('typewritingclass/inject')) {
+          prelude = `import '${VIRTUAL_CONFIG_ID}';${prelude}`
         }
-
-        const s = new MagicString(code)
-
-        if (result.code !== code) {
-          s.overwrite(0, code.length, result.code)
-        }
-
-        if (!result.code.includes(VIRTUAL_CSS_ID)) {
-          s.prepend(`import '${VIRTUAL_CSS_ID}';\n`)
+        let transformedCode = result.code
+        if (prelude) {
+          transformedCode = `${prelude}\n${transformedCode}`
+          map.mappings = ';' + map.mappings
         }
 
         const transformed = {
-          code: s.toString(),
-          map: s.generateMap({ source: id, includeContent: true }),
+          code: transformedCode,
+          map,
         }
 
         // Emit diagnostics AFTER preparing the transformed output.
@@ -221,7 +317,12 @@ if (import.meta.hot) {
         // since partial compilation (compiling what we can, runtime fallback for
         // the rest) is always preferable to discarding the entire result.
         for (const diag of result.diagnostics) {
-          this.warn({ message: diag.message, id, pos: diag.line })
+          this.warn({
+            message: diag.message,
+            id,
+            loc: { file: id, line: diag.line, column: diag.column },
+            frame: diag.codeFrame,
+          })
         }
 
         return transformed
@@ -229,6 +330,7 @@ if (import.meta.hot) {
         if (!code.includes('typewritingclass/inject')) {
           const s = new MagicString(code)
           s.prepend(`import 'typewritingclass/inject';\n`)
+          if (configJson) s.prepend(`import '${VIRTUAL_CONFIG_ID}';\n`)
           return {
             code: s.toString(),
             map: s.generateMap({ source: id, includeContent: true }),
@@ -238,7 +340,14 @@ if (import.meta.hot) {
     },
 
     generateBundle(_, bundle) {
-      const css = generateAllCss()
+      if (!compiler) return
+      const { css, collisions } = compiler.buildStylesheet()
+      for (const collision of collisions) {
+        this.warn(
+          `Class ${collision.className} is generated for two different rules; keeping the first.\n` +
+            `${collision.cssText}\n${collision.otherCssText}`,
+        )
+      }
       if (!css) return
 
       for (const chunk of Object.values(bundle)) {
@@ -258,12 +367,6 @@ if (import.meta.hot) {
   }
 
   function generateAllCss(): string {
-    const allRules: string[] = []
-    for (const rules of fileRules.values()) {
-      allRules.push(...rules)
-    }
-    if (allRules.length === 0) return ''
-
-    return native.generateCss(JSON.stringify(allRules))
+    return compiler?.stylesheet() ?? ''
   }
 }
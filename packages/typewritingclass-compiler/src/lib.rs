@@ -1,15 +1,37 @@
 mod css;
+mod diagnostics;
 mod extractor;
+mod fold;
 mod hash;
+mod minify;
 mod modifiers;
+mod modules;
+mod scope;
+mod sourcemap;
 mod style_rule;
+mod stylesheet;
 mod theme;
+mod theme_input;
 mod tokens;
 mod utilities;
+mod visit;
 
-use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{LazyLock, Mutex};
+use napi::bindgen_prelude::{FnArgs, Function};
+use napi::{Error, Status};
 use napi_derive::napi;
 
+use modules::{FsResolver, ModuleCache, ModuleLoader, ModuleResolver};
+use style_rule::StyleRule;
+use stylesheet::Stylesheet;
+
+/// `(specifier, importer) => string | null`
+type ResolveFn<'a> = Function<'a, FnArgs<(String, String)>, Option<String>>;
+
+/// Evaluated exports of imported modules, shared by every transform
+static MODULE_CACHE: LazyLock<Mutex<ModuleCache>> = LazyLock::new(Default::default);
+
 /// Theme data passed from the Vite plugin (loaded from the TS theme package)
 #[napi(object)]
 pub struct ThemeInput {
@@ -25,6 +47,10 @@ pub struct ThemeInput {
     pub font_weights: String,
     /// JSON: { "sans": "ui-sans-serif, system-ui, ...", ... }
     pub font_families: String,
+    /// JSON: { "tight": "-0.025em", "wide": "0.025em", ... }
+    pub tracking: Option<String>,
+    /// JSON: { "snug": "1.375", "6": "1.5rem", ... }
+    pub leading: Option<String>,
     /// JSON: { "none": "0px", "sm": "0.125rem", "DEFAULT": "0.25rem", ... }
     pub radii: String,
     /// JSON: { "sm": "0 1px 2px ...", "DEFAULT": "0 1px 3px ...", ... }
@@ -35,6 +61,14 @@ pub struct ThemeInput {
     pub animations: String,
     /// JSON: { "spin": "@keyframes spin { ... }", ... }
     pub keyframes: String,
+    /// JSON: { "sm": "24rem", "md": "28rem", ... }
+    pub containers: Option<String>,
+    /// JSON: { "sm": "640px", "md": "768px", ... } (default: the runtime's breakpoints)
+    pub breakpoints: Option<String>,
+    /// How `dark` applies: "media" (default), "class" or "attr"
+    pub dark_mode: Option<String>,
+    /// Selector for the "class" and "attr" dark modes (default: `.dark` / `[data-theme="dark"]`)
+    pub dark_selector: Option<String>,
     /// Default border-radius value
     pub default_radius: String,
     /// Default shadow value
@@ -46,72 +80,181 @@ pub struct ExtractedRule {
     pub class_name: String,
     pub css_text: String,
     pub layer: u32,
+    /// The rule's place among those of its file, so rules of the same shape
+    /// keep their file order in deterministic order; its layer when omitted
+    pub position: Option<u32>,
+    /// Named cascade layer set with `layer('name')`
+    pub cascade_layer: Option<String>,
+    /// Explicit priority set with `layer(n)`
+    pub priority: Option<u32>,
+    // The shape of the rule, so `generateCss` can order and group rules
+    // without re-reading their sources
+    pub declarations: Vec<Declaration>,
+    pub selectors: Vec<String>,
+    pub selector_template: Option<String>,
+    pub media_queries: Vec<String>,
+    pub supports_queries: Vec<String>,
+    pub container_queries: Vec<String>,
+    /// Extra CSS emitted alongside the rule, e.g. `@keyframes`
+    pub extra_css: Vec<String>,
+}
+
+#[napi(object)]
+pub struct Declaration {
+    pub property: String,
+    pub value: String,
+}
+
+impl From<extractor::CssRule> for ExtractedRule {
+    fn from((class_name, css_text, layer, rule): extractor::CssRule) -> Self {
+        ExtractedRule {
+            class_name,
+            css_text,
+            layer,
+            position: None,
+            cascade_layer: rule.layer,
+            priority: rule.priority,
+            declarations: rule
+                .declarations
+                .into_iter()
+                .map(|(property, value)| Declaration { property, value })
+                .collect(),
+            selectors: rule.selectors,
+            selector_template: rule.selector_template,
+            media_queries: rule.media_queries,
+            supports_queries: rule.supports_queries,
+            container_queries: rule.container_queries,
+            extra_css: rule.extra_css,
+        }
+    }
+}
+
+impl From<ExtractedRule> for extractor::CssRule {
+    fn from(extracted: ExtractedRule) -> Self {
+        let mut rule = StyleRule::new(vec![]);
+        rule.declarations = extracted
+            .declarations
+            .into_iter()
+            .map(|declaration| (declaration.property, declaration.value))
+            .collect();
+        rule.selectors = extracted.selectors;
+        rule.selector_template = extracted.selector_template;
+        rule.media_queries = extracted.media_queries;
+        rule.supports_queries = extracted.supports_queries;
+        rule.container_queries = extracted.container_queries;
+        rule.extra_css = extracted.extra_css;
+        rule.layer = extracted.cascade_layer;
+        rule.priority = extracted.priority;
+        (extracted.class_name, extracted.css_text, extracted.layer, rule)
+    }
+}
+
+/// Two rules that share a class name but render different CSS
+#[napi(object)]
+pub struct ClassCollision {
+    pub class_name: String,
+    /// CSS of the rule that was kept
+    pub css_text: String,
+    /// CSS of the rule that was dropped
+    pub other_css_text: String,
+}
+
+impl From<css::ClassCollision> for ClassCollision {
+    fn from(collision: css::ClassCollision) -> Self {
+        ClassCollision {
+            class_name: collision.class_name,
+            css_text: collision.css,
+            other_css_text: collision.other_css,
+        }
+    }
+}
+
+#[napi(object)]
+pub struct StylesheetOutput {
+    pub css: String,
+    /// Class names shared by rules with different CSS; the first rule of
+    /// each is kept
+    pub collisions: Vec<ClassCollision>,
+}
+
+fn stylesheet_output((css, collisions): (String, Vec<css::ClassCollision>)) -> StylesheetOutput {
+    StylesheetOutput {
+        css,
+        collisions: collisions.into_iter().map(ClassCollision::from).collect(),
+    }
 }
 
 #[napi(object)]
 pub struct Diagnostic {
     pub message: String,
+    /// 1-based line of the start of the span
     pub line: u32,
+    /// 0-based UTF-16 column of the start of the span
     pub column: u32,
+    /// 1-based line of the end of the span
+    pub end_line: u32,
+    /// 0-based UTF-16 column of the end of the span
+    pub end_column: u32,
+    /// Byte offset of the start of the span
+    pub start: u32,
+    /// Byte offset of the end of the span
+    pub end: u32,
     pub severity: String, // "error" | "warning"
+    /// Pre-rendered code frame highlighting the span
+    pub code_frame: Option<String>,
+}
+
+/// A malformed value in the theme input
+#[napi(object)]
+pub struct ThemeIssue {
+    /// The `ThemeInput` field, e.g. `textSizes`
+    pub field: String,
+    /// JSON path of the value within the field, e.g. `$.blue["500"]`
+    pub path: String,
+    pub reason: String,
+}
+
+/// Optional transform settings
+#[napi(object, object_to_js = false)]
+pub struct TransformOptions<'a> {
+    /// Render a code frame for every diagnostic (default: true)
+    pub code_frame: Option<bool>,
+    /// Extract from the parts of a file that parsed when it contains
+    /// recoverable syntax errors (default: false)
+    pub recover: Option<bool>,
+    /// Generate a v3 source map for the transformed code (default: false)
+    pub source_map: Option<bool>,
+    /// Embed the original source in the map's `sourcesContent` (default: true)
+    pub sources_content: Option<bool>,
+    /// Project root. Relative imports of files inside it are followed so their
+    /// exported style constants can be inlined.
+    pub root: Option<String>,
+    /// Resolve an import to an absolute file path whose exported style
+    /// constants should be inlined. Return `null` to fall back to `root`
+    /// resolution (or leave the import to the runtime).
+    pub resolve: Option<ResolveFn<'a>>,
+    /// What to do with a malformed theme: "warn" (default), which drops the
+    /// bad values and reports them in `themeWarnings`, or "throw"
+    pub theme_errors: Option<String>,
 }
 
 #[napi(object)]
 pub struct TransformOutput {
     pub code: String,
+    /// v3 source map (JSON) for `code`, when `sourceMap` is enabled
+    pub map: Option<String>,
     pub rules: Vec<ExtractedRule>,
     pub next_layer: u32,
     pub has_dynamic: bool,
     pub diagnostics: Vec<Diagnostic>,
-}
-
-fn parse_theme(input: &ThemeInput) -> theme::ThemeData {
-    let colors: HashMap<String, HashMap<String, String>> =
-        serde_json::from_str(&input.colors).unwrap_or_default();
-    let named_colors: HashMap<String, String> =
-        serde_json::from_str(&input.named_colors).unwrap_or_default();
-    let spacing: HashMap<String, String> =
-        serde_json::from_str(&input.spacing).unwrap_or_default();
-    let text_sizes_raw: HashMap<String, HashMap<String, String>> =
-        serde_json::from_str(&input.text_sizes).unwrap_or_default();
-    let text_sizes: HashMap<String, (String, String)> = text_sizes_raw
-        .into_iter()
-        .filter_map(|(k, v)| {
-            let fs = v.get("fontSize")?.clone();
-            let lh = v.get("lineHeight")?.clone();
-            Some((k, (fs, lh)))
-        })
-        .collect();
-    let font_weights: HashMap<String, String> =
-        serde_json::from_str(&input.font_weights).unwrap_or_default();
-    let font_families: HashMap<String, String> =
-        serde_json::from_str(&input.font_families).unwrap_or_default();
-    let radii: HashMap<String, String> =
-        serde_json::from_str(&input.radii).unwrap_or_default();
-    let shadows: HashMap<String, String> =
-        serde_json::from_str(&input.shadows).unwrap_or_default();
-    let sizes: HashMap<String, String> =
-        serde_json::from_str(&input.sizes).unwrap_or_default();
-    let animations: HashMap<String, String> =
-        serde_json::from_str(&input.animations).unwrap_or_default();
-    let keyframes: HashMap<String, String> =
-        serde_json::from_str(&input.keyframes).unwrap_or_default();
-
-    theme::ThemeData {
-        colors,
-        named_colors,
-        spacing,
-        text_sizes,
-        font_weights,
-        font_families,
-        radii,
-        shadows,
-        sizes,
-        animations,
-        keyframes,
-        default_radius: input.default_radius.clone(),
-        default_shadow: input.default_shadow.clone(),
-    }
+    /// Problems with the theme input, unless `themeErrors` is "throw"
+    pub theme_warnings: Vec<ThemeIssue>,
+    /// Absolute paths of the imported files whose exports were evaluated.
+    /// The file should be transformed again when any of them changes.
+    pub dependencies: Vec<String>,
+    /// Set by `Compiler.transform`: whether the file's rules differ from
+    /// those of its previous version, i.e. the stylesheet changed
+    pub rules_changed: Option<bool>,
 }
 
 #[napi]
@@ -121,20 +264,49 @@ pub fn transform(
     layer_offset: u32,
     theme_input: ThemeInput,
     strict: Option<bool>,
-) -> TransformOutput {
-    let theme_data = parse_theme(&theme_input);
-    let strict_mode = strict.unwrap_or(true);
-    let result = extractor::transform(&code, &filename, layer_offset, &theme_data, strict_mode);
+    options: Option<TransformOptions>,
+) -> napi::Result<TransformOutput> {
+    let (theme_data, theme_issues) =
+        load_theme(&theme_input, options.as_ref().and_then(|o| o.theme_errors.as_deref()))?;
+    let extract_options = extractor::ExtractOptions {
+        strict: strict.unwrap_or(true),
+        code_frame: options.as_ref().and_then(|o| o.code_frame).unwrap_or(true),
+        recover: options.as_ref().and_then(|o| o.recover).unwrap_or(false),
+        source_map: options.as_ref().and_then(|o| o.source_map).unwrap_or(false),
+        sources_content: options.as_ref().and_then(|o| o.sources_content).unwrap_or(true),
+    };
+    let resolver = ImportResolver {
+        callback: options.as_ref().and_then(|o| o.resolve.as_ref()),
+        root: options.as_ref().and_then(|o| o.root.as_deref()).map(FsResolver::new),
+    };
+    let (result, dependencies) = if resolver.is_active() {
+        let mut cache = MODULE_CACHE.lock().unwrap_or_else(|e| e.into_inner());
+        let mut loader = ModuleLoader::new(&resolver, &mut cache, &theme_data);
+        let result =
+            extractor::transform(&code, &filename, layer_offset, &theme_data, &extract_options, Some(&mut loader));
+        (result, loader.dependencies())
+    } else {
+        (extractor::transform(&code, &filename, layer_offset, &theme_data, &extract_options, None), vec![])
+    };
+
+    Ok(transform_output(result, layer_offset, theme_issues, dependencies))
+}
 
+fn transform_output(
+    result: extractor::TransformResult,
+    layer_offset: u32,
+    theme_issues: Vec<theme_input::ThemeIssue>,
+    dependencies: Vec<PathBuf>,
+) -> TransformOutput {
     TransformOutput {
         code: result.code,
+        map: result.map,
         rules: result
             .css_rules
             .into_iter()
-            .map(|(class_name, css_text, layer)| ExtractedRule {
-                class_name,
-                css_text,
-                layer,
+            .map(|rule| ExtractedRule {
+                position: Some(rule.2.saturating_sub(layer_offset)),
+                ..ExtractedRule::from(rule)
             })
             .collect(),
         next_layer: result.next_layer,
@@ -146,14 +318,354 @@ pub fn transform(
                 message: d.message,
                 line: d.line,
                 column: d.column,
+                end_line: d.end_line,
+                end_column: d.end_column,
+                start: d.start,
+                end: d.end,
                 severity: d.severity,
+                code_frame: d.code_frame,
             })
             .collect(),
+        theme_warnings: theme_issues.into_iter().map(ThemeIssue::from).collect(),
+        dependencies: dependencies
+            .into_iter()
+            .map(|path| path.to_string_lossy().into_owned())
+            .collect(),
+        rules_changed: None,
     }
 }
 
+/// Every problem with a theme input. An empty list means `transform` will
+/// accept it.
 #[napi]
-pub fn generate_css(rules_json: String) -> String {
-    let rules: Vec<String> = serde_json::from_str(&rules_json).unwrap_or_default();
-    css::wrap_in_layer(&rules)
+pub fn validate_theme(theme_input: ThemeInput) -> Vec<ThemeIssue> {
+    theme_input::parse_theme(&theme_input).1.into_iter().map(ThemeIssue::from).collect()
+}
+
+impl From<theme_input::ThemeIssue> for ThemeIssue {
+    fn from(issue: theme_input::ThemeIssue) -> Self {
+        ThemeIssue {
+            field: issue.field,
+            path: issue.path,
+            reason: issue.reason,
+        }
+    }
+}
+
+/// Parse the theme input, failing on any problem only when `theme_errors`
+/// is "throw"
+fn load_theme(
+    input: &ThemeInput,
+    theme_errors: Option<&str>,
+) -> napi::Result<(theme::ThemeData, Vec<theme_input::ThemeIssue>)> {
+    let (theme, issues) = theme_input::parse_theme(input);
+    match theme_errors {
+        None | Some("warn") => Ok((theme, issues)),
+        Some("throw") if !issues.is_empty() => Err(invalid_theme(&issues)),
+        Some("throw") => Ok((theme, issues)),
+        Some(other) => Err(Error::new(
+            Status::InvalidArg,
+            format!("Invalid themeErrors \"{}\": expected \"warn\" or \"throw\"", other),
+        )),
+    }
+}
+
+/// The error thrown for a malformed theme, listing every problem
+fn invalid_theme(issues: &[theme_input::ThemeIssue]) -> Error {
+    let lines: Vec<String> = issues.iter().map(|issue| format!("  {}", issue)).collect();
+    Error::new(Status::InvalidArg, format!("Invalid theme:\n{}", lines.join("\n")))
+}
+
+/// Settings for a `Compiler`
+#[napi(object)]
+#[derive(Default)]
+pub struct CompilerOptions {
+    /// Report styles that can't be evaluated statically as errors (default: true)
+    pub strict: Option<bool>,
+    /// Render a code frame for every diagnostic (default: true)
+    pub code_frame: Option<bool>,
+    /// Extract from the parts of a file that parsed when it contains
+    /// recoverable syntax errors (default: false)
+    pub recover: Option<bool>,
+    /// Generate a v3 source map for the transformed code (default: false)
+    pub source_map: Option<bool>,
+    /// Embed the original source in the map's `sourcesContent` (default: true)
+    pub sources_content: Option<bool>,
+    /// Project root. Relative imports of files inside it are followed so their
+    /// exported style constants can be inlined.
+    pub root: Option<String>,
+    /// What to do with a malformed theme: "warn" (default), which drops the
+    /// bad values and reports them in `themeWarnings`, or "throw"
+    pub theme_errors: Option<String>,
+    /// Cascade layer order of the stylesheet (default: reset, base,
+    /// components, utilities, overrides). Rules are only layered once one is
+    /// given a named layer; empty to emit them unlayered regardless.
+    pub layer_order: Option<Vec<String>>,
+    /// How rules are ordered within a layer: "source" (default), in the order
+    /// they were compiled, or "deterministic", by the shape of each rule so
+    /// the output doesn't depend on the order files were transformed in
+    pub order: Option<String>,
+    /// Emit rules sharing the same `@media`, `@supports` and `@container`
+    /// context in one block, with min-width queries ascending and max-width
+    /// queries descending. Rules only move when that can't change which
+    /// declaration wins. (default: false)
+    pub group_at_rules: Option<bool>,
+    /// Emit minified CSS: no whitespace, short hex colors and zero lengths,
+    /// no final semicolons, and the selectors of rules with identical
+    /// declarations merged. Class names are unchanged. (default: false)
+    pub minify: Option<bool>,
+}
+
+/// How `generateCss` assembles a stylesheet
+#[napi(object)]
+#[derive(Default)]
+pub struct StylesheetOptions {
+    /// Cascade layer order (default: reset, base, components, utilities,
+    /// overrides). Rules are only layered once one is given a named layer;
+    /// empty to emit them unlayered regardless.
+    pub layer_order: Option<Vec<String>>,
+    /// "source" (default) or "deterministic"; see `CompilerOptions.order`
+    pub order: Option<String>,
+    /// See `CompilerOptions.groupAtRules` (default: false)
+    pub group_at_rules: Option<bool>,
+    /// See `CompilerOptions.minify` (default: false)
+    pub minify: Option<bool>,
+}
+
+impl StylesheetOptions {
+    fn parse(self) -> napi::Result<css::StylesheetOptions> {
+        let defaults = css::StylesheetOptions::default();
+        let order = match self.order.as_deref() {
+            None => defaults.order,
+            Some(order) => css::RuleOrder::parse(order).ok_or_else(|| {
+                Error::new(
+                    Status::InvalidArg,
+                    format!("Invalid order \"{}\": expected \"source\" or \"deterministic\"", order),
+                )
+            })?,
+        };
+        Ok(css::StylesheetOptions {
+            layer_order: self.layer_order.unwrap_or(defaults.layer_order),
+            order,
+            group_at_rules: self.group_at_rules.unwrap_or(defaults.group_at_rules),
+            minify: self.minify.unwrap_or(defaults.minify),
+        })
+    }
+}
+
+/// A compiler for one build: the theme is parsed once, and the rules of
+/// every transformed file are kept until the file changes or is removed, so
+/// `stylesheet()` always reflects the current sources.
+#[napi]
+pub struct Compiler {
+    theme: theme::ThemeData,
+    theme_issues: Vec<theme_input::ThemeIssue>,
+    options: extractor::ExtractOptions,
+    root: Option<String>,
+    modules: ModuleCache,
+    stylesheet: Stylesheet,
+}
+
+#[napi]
+impl Compiler {
+    #[napi(constructor)]
+    pub fn new(theme_input: ThemeInput, options: Option<CompilerOptions>) -> napi::Result<Self> {
+        let options = options.unwrap_or_default();
+        let (theme, theme_issues) = load_theme(&theme_input, options.theme_errors.as_deref())?;
+        let stylesheet_options = StylesheetOptions {
+            layer_order: options.layer_order,
+            order: options.order,
+            group_at_rules: options.group_at_rules,
+            minify: options.minify,
+        }
+        .parse()?;
+        Ok(Compiler {
+            theme,
+            theme_issues,
+            options: extractor::ExtractOptions {
+                strict: options.strict.unwrap_or(true),
+                code_frame: options.code_frame.unwrap_or(true),
+                recover: options.recover.unwrap_or(false),
+                source_map: options.source_map.unwrap_or(false),
+                sources_content: options.sources_content.unwrap_or(true),
+            },
+            root: options.root,
+            modules: ModuleCache::default(),
+            stylesheet: Stylesheet::new(stylesheet_options),
+        })
+    }
+
+    /// Problems with the theme input, unless `themeErrors` is "throw"
+    #[napi(getter)]
+    pub fn theme_warnings(&self) -> Vec<ThemeIssue> {
+        self.theme_issues.iter().cloned().map(ThemeIssue::from).collect()
+    }
+
+    /// Transform a file and keep its rules in place of those of its previous
+    /// version, unless syntax errors stopped it from being read. `resolve`
+    /// maps an import to a file whose exported style constants should be
+    /// inlined, like `TransformOptions.resolve`.
+    #[napi]
+    pub fn transform(&mut self, code: String, filename: String, resolve: Option<ResolveFn>) -> TransformOutput {
+        let layer_offset = self.stylesheet.layer_offset(&filename);
+        let resolver = ImportResolver {
+            callback: resolve.as_ref(),
+            root: self.root.as_deref().map(FsResolver::new),
+        };
+        let (result, dependencies) = if resolver.is_active() {
+            let mut loader = ModuleLoader::new(&resolver, &mut self.modules, &self.theme);
+            let result =
+                extractor::transform(&code, &filename, layer_offset, &self.theme, &self.options, Some(&mut loader));
+            (result, loader.dependencies())
+        } else {
+            (extractor::transform(&code, &filename, layer_offset, &self.theme, &self.options, None), vec![])
+        };
+        // A file that doesn't parse keeps the rules of its last good version
+        // rather than dropping its styles while it's being edited
+        let rules_changed =
+            result.parsed && self.stylesheet.set_rules(&filename, result.css_rules.clone(), result.next_layer);
+        TransformOutput {
+            rules_changed: Some(rules_changed),
+            ..transform_output(result, layer_offset, vec![], dependencies)
+        }
+    }
+
+    /// Forget a deleted file: its rules leave the stylesheet and modules
+    /// evaluated from it are dropped. Returns whether it had rules.
+    #[napi]
+    pub fn remove_file(&mut self, filename: String) -> bool {
+        self.invalidate(filename.clone());
+        self.stylesheet.remove(&filename)
+    }
+
+    /// Drop the cached exports of modules evaluated from a changed file
+    #[napi]
+    pub fn invalidate(&mut self, path: String) {
+        let path = PathBuf::from(path);
+        self.modules.invalidate(&path.canonicalize().unwrap_or(path));
+    }
+
+    /// The CSS of every file's rules, in layer order with each class once
+    #[napi]
+    pub fn stylesheet(&self) -> String {
+        self.stylesheet.render()
+    }
+
+    /// The stylesheet, plus any class name shared by rules with different CSS
+    #[napi]
+    pub fn build_stylesheet(&self) -> StylesheetOutput {
+        stylesheet_output(self.stylesheet.build())
+    }
+}
+
+/// Assemble a stylesheet from extracted rules, e.g. those of several
+/// `transform` calls. Rules are deduplicated by class name; a class name
+/// whose rules render different CSS is reported in `collisions`.
+#[napi]
+pub fn build_stylesheet(rules: Vec<ExtractedRule>, options: Option<StylesheetOptions>) -> napi::Result<StylesheetOutput> {
+    let options = options.unwrap_or_default().parse()?;
+    // A rule's position and layer give the layer offset of its file
+    let rules: Vec<(extractor::CssRule, u32)> = rules
+        .into_iter()
+        .map(|rule| {
+            let offset = rule.layer.saturating_sub(rule.position.unwrap_or(rule.layer));
+            (extractor::CssRule::from(rule), offset)
+        })
+        .collect();
+    Ok(stylesheet_output(stylesheet::build(rules.iter().map(|(rule, offset)| (rule, *offset)), &options)))
+}
+
+/// Assemble a stylesheet from a JSON array of rules, either CSS strings
+/// (layered in array order) or `ExtractedRule` objects. Duplicate rules are
+/// emitted once; JSON that isn't an array throws.
+#[napi]
+pub fn generate_css(rules_json: String, options: Option<StylesheetOptions>) -> napi::Result<String> {
+    let options = options.unwrap_or_default().parse()?;
+    let json: Vec<serde_json::Value> = serde_json::from_str(&rules_json)
+        .map_err(|err| Error::new(Status::InvalidArg, format!("Invalid rules JSON: {}", err)))?;
+    let parsed: Vec<(extractor::CssRule, u32)> = json
+        .iter()
+        .enumerate()
+        .filter_map(|(index, rule)| match rule {
+            serde_json::Value::String(css) => {
+                Some(((String::new(), css.clone(), index as u32, StyleRule::new(vec![])), 0))
+            }
+            serde_json::Value::Object(rule) => {
+                let layer = rule.get("layer").and_then(|layer| layer.as_u64()).unwrap_or(index as u64) as u32;
+                let position = rule.get("position").and_then(|position| position.as_u64()).map(|p| p as u32);
+                let rule = (
+                    json_string(rule, "className").unwrap_or_default(),
+                    json_string(rule, "cssText")?,
+                    layer,
+                    rule_from_json(rule),
+                );
+                Some((rule, layer.saturating_sub(position.unwrap_or(layer))))
+            }
+            _ => None,
+        })
+        .collect();
+    Ok(stylesheet::build(parsed.iter().map(|(rule, offset)| (rule, *offset)), &options).0)
+}
+
+/// The `StyleRule` described by an `ExtractedRule` object; missing fields
+/// are left empty
+fn rule_from_json(json: &serde_json::Map<String, serde_json::Value>) -> StyleRule {
+    let strings = |key: &str| -> Vec<String> {
+        json.get(key)
+            .and_then(|value| value.as_array())
+            .map(|items| items.iter().filter_map(|item| item.as_str().map(String::from)).collect())
+            .unwrap_or_default()
+    };
+    let mut rule = StyleRule::new(vec![]);
+    rule.declarations = json
+        .get("declarations")
+        .and_then(|value| value.as_array())
+        .map(|declarations| {
+            declarations
+                .iter()
+                .filter_map(|declaration| {
+                    let declaration = declaration.as_object()?;
+                    Some((json_string(declaration, "property")?, json_string(declaration, "value")?))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    rule.selectors = strings("selectors");
+    rule.selector_template = json_string(json, "selectorTemplate");
+    rule.media_queries = strings("mediaQueries");
+    rule.supports_queries = strings("supportsQueries");
+    rule.container_queries = strings("containerQueries");
+    rule.extra_css = strings("extraCss");
+    rule.layer = json_string(json, "cascadeLayer");
+    rule.priority = json.get("priority").and_then(|priority| priority.as_u64()).map(|priority| priority as u32);
+    rule
+}
+
+fn json_string(json: &serde_json::Map<String, serde_json::Value>, key: &str) -> Option<String> {
+    json.get(key).and_then(|value| value.as_str()).map(String::from)
+}
+
+/// The user's `resolve` callback, falling back to `root`-relative resolution
+struct ImportResolver<'a, 'f> {
+    callback: Option<&'f ResolveFn<'a>>,
+    root: Option<FsResolver>,
+}
+
+impl ImportResolver<'_, '_> {
+    /// Whether imports are followed at all
+    fn is_active(&self) -> bool {
+        self.callback.is_some() || self.root.is_some()
+    }
+}
+
+impl ModuleResolver for ImportResolver<'_, '_> {
+    fn resolve(&self, specifier: &str, importer: &str) -> Option<PathBuf> {
+        if let Some(callback) = self.callback {
+            let args = FnArgs::from((specifier.to_string(), importer.to_string()));
+            if let Ok(Some(path)) = callback.call(args) {
+                return Some(PathBuf::from(path));
+            }
+        }
+        self.root.as_ref()?.resolve(specifier, importer)
+    }
 }
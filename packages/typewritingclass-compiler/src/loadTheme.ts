@@ -59,31 +59,24 @@ function buildThemeInput(
   bordersModule: any,
   animationsModule: any,
 ): ThemeInput {
-  // Build color scales map
-  const colorScaleNames = [
-    'slate', 'gray', 'zinc', 'neutral', 'stone', 'red', 'orange', 'amber',
-    'yellow', 'lime', 'green', 'emerald', 'teal', 'cyan', 'sky', 'blue',
-    'indigo', 'violet', 'purple', 'fuchsia', 'pink', 'rose',
-  ]
+  // Every exported scale object is a color scale and every exported string
+  // a named color, so custom palettes get tokens like the defaults
   const colors: Record<string, Record<string, string>> = {}
-  for (const name of colorScaleNames) {
-    const scale = (colorsModule as Record<string, any>)[name]
-    if (scale && typeof scale === 'object') {
+  const namedColors: Record<string, string> = {}
+  for (const [name, value] of Object.entries(colorsModule as Record<string, any>)) {
+    if (name === 'default') continue
+    if (typeof value === 'string') {
+      namedColors[name] = value
+    } else if (value && typeof value === 'object') {
       colors[name] = {}
-      for (const [shade, hex] of Object.entries(scale)) {
-        colors[name][String(shade)] = hex as string
+      for (const [shade, hex] of Object.entries(value)) {
+        if (typeof hex === 'string') {
+          colors[name][String(shade)] = hex
+        }
       }
     }
   }
 
-  // Named colors
-  const namedColors: Record<string, string> = {
-    white: colorsModule.white,
-    black: colorsModule.black,
-    transparent: colorsModule.transparent,
-    currentColor: colorsModule.currentColor,
-  }
-
   // Spacing scale
   const { spacing: spacingNs } = spacingModule as any
   const spacing: Record<string, string> = {}
@@ -122,6 +115,26 @@ function buildThemeInput(
     }
   }
 
+  // Letter spacing and line height scales
+  const tracking: Record<string, string> = {}
+  const letterSpacingsObj = (typographyModule as Record<string, any>).letterSpacings
+  if (letterSpacingsObj && typeof letterSpacingsObj === 'object') {
+    for (const [name, val] of Object.entries(letterSpacingsObj)) {
+      if (typeof val === 'string') {
+        tracking[name] = val
+      }
+    }
+  }
+  const leading: Record<string, string> = {}
+  const lineHeightsObj = (typographyModule as Record<string, any>).lineHeights
+  if (lineHeightsObj && typeof lineHeightsObj === 'object') {
+    for (const [name, val] of Object.entries(lineHeightsObj)) {
+      if (typeof val === 'string') {
+        leading[name] = val
+      }
+    }
+  }
+
   // Border radii
   const radiusNames = ['none', 'sm', 'DEFAULT', 'md', 'lg', 'xl', '_2xl', '_3xl', 'full']
   const radii: Record<string, string> = {}
@@ -152,6 +165,28 @@ function buildThemeInput(
     }
   }
 
+  // Container query breakpoints
+  const containers: Record<string, string> = {}
+  const containersObj = (sizesModule as Record<string, any>).containers
+  if (containersObj && typeof containersObj === 'object') {
+    for (const [name, val] of Object.entries(containersObj)) {
+      if (typeof val === 'string') {
+        containers[name] = val
+      }
+    }
+  }
+
+  // Responsive breakpoints
+  const breakpoints: Record<string, string> = {}
+  const breakpointsObj = (sizesModule as Record<string, any>).breakpoints
+  if (breakpointsObj && typeof breakpointsObj === 'object') {
+    for (const [name, val] of Object.entries(breakpointsObj)) {
+      if (typeof val === 'string') {
+        breakpoints[name] = val
+      }
+    }
+  }
+
   // Animations
   const animationNames = ['spin', 'ping', 'pulse', 'bounce']
   const animations: Record<string, string> = {}
@@ -178,11 +213,15 @@ function buildThemeInput(
     textSizes: JSON.stringify(textSizes),
     fontWeights: JSON.stringify(fontWeights),
     fontFamilies: JSON.stringify(fontFamilies),
+    tracking: JSON.stringify(tracking),
+    leading: JSON.stringify(leading),
     radii: JSON.stringify(radii),
     shadows: JSON.stringify(shadows),
     sizes: JSON.stringify(sizes),
     animations: JSON.stringify(animations),
     keyframes: JSON.stringify(keyframesMap),
+    containers: JSON.stringify(containers),
+    breakpoints: JSON.stringify(breakpoints),
     defaultRadius: radii['DEFAULT'] ?? '0.25rem',
     defaultShadow: shadows['DEFAULT'] ?? '0 1px 3px 0 rgb(0 0 0 / 0.1), 0 1px 2px -1px rgb(0 0 0 / 0.1)',
   }
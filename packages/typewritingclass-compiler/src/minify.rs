@@ -0,0 +1,223 @@
+//! Minified CSS: whitespace removed, hex colors and zero lengths shortened
+//! and the last semicolon of each block dropped.
+
+/// Units whose zero can be written as a bare `0`. Percentages, times and
+/// angles are left alone since a bare `0` isn't always valid for them.
+const LENGTH_UNITS: &[&str] = &[
+    "px", "rem", "em", "ex", "ch", "vw", "vh", "vmin", "vmax", "svh", "lvh", "dvh", "cm", "mm", "in", "pt", "pc",
+];
+
+/// A declaration value with its whitespace collapsed, hex colors shortened
+/// and zero lengths written as `0`. Lengths inside functions such as
+/// `calc()` keep their unit, and so do those of `flex` and `flex-basis`,
+/// where `1 0px` and `1 0` mean different things. `url()` is copied as is,
+/// and custom properties are only trimmed since they may be substituted
+/// anywhere.
+pub fn minify_value(property: &str, value: &str) -> String {
+    let value = value.trim();
+    if property.starts_with("--") {
+        return value.to_string();
+    }
+    let zero_lengths = !matches!(property, "flex" | "flex-basis");
+    let mut out = String::new();
+    let mut token = String::new();
+    let mut depth = 0_u32;
+    let mut quote = None;
+    let mut in_url = false;
+    for c in value.chars() {
+        if let Some(q) = quote {
+            token.push(c);
+            if c == q {
+                quote = None;
+            }
+            continue;
+        }
+        if in_url && !matches!(c, ')' | '"' | '\'') {
+            token.push(c);
+            continue;
+        }
+        match c {
+            '"' | '\'' => {
+                quote = Some(c);
+                token.push(c);
+            }
+            '(' => {
+                in_url = token.eq_ignore_ascii_case("url");
+                push_token(&mut out, &mut token, false, false);
+                out.push('(');
+                depth += 1;
+            }
+            ')' | ',' => {
+                push_token(&mut out, &mut token, !in_url, depth == 0 && zero_lengths);
+                in_url = false;
+                trim_end_space(&mut out);
+                out.push(c);
+                depth = depth.saturating_sub(u32::from(c == ')'));
+            }
+            c if c.is_whitespace() => {
+                push_token(&mut out, &mut token, true, depth == 0 && zero_lengths);
+                if !out.is_empty() && !out.ends_with([' ', ',', '(']) {
+                    out.push(' ');
+                }
+            }
+            _ => token.push(c),
+        }
+    }
+    push_token(&mut out, &mut token, !in_url, depth == 0 && zero_lengths);
+    trim_end_space(&mut out);
+    out
+}
+
+/// Write `token` to `out`, with hex colors shortened when `hex_colors` and
+/// zero lengths when `zero_lengths`
+fn push_token(out: &mut String, token: &mut String, hex_colors: bool, zero_lengths: bool) {
+    if token.is_empty() {
+        return;
+    }
+    if hex_colors && token.starts_with('#') {
+        out.push_str(&shorten_hex(token));
+    } else if zero_lengths && is_zero_length(token) {
+        out.push('0');
+    } else {
+        out.push_str(token);
+    }
+    token.clear();
+}
+
+fn trim_end_space(out: &mut String) {
+    if out.ends_with(' ') {
+        out.pop();
+    }
+}
+
+/// `#AABBCC` -> `#abc`, `#aabbccdd` -> `#abcd`; anything else lowercased
+/// when it's a hex color, or returned as is
+fn shorten_hex(color: &str) -> String {
+    let hex = &color[1..];
+    if !matches!(hex.len(), 3 | 4 | 6 | 8) || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return color.to_string();
+    }
+    let hex = hex.to_ascii_lowercase();
+    let bytes = hex.as_bytes();
+    if matches!(hex.len(), 6 | 8) && bytes.chunks(2).all(|pair| pair[0] == pair[1]) {
+        let short: String = bytes.chunks(2).map(|pair| pair[0] as char).collect();
+        return format!("#{}", short);
+    }
+    format!("#{}", hex)
+}
+
+/// `0px`, `-0.0rem`...
+fn is_zero_length(token: &str) -> bool {
+    let number_end = token
+        .find(|c: char| !(c.is_ascii_digit() || matches!(c, '.' | '+' | '-')))
+        .unwrap_or(token.len());
+    let (number, unit) = token.split_at(number_end);
+    LENGTH_UNITS.contains(&unit) && number.parse::<f64>().is_ok_and(|n| n == 0.0)
+}
+
+/// A selector or at-rule prelude with its whitespace collapsed and the
+/// spaces after `,` and `:` removed
+pub fn minify_prelude(prelude: &str) -> String {
+    let collapsed = prelude.split_whitespace().collect::<Vec<_>>().join(" ");
+    collapsed.replace(", ", ",").replace(": ", ":")
+}
+
+/// Minify CSS that is only available as text, such as `@keyframes` blocks
+/// or rules passed to `generate_css` as strings. Declarations are told apart
+/// from selectors by ending in `;` or `}` rather than `{`.
+pub fn minify_css(css: &str) -> String {
+    let mut out = String::new();
+    let mut segment = String::new();
+    let mut quote = None;
+    let mut depth = 0_u32;
+    for c in css.chars() {
+        if let Some(q) = quote {
+            segment.push(c);
+            if c == q {
+                quote = None;
+            }
+            continue;
+        }
+        match c {
+            '"' | '\'' => {
+                quote = Some(c);
+                segment.push(c);
+            }
+            '(' | ')' => {
+                depth = if c == '(' { depth + 1 } else { depth.saturating_sub(1) };
+                segment.push(c);
+            }
+            '{' if depth == 0 => {
+                out.push_str(&minify_prelude(&segment));
+                out.push('{');
+                segment.clear();
+            }
+            ';' | '}' if depth == 0 => {
+                let declaration = minify_declaration(&segment);
+                if !declaration.is_empty() {
+                    // Separate from the previous declaration; the last one
+                    // before `}` gets no semicolon
+                    if out.ends_with(|c: char| c != '{' && c != '}' && c != ';') {
+                        out.push(';');
+                    }
+                    out.push_str(&declaration);
+                }
+                if c == '}' {
+                    out.push('}');
+                }
+                segment.clear();
+            }
+            _ => segment.push(c),
+        }
+    }
+    out.push_str(segment.trim());
+    out
+}
+
+/// `prop: value` minified, or the trimmed text when it isn't a declaration
+fn minify_declaration(text: &str) -> String {
+    let text = text.trim();
+    match text.split_once(':') {
+        Some((property, value)) => {
+            let property = property.trim();
+            format!("{}:{}", property, minify_value(property, value))
+        }
+        None => text.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_minify_value() {
+        assert_eq!(minify_value("color", "#FFFFFF"), "#fff");
+        assert_eq!(minify_value("color", "#11223344"), "#1234");
+        assert_eq!(minify_value("color", "#3b82f6"), "#3b82f6");
+        assert_eq!(minify_value("margin", "0px  auto"), "0 auto");
+        assert_eq!(minify_value("inset", "-0.0rem"), "0");
+        assert_eq!(minify_value("flex", "1 1 0%"), "1 1 0%");
+        assert_eq!(minify_value("flex", "1 0px"), "1 0px");
+        assert_eq!(minify_value("flex-basis", "0rem"), "0rem");
+        assert_eq!(minify_value("fill", "url(#aabbcc)"), "url(#aabbcc)");
+        assert_eq!(minify_value("background", "url('a  b.png') #FFFFFF"), "url('a  b.png') #fff");
+        assert_eq!(minify_value("transition-duration", "0s"), "0s");
+        assert_eq!(minify_value("width", "calc(100% - 0px)"), "calc(100% - 0px)");
+        assert_eq!(minify_value("box-shadow", "0 1px 2px 0 rgb(0 0 0 / 0.05), 0px 0px #000000"), "0 1px 2px 0 rgb(0 0 0 / 0.05),0 0 #000");
+        assert_eq!(minify_value("content", "'a  b'"), "'a  b'");
+        assert_eq!(minify_value("--tw-ring", " 0px "), "0px");
+    }
+
+    #[test]
+    fn test_minify_css() {
+        assert_eq!(
+            minify_css("@keyframes spin {\n  from {\n    transform: rotate(0deg);\n  }\n  to {\n    transform: rotate(360deg);\n  }\n}"),
+            "@keyframes spin{from{transform:rotate(0deg)}to{transform:rotate(360deg)}}"
+        );
+        assert_eq!(
+            minify_css("@media (min-width: 768px) {\n.a:hover, .b > .c {\n  color: #ffffff;\n  margin: 0px;\n}\n}"),
+            "@media (min-width:768px){.a:hover,.b > .c{color:#fff;margin:0}}"
+        );
+    }
+}
@@ -1,8 +1,28 @@
+use crate::fold;
 use crate::style_rule::StyleRule;
+use crate::theme::{DarkMode, ThemeData};
+use crate::utilities::Value;
+
+/// A modifier as written in source: a fixed one (`hover`) or a factory
+/// called with static arguments (`data('state=open')`)
+#[derive(Debug, Clone)]
+pub enum Modifier {
+    Named(String),
+    Factory(String, Vec<Value>),
+}
+
+impl Modifier {
+    pub fn apply(&self, rule: StyleRule, theme: &ThemeData) -> Option<StyleRule> {
+        match self {
+            Modifier::Named(name) => apply(name, rule, theme),
+            Modifier::Factory(name, args) => apply_factory(name, args, rule, theme),
+        }
+    }
+}
 
 /// Apply a modifier to a StyleRule by name.
 /// Returns None if the modifier name is unrecognized.
-pub fn apply(name: &str, rule: StyleRule) -> Option<StyleRule> {
+pub fn apply(name: &str, rule: StyleRule, theme: &ThemeData) -> Option<StyleRule> {
     match name {
         // ─── Pseudo-classes (basic) ──────────────────────────────────────
         "hover" => Some(rule.with_selector(":hover")),
@@ -39,22 +59,11 @@ pub fn apply(name: &str, rule: StyleRule) -> Option<StyleRule> {
         "target" => Some(rule.with_selector(":target")),
         "open_" => Some(rule.with_selector("[open]")),
 
-        // ─── Responsive (min-width) ─────────────────────────────────────
-        "sm" => Some(rule.with_media_query("(min-width: 640px)")),
-        "md" => Some(rule.with_media_query("(min-width: 768px)")),
-        "lg" => Some(rule.with_media_query("(min-width: 1024px)")),
-        "xl" => Some(rule.with_media_query("(min-width: 1280px)")),
-        "_2xl" => Some(rule.with_media_query("(min-width: 1536px)")),
-
-        // ─── Responsive (max-width) ─────────────────────────────────────
-        "maxSm" => Some(rule.with_media_query("(max-width: 639px)")),
-        "maxMd" => Some(rule.with_media_query("(max-width: 767px)")),
-        "maxLg" => Some(rule.with_media_query("(max-width: 1023px)")),
-        "maxXl" => Some(rule.with_media_query("(max-width: 1279px)")),
-        "max2xl" => Some(rule.with_media_query("(max-width: 1535px)")),
-
         // ─── Color scheme ────────────────────────────────────────────────
-        "dark" => Some(rule.with_media_query("(prefers-color-scheme: dark)")),
+        "dark" => match &theme.dark_mode {
+            DarkMode::Media => Some(rule.with_media_query("(prefers-color-scheme: dark)")),
+            DarkMode::Selector(selector) => Some(rule.with_selector_template(&format!("{} &", selector))),
+        },
 
         // ─── Media queries ───────────────────────────────────────────────
         "motionReduce" => Some(rule.with_media_query("(prefers-reduced-motion: reduce)")),
@@ -96,50 +105,267 @@ pub fn apply(name: &str, rule: StyleRule) -> Option<StyleRule> {
         "ariaRequired" => Some(rule.with_selector("[aria-required=\"true\"]")),
         "ariaSelected" => Some(rule.with_selector("[aria-selected=\"true\"]")),
 
-        // ─── Group modifiers (selector template) ─────────────────────────
-        "groupHover" => Some(rule.with_selector_template(".group:hover &")),
-        "groupFocus" => Some(rule.with_selector_template(".group:focus &")),
-        "groupActive" => Some(rule.with_selector_template(".group:active &")),
-        "groupFocusVisible" => Some(rule.with_selector_template(".group:focus-visible &")),
-        "groupFocusWithin" => Some(rule.with_selector_template(".group:focus-within &")),
-        "groupDisabled" => Some(rule.with_selector_template(".group:disabled &")),
-        "groupChecked" => Some(rule.with_selector_template(".group:checked &")),
-        "groupEmpty" => Some(rule.with_selector_template(".group:empty &")),
-        "groupFirst" => Some(rule.with_selector_template(".group:first-child &")),
-        "groupLast" => Some(rule.with_selector_template(".group:last-child &")),
-        "groupOdd" => Some(rule.with_selector_template(".group:nth-child(odd) &")),
-        "groupEven" => Some(rule.with_selector_template(".group:nth-child(even) &")),
-        "groupOpen" => Some(rule.with_selector_template(".group[open] &")),
-        "groupVisited" => Some(rule.with_selector_template(".group:visited &")),
-
-        // ─── Peer modifiers (selector template) ─────────────────────────
-        "peerHover" => Some(rule.with_selector_template(".peer:hover ~ &")),
-        "peerFocus" => Some(rule.with_selector_template(".peer:focus ~ &")),
-        "peerActive" => Some(rule.with_selector_template(".peer:active ~ &")),
-        "peerFocusVisible" => Some(rule.with_selector_template(".peer:focus-visible ~ &")),
-        "peerDisabled" => Some(rule.with_selector_template(".peer:disabled ~ &")),
-        "peerChecked" => Some(rule.with_selector_template(".peer:checked ~ &")),
-        "peerInvalid" => Some(rule.with_selector_template(".peer:invalid ~ &")),
-        "peerRequired" => Some(rule.with_selector_template(".peer:required ~ &")),
-        "peerPlaceholderShown" => Some(rule.with_selector_template(".peer:placeholder-shown ~ &")),
-        "peerFocusWithin" => Some(rule.with_selector_template(".peer:focus-within ~ &")),
-        "peerEmpty" => Some(rule.with_selector_template(".peer:empty ~ &")),
-        "peerFirst" => Some(rule.with_selector_template(".peer:first-child ~ &")),
-        "peerLast" => Some(rule.with_selector_template(".peer:last-child ~ &")),
-        "peerOdd" => Some(rule.with_selector_template(".peer:nth-child(odd) ~ &")),
-        "peerEven" => Some(rule.with_selector_template(".peer:nth-child(even) ~ &")),
-        "peerOpen" => Some(rule.with_selector_template(".peer[open] ~ &")),
-        "peerVisited" => Some(rule.with_selector_template(".peer:visited ~ &")),
-
         // ─── Direction modifiers (selector template) ─────────────────────
         "rtl" => Some(rule.with_selector_template("[dir=\"rtl\"] &")),
         "ltr" => Some(rule.with_selector_template("[dir=\"ltr\"] &")),
 
+        _ => {
+            if let Some(query) = breakpoint_query(name, theme) {
+                return Some(rule.with_media_query(&query));
+            }
+            group_peer_template(name).map(|template| rule.with_selector_template(template))
+        }
+    }
+}
+
+/// The media query of a responsive modifier, read from the theme's
+/// breakpoints: `md` is `(min-width: <md>)` and `maxMd` is just below it.
+/// `_2xl` / `max2xl` name the `2xl` breakpoint.
+fn breakpoint_query(name: &str, theme: &ThemeData) -> Option<String> {
+    if let Some(width) = theme.resolve_breakpoint(name.strip_prefix('_').unwrap_or(name)) {
+        return Some(format!("(min-width: {})", width));
+    }
+    let rest = name.strip_prefix("max")?;
+    let mut chars = rest.chars();
+    let breakpoint = format!("{}{}", chars.next()?.to_ascii_lowercase(), chars.as_str());
+    let width = theme.resolve_breakpoint(&breakpoint)?;
+    Some(format!("(max-width: {})", below(width)))
+}
+
+/// The largest width below a breakpoint: one pixel less for pixel widths,
+/// `calc()` for anything else
+fn below(width: &str) -> String {
+    match width.strip_suffix("px").and_then(|n| n.parse::<f64>().ok()) {
+        Some(px) => format!("{}px", fold::to_js_string(&Value::Num(px - 1.0)).unwrap_or_default()),
+        None => format!("calc({} - 1px)", width),
+    }
+}
+
+/// Check if a name is a responsive modifier for one of the theme's
+/// breakpoints, including user-defined names (`tablet`, `maxTablet`)
+pub fn is_breakpoint_modifier(name: &str, theme: &ThemeData) -> bool {
+    breakpoint_query(name, theme).is_some()
+}
+
+/// The selector template of a group or peer modifier
+fn group_peer_template(name: &str) -> Option<&'static str> {
+    match name {
+        // ─── Group modifiers ─────────────────────────────────────────────
+        "groupHover" => Some(".group:hover &"),
+        "groupFocus" => Some(".group:focus &"),
+        "groupActive" => Some(".group:active &"),
+        "groupFocusVisible" => Some(".group:focus-visible &"),
+        "groupFocusWithin" => Some(".group:focus-within &"),
+        "groupDisabled" => Some(".group:disabled &"),
+        "groupChecked" => Some(".group:checked &"),
+        "groupEmpty" => Some(".group:empty &"),
+        "groupFirst" => Some(".group:first-child &"),
+        "groupLast" => Some(".group:last-child &"),
+        "groupOdd" => Some(".group:nth-child(odd) &"),
+        "groupEven" => Some(".group:nth-child(even) &"),
+        "groupOpen" => Some(".group[open] &"),
+        "groupVisited" => Some(".group:visited &"),
+
+        // ─── Peer modifiers ──────────────────────────────────────────────
+        "peerHover" => Some(".peer:hover ~ &"),
+        "peerFocus" => Some(".peer:focus ~ &"),
+        "peerActive" => Some(".peer:active ~ &"),
+        "peerFocusVisible" => Some(".peer:focus-visible ~ &"),
+        "peerDisabled" => Some(".peer:disabled ~ &"),
+        "peerChecked" => Some(".peer:checked ~ &"),
+        "peerInvalid" => Some(".peer:invalid ~ &"),
+        "peerRequired" => Some(".peer:required ~ &"),
+        "peerPlaceholderShown" => Some(".peer:placeholder-shown ~ &"),
+        "peerFocusWithin" => Some(".peer:focus-within ~ &"),
+        "peerEmpty" => Some(".peer:empty ~ &"),
+        "peerFirst" => Some(".peer:first-child ~ &"),
+        "peerLast" => Some(".peer:last-child ~ &"),
+        "peerOdd" => Some(".peer:nth-child(odd) ~ &"),
+        "peerEven" => Some(".peer:nth-child(even) ~ &"),
+        "peerOpen" => Some(".peer[open] ~ &"),
+        "peerVisited" => Some(".peer:visited ~ &"),
+
+        _ => None,
+    }
+}
+
+/// A group or peer template rewritten to target the element marked with
+/// `namedGroup(name)` / `namedPeer(name)`: `.group:hover &` becomes
+/// `.group\/sidebar:hover &`
+fn named_template(template: &str, name: &str) -> String {
+    let split = template.find([':', '[']).unwrap_or(template.len());
+    format!("{}\\/{}{}", &template[..split], escape_ident(name), &template[split..])
+}
+
+/// `name` escaped to continue a class selector, like the runtime's
+/// `escapeIdentifier`: `main.nav` becomes `main\.nav`
+fn escape_ident(name: &str) -> String {
+    let mut escaped = String::with_capacity(name.len());
+    for c in name.chars() {
+        if c.is_ascii_alphanumeric() || c == '_' || c == '-' || !c.is_ascii() {
+            escaped.push(c);
+        } else if c.is_ascii_control() {
+            escaped.push_str(&format!("\\{:x} ", c as u32));
+        } else {
+            escaped.push('\\');
+            escaped.push(c);
+        }
+    }
+    escaped
+}
+
+/// The class that marks an element as a named group or peer
+/// (`namedGroup('sidebar')` is `group/sidebar`)
+pub fn marker_class(name: &str, args: &[Value]) -> Option<String> {
+    let marker = match name {
+        "namedGroup" => "group",
+        "namedPeer" => "peer",
+        _ => return None,
+    };
+    match args {
+        [Value::Str(group)] if !group.is_empty() => Some(format!("{}/{}", marker, group)),
+        _ => None,
+    }
+}
+
+/// Check if a name is a named group/peer marker
+pub fn is_marker(name: &str) -> bool {
+    matches!(name, "namedGroup" | "namedPeer")
+}
+
+/// Apply a modifier factory to a StyleRule by name and arguments.
+/// Returns None if the name is unrecognized or an argument has the wrong type.
+pub fn apply_factory(name: &str, args: &[Value], rule: StyleRule, theme: &ThemeData) -> Option<StyleRule> {
+    // Container queries take an optional container name
+    if let ("containerMin" | "containerMax", [size, rest @ ..]) = (name, args) {
+        let feature = if name == "containerMin" { "min-width" } else { "max-width" };
+        let width = match size {
+            Value::Str(s) => theme.resolve_container(s).unwrap_or(s).to_string(),
+            _ => media_length(size)?,
+        };
+        let query = match rest {
+            [] => format!("({}: {})", feature, width),
+            [Value::Str(container)] => format!("{} ({}: {})", container, feature, width),
+            _ => return None,
+        };
+        return Some(rule.with_container_query(&query));
+    }
+
+    // Group and peer modifiers take the name of the group they refer to
+    if let Some(template) = group_peer_template(name) {
+        let [Value::Str(group)] = args else {
+            return None;
+        };
+        return Some(rule.with_selector_template(&named_template(template, group)));
+    }
+    if let ("groupHas" | "peerHas", [Value::Str(s), Value::Str(group)]) = (name, args) {
+        let template = match name {
+            "groupHas" => format!(".group:has({}) &", s),
+            _ => format!(".peer:has({}) ~ &", s),
+        };
+        return Some(rule.with_selector_template(&named_template(&template, group)));
+    }
+
+    let [arg] = args else {
+        return None;
+    };
+    match (name, arg) {
+        // ─── Pseudo-classes ──────────────────────────────────────────────
+        ("has_", Value::Str(s)) => Some(rule.with_selector(&format!(":has({})", s))),
+        ("not_", Value::Str(s)) => Some(rule.with_selector(&format!(":not({})", s))),
+        ("nth", Value::Num(_) | Value::Str(_)) => {
+            Some(rule.with_selector(&format!(":nth-child({})", fold::to_js_string(arg)?)))
+        }
+
+        // ─── Attributes ──────────────────────────────────────────────────
+        ("aria", Value::Str(s)) => Some(rule.with_selector(&format!("[aria-{}]", s))),
+        ("data", Value::Str(s)) => Some(rule.with_selector(&format!("[data-{}]", s))),
+
+        // ─── Group / peer ────────────────────────────────────────────────
+        ("groupHas", Value::Str(s)) => Some(rule.with_selector_template(&format!(".group:has({}) &", s))),
+        ("peerHas", Value::Str(s)) => Some(rule.with_selector_template(&format!(".peer:has({}) ~ &", s))),
+
+        // ─── Media queries ───────────────────────────────────────────────
+        ("minWidth", Value::Num(_) | Value::Str(_)) => {
+            Some(rule.with_media_query(&format!("(min-width: {})", media_length(arg)?)))
+        }
+        ("maxWidth", Value::Num(_) | Value::Str(_)) => {
+            Some(rule.with_media_query(&format!("(max-width: {})", media_length(arg)?)))
+        }
+        ("screen", _) => Some(rule.with_media_query(&format!("(min-width: {})", screen_width(arg, theme)?))),
+        ("maxScreen", _) => {
+            Some(rule.with_media_query(&format!("(max-width: {})", below(&screen_width(arg, theme)?))))
+        }
+
+        // ─── Feature queries ─────────────────────────────────────────────
+        ("supports", Value::Str(s)) => Some(rule.with_supports_query(s)),
+
+        // ─── Cascade layers ──────────────────────────────────────────────
+        ("layer", Value::Str(s)) if !s.is_empty() => Some(rule.with_layer(s)),
+        ("layer", Value::Num(n)) if *n >= 0.0 && n.fract() == 0.0 && *n <= u32::MAX as f64 => {
+            Some(rule.with_priority(*n as u32))
+        }
+
+        _ => None,
+    }
+}
+
+/// Numbers are pixel widths, strings are used as written
+fn media_length(arg: &Value) -> Option<String> {
+    match arg {
+        Value::Num(_) => Some(format!("{}px", fold::to_js_string(arg)?)),
+        Value::Str(s) => Some(s.clone()),
+        Value::Dynamic(..) => None,
+    }
+}
+
+/// A theme breakpoint name (`'tablet'`), a CSS length, or a number of pixels
+fn screen_width(arg: &Value, theme: &ThemeData) -> Option<String> {
+    match arg {
+        Value::Str(s) => match theme.resolve_breakpoint(s) {
+            Some(width) => Some(width.to_string()),
+            // Unknown names are typos rather than lengths
+            None if s.starts_with(|c: char| c.is_ascii_digit() || c == '.') => Some(s.clone()),
+            None => None,
+        },
+        _ => media_length(arg),
+    }
+}
+
+/// Check if a name is a known modifier factory
+pub fn is_modifier_factory(name: &str) -> bool {
+    matches!(
+        name,
+        "has_" | "not_" | "nth" | "aria" | "data" | "supports"
+        | "groupHas" | "peerHas" | "minWidth" | "maxWidth"
+        | "screen" | "maxScreen" | "containerMin" | "containerMax" | "layer"
+    )
+}
+
+/// The factory behind a parameterized tw chain modifier (`tw.has(...)` is `has_`)
+pub fn tw_factory(name: &str) -> Option<&'static str> {
+    match name {
+        "has" => Some("has_"),
+        "not" => Some("not_"),
+        "nth" => Some("nth"),
+        "aria" => Some("aria"),
+        "data" => Some("data"),
+        "supports" => Some("supports"),
+        "layer" => Some("layer"),
+        "groupHas" => Some("groupHas"),
+        "peerHas" => Some("peerHas"),
+        "minWidth" => Some("minWidth"),
+        "maxWidth" => Some("maxWidth"),
+        "screen" => Some("screen"),
+        "maxScreen" => Some("maxScreen"),
+        "containerMin" => Some("containerMin"),
+        "containerMax" => Some("containerMax"),
         _ => None,
     }
 }
 
-/// Check if a name is a known modifier
+/// Check if a name is a known modifier. Responsive modifiers depend on the
+/// theme: see `is_breakpoint_modifier`.
 pub fn is_modifier(name: &str) -> bool {
     matches!(
         name,
@@ -154,9 +380,6 @@ pub fn is_modifier(name: &str) -> bool {
         // Pseudo-classes (structural)
         | "even" | "odd" | "firstOfType" | "lastOfType"
         | "onlyChild" | "onlyOfType" | "target" | "open_"
-        // Responsive
-        | "sm" | "md" | "lg" | "xl" | "_2xl"
-        | "maxSm" | "maxMd" | "maxLg" | "maxXl" | "max2xl"
         // Color scheme
         | "dark"
         // Media queries
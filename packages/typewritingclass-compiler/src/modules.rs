@@ -0,0 +1,178 @@
+//! Resolution and caching of imported modules whose exported style
+//! constants are evaluated at compile time.
+
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::extractor::{self, Binding};
+use crate::theme::ThemeData;
+
+/// Maps an import specifier to the file it refers to
+pub trait ModuleResolver {
+    /// Absolute path of `specifier` imported from `importer`, or `None` to
+    /// leave the import to the runtime
+    fn resolve(&self, specifier: &str, importer: &str) -> Option<PathBuf>;
+}
+
+/// Candidate suffixes tried, in order, for an extensionless relative import
+const EXTENSIONS: &[&str] = &["ts", "tsx", "mts", "js", "jsx", "mjs"];
+
+/// Resolves relative imports (`./styles`, `../theme/card.js`) to files
+/// inside a project root
+pub struct FsResolver {
+    root: PathBuf,
+}
+
+impl FsResolver {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        let root = root.into();
+        Self {
+            root: root.canonicalize().unwrap_or(root),
+        }
+    }
+
+    fn find_file(&self, base: &Path) -> Option<PathBuf> {
+        let mut candidates = vec![base.to_path_buf()];
+        // TypeScript ESM projects import `./styles.js` for `./styles.ts`
+        if let Some(ext @ ("js" | "jsx" | "mjs")) = base.extension().and_then(|e| e.to_str()) {
+            let ts_ext = match ext {
+                "jsx" => "tsx",
+                "mjs" => "mts",
+                _ => "ts",
+            };
+            candidates.push(base.with_extension(ts_ext));
+        }
+        for ext in EXTENSIONS {
+            candidates.push(append_extension(base, ext));
+        }
+        for ext in EXTENSIONS {
+            candidates.push(base.join(format!("index.{}", ext)));
+        }
+        candidates
+            .into_iter()
+            .filter(|path| path.is_file())
+            .filter_map(|path| path.canonicalize().ok())
+            .find(|path| path.starts_with(&self.root))
+    }
+}
+
+impl ModuleResolver for FsResolver {
+    fn resolve(&self, specifier: &str, importer: &str) -> Option<PathBuf> {
+        if !specifier.starts_with("./") && !specifier.starts_with("../") {
+            return None;
+        }
+        let dir = Path::new(importer).parent()?;
+        self.find_file(&dir.join(specifier))
+    }
+}
+
+fn append_extension(path: &Path, ext: &str) -> PathBuf {
+    let mut s = path.as_os_str().to_owned();
+    s.push(".");
+    s.push(ext);
+    PathBuf::from(s)
+}
+
+/// Evaluated exports of imported modules, reused across transforms until one
+/// of the files they were computed from changes
+#[derive(Default)]
+pub struct ModuleCache {
+    entries: HashMap<PathBuf, CachedModule>,
+}
+
+impl ModuleCache {
+    /// Drop every module evaluated from `path`, whether it's the module
+    /// itself or a file it imports
+    pub fn invalidate(&mut self, path: &Path) {
+        self.entries
+            .retain(|module, entry| module != path && entry.sources.iter().all(|(p, _)| p != path));
+    }
+}
+
+struct CachedModule {
+    theme: u64,
+    /// Every file read while evaluating the module, with the content read
+    sources: Vec<(PathBuf, String)>,
+    exports: HashMap<String, Binding>,
+}
+
+/// Loads imported modules for a single transform
+pub struct ModuleLoader<'r> {
+    resolver: &'r dyn ModuleResolver,
+    cache: &'r mut ModuleCache,
+    theme: u64,
+    /// Modules currently being evaluated, to break import cycles
+    in_progress: HashSet<PathBuf>,
+    /// Files read so far, recorded against the modules that depend on them
+    reads: Vec<(PathBuf, String)>,
+}
+
+impl<'r> ModuleLoader<'r> {
+    pub fn new(resolver: &'r dyn ModuleResolver, cache: &'r mut ModuleCache, theme: &ThemeData) -> Self {
+        Self {
+            resolver,
+            cache,
+            theme: theme.fingerprint(),
+            in_progress: HashSet::new(),
+            reads: vec![],
+        }
+    }
+
+    /// The statically known exports of the module `specifier` imported from
+    /// `importer`. `None` when it can't be resolved or read.
+    pub fn exports(
+        &mut self,
+        specifier: &str,
+        importer: &str,
+        theme: &ThemeData,
+    ) -> Option<HashMap<String, Binding>> {
+        let path = self.resolver.resolve(specifier, importer)?;
+
+        if let Some(entry) = self.cache.entries.get(&path) {
+            let fresh = entry.theme == self.theme
+                && entry
+                    .sources
+                    .iter()
+                    .all(|(p, content)| fs::read_to_string(p).ok().as_ref() == Some(content));
+            if fresh {
+                self.reads.extend(entry.sources.iter().cloned());
+                return Some(entry.exports.clone());
+            }
+        }
+
+        if !self.in_progress.insert(path.clone()) {
+            // Import cycle: the module is still being evaluated further up
+            return None;
+        }
+        let source = fs::read_to_string(&path).ok();
+        let exports = source.as_ref().map(|source| {
+            let first_read = self.reads.len();
+            self.reads.push((path.clone(), source.clone()));
+            let exports = extractor::evaluate_module(source, &path.to_string_lossy(), theme, self);
+            let sources = self.reads[first_read..].to_vec();
+            self.cache.entries.insert(
+                path.clone(),
+                CachedModule {
+                    theme: self.theme,
+                    sources,
+                    exports: exports.clone(),
+                },
+            );
+            exports
+        });
+        self.in_progress.remove(&path);
+        exports
+    }
+
+    /// Every file read so far, once each in the order first read. A change
+    /// to any of them can change what the transformed file compiles to.
+    pub fn dependencies(&self) -> Vec<PathBuf> {
+        let mut seen = HashSet::new();
+        self.reads
+            .iter()
+            .filter(|(path, _)| seen.insert(path))
+            .map(|(path, _)| path.clone())
+            .collect()
+    }
+}
@@ -0,0 +1,234 @@
+//! Module-level `const` analysis used for compile-time propagation.
+//!
+//! Only top-level `const name = init` declarations are tracked. A name that is
+//! bound anywhere else in the module (a parameter, a nested declaration, a
+//! catch clause...) is treated as ambiguous and left alone, so every reference
+//! to a tracked name is guaranteed to refer to the module-level constant.
+//! A value copied into another variable (`const a = card`) shares its
+//! escapes with that variable, whatever its scope.
+
+use std::collections::HashMap;
+
+use oxc_ast::ast::*;
+use oxc_ast_visit::{walk, Visit};
+use oxc_span::{GetSpan, Span};
+
+/// A top-level constant and every place its value may change or leave the
+/// module's sight.
+pub struct LocalConst<'a> {
+    pub name: &'a str,
+    pub init: &'a Expression<'a>,
+    /// Assignments and updates that write to the binding itself
+    pub reassignments: Vec<Span>,
+    /// Places where the bound value may be mutated
+    pub escapes: Vec<Escape<'a>>,
+}
+
+#[derive(Clone)]
+pub enum Escape<'a> {
+    /// A property of the value is assigned, updated or deleted
+    Mutated(Span),
+    /// The value is passed to a call, which may mutate it
+    Argument { span: Span, callee: &'a Expression<'a> },
+}
+
+/// Find the module's top-level constants, keyed by the span of their
+/// initializer.
+pub fn analyze<'a>(program: &'a Program<'a>) -> HashMap<Span, LocalConst<'a>> {
+    let mut collector = Collector::default();
+    collector.visit_program(program);
+
+    let mut constants = HashMap::new();
+    for stmt in &program.body {
+        let decl = match stmt {
+            Statement::VariableDeclaration(decl) => decl,
+            Statement::ExportNamedDeclaration(export) => match &export.declaration {
+                Some(Declaration::VariableDeclaration(decl)) => decl,
+                _ => continue,
+            },
+            _ => continue,
+        };
+        if decl.kind != VariableDeclarationKind::Const || decl.declare {
+            continue;
+        }
+        for declarator in &decl.declarations {
+            let (BindingPattern::BindingIdentifier(id), Some(init)) = (&declarator.id, &declarator.init)
+            else {
+                continue;
+            };
+            let name = id.name.as_str();
+            if collector.binding_counts.get(name).copied().unwrap_or(0) > 1 {
+                continue;
+            }
+            constants.insert(
+                init.span(),
+                LocalConst {
+                    name,
+                    init,
+                    reassignments: collector.writes.remove(name).unwrap_or_default(),
+                    escapes: collector.escapes_of(name),
+                },
+            );
+        }
+    }
+    constants
+}
+
+#[derive(Default)]
+struct Collector<'a> {
+    binding_counts: HashMap<&'a str, usize>,
+    writes: HashMap<&'a str, Vec<Span>>,
+    escapes: HashMap<&'a str, Vec<Escape<'a>>>,
+    /// name -> the variables its value was copied into
+    aliases: HashMap<&'a str, Vec<&'a str>>,
+}
+
+impl<'a> Collector<'a> {
+    /// The escapes of `name` and of every variable its value was copied
+    /// into, directly or through other copies, in source order
+    fn escapes_of(&self, name: &'a str) -> Vec<Escape<'a>> {
+        let mut seen = vec![name];
+        let mut pending = vec![name];
+        let mut escapes = vec![];
+        while let Some(name) = pending.pop() {
+            escapes.extend(self.escapes.get(name).into_iter().flatten().cloned());
+            for &alias in self.aliases.get(name).into_iter().flatten() {
+                if !seen.contains(&alias) {
+                    seen.push(alias);
+                    pending.push(alias);
+                }
+            }
+        }
+        escapes.sort_by_key(|escape| match escape {
+            Escape::Mutated(span) | Escape::Argument { span, .. } => span.start,
+        });
+        escapes
+    }
+
+    /// `alias = value` or `const alias = value`, where `value` may be a name
+    fn record_alias(&mut self, alias: &'a str, value: &'a Expression<'a>) {
+        if let Expression::Identifier(id) = value.get_inner_expression() {
+            self.aliases.entry(id.name.as_str()).or_default().push(alias);
+        }
+    }
+
+    fn record_simple_target(&mut self, target: &'a SimpleAssignmentTarget<'a>) {
+        if let SimpleAssignmentTarget::AssignmentTargetIdentifier(id) = target {
+            self.record_write(id);
+        } else if let Some(member) = target.as_member_expression() {
+            self.record_mutation(member);
+        }
+    }
+
+    fn record_write(&mut self, id: &'a IdentifierReference<'a>) {
+        self.writes.entry(id.name.as_str()).or_default().push(id.span);
+    }
+
+    fn record_mutation(&mut self, member: &'a MemberExpression<'a>) {
+        if let Some(name) = root_identifier(member.object()) {
+            self.escapes
+                .entry(name)
+                .or_default()
+                .push(Escape::Mutated(member.span()));
+        }
+    }
+
+    fn record_arguments(&mut self, callee: &'a Expression<'a>, args: &'a [Argument<'a>]) {
+        for arg in args {
+            let expr = match arg {
+                Argument::SpreadElement(spread) => &spread.argument,
+                _ => match arg.as_expression() {
+                    Some(e) => e,
+                    None => continue,
+                },
+            };
+            if let Expression::Identifier(id) = expr {
+                self.escapes
+                    .entry(id.name.as_str())
+                    .or_default()
+                    .push(Escape::Argument { span: id.span, callee });
+            }
+        }
+    }
+}
+
+// oxc hands out nodes borrowed for the visit only; `alloc` extends them to
+// the lifetime of the program's arena they live in
+impl<'a> Visit<'a> for Collector<'a> {
+    fn visit_variable_declarator(&mut self, declarator: &VariableDeclarator<'a>) {
+        let declarator = self.alloc(declarator);
+        if let (BindingPattern::BindingIdentifier(id), Some(init)) = (&declarator.id, &declarator.init) {
+            self.record_alias(id.name.as_str(), init);
+        }
+        walk::walk_variable_declarator(self, declarator);
+    }
+
+    fn visit_assignment_expression(&mut self, assign: &AssignmentExpression<'a>) {
+        let assign = self.alloc(assign);
+        if let AssignmentTarget::AssignmentTargetIdentifier(id) = &assign.left {
+            self.record_alias(id.name.as_str(), &assign.right);
+        }
+        walk::walk_assignment_expression(self, assign);
+    }
+
+    fn visit_call_expression(&mut self, call: &CallExpression<'a>) {
+        let call = self.alloc(call);
+        self.record_arguments(&call.callee, &call.arguments);
+        walk::walk_call_expression(self, call);
+    }
+
+    fn visit_new_expression(&mut self, new: &NewExpression<'a>) {
+        let new = self.alloc(new);
+        self.record_arguments(&new.callee, &new.arguments);
+        walk::walk_new_expression(self, new);
+    }
+
+    fn visit_unary_expression(&mut self, unary: &UnaryExpression<'a>) {
+        let unary = self.alloc(unary);
+        if unary.operator == UnaryOperator::Delete
+            && let Some(member) = unary.argument.as_member_expression()
+        {
+            self.record_mutation(member);
+        }
+        walk::walk_unary_expression(self, unary);
+    }
+
+    /// Every identifier that introduces a binding (declarations, parameters,
+    /// function and class names, catch parameters, imports)
+    fn visit_binding_identifier(&mut self, id: &BindingIdentifier<'a>) {
+        *self.binding_counts.entry(self.alloc(id).name.as_str()).or_default() += 1;
+    }
+
+    /// The targets of assignments, updates and `for…in`/`for…of` heads,
+    /// including those nested in destructuring patterns
+    fn visit_simple_assignment_target(&mut self, target: &SimpleAssignmentTarget<'a>) {
+        let target = self.alloc(target);
+        self.record_simple_target(target);
+        walk::walk_simple_assignment_target(self, target);
+    }
+
+    /// `a` in `({ a } = value)`
+    fn visit_assignment_target_property_identifier(&mut self, prop: &AssignmentTargetPropertyIdentifier<'a>) {
+        let prop = self.alloc(prop);
+        self.record_write(&prop.binding);
+        walk::walk_assignment_target_property_identifier(self, prop);
+    }
+
+    // Types bind names of their own (type parameters, the parameters of
+    // function types) that never shadow a constant
+    fn visit_ts_type(&mut self, _ty: &TSType<'a>) {}
+
+    fn visit_ts_type_parameter_declaration(&mut self, _decl: &TSTypeParameterDeclaration<'a>) {}
+
+    fn visit_ts_type_alias_declaration(&mut self, _decl: &TSTypeAliasDeclaration<'a>) {}
+
+    fn visit_ts_interface_declaration(&mut self, _decl: &TSInterfaceDeclaration<'a>) {}
+}
+
+/// `a` for `a`, `a.b`, `a[0].c`...
+fn root_identifier<'a>(expr: &'a Expression<'a>) -> Option<&'a str> {
+    match expr {
+        Expression::Identifier(id) => Some(id.name.as_str()),
+        _ => root_identifier(expr.as_member_expression()?.object()),
+    }
+}
@@ -0,0 +1,233 @@
+use crate::diagnostics::LineIndex;
+
+/// A piece of the transformed output
+enum Chunk {
+    /// An unchanged byte range of the original source
+    Original(usize, usize),
+    /// Generated text. `origin` is the original byte offset it maps back to;
+    /// `None` leaves the text unmapped (e.g. injected import lines).
+    Inserted { text: String, origin: Option<u32> },
+}
+
+/// Original source plus a set of non-overlapping edits. Produces both the
+/// transformed code and a v3 source map describing it.
+pub struct SplicedSource<'a> {
+    source: &'a str,
+    chunks: Vec<Chunk>,
+}
+
+impl<'a> SplicedSource<'a> {
+    /// Apply `(start, end, replacement)` edits to `source`. A zero-width edit
+    /// is an insertion. Edits overlapping an earlier one are ignored.
+    pub fn new(source: &'a str, replacements: &[(u32, u32, String)]) -> Self {
+        let mut sorted: Vec<&(u32, u32, String)> = replacements.iter().collect();
+        sorted.sort_by_key(|r| (r.0, r.1));
+
+        let mut chunks = vec![];
+        let mut cursor = 0usize;
+        for (start, end, text) in sorted {
+            let (start, end) = (*start as usize, *end as usize);
+            if start < cursor || end > source.len() {
+                continue;
+            }
+            if start > cursor {
+                chunks.push(Chunk::Original(cursor, start));
+            }
+            chunks.push(Chunk::Inserted {
+                text: text.clone(),
+                origin: Some(start as u32),
+            });
+            cursor = end;
+        }
+        if cursor < source.len() {
+            chunks.push(Chunk::Original(cursor, source.len()));
+        }
+        Self { source, chunks }
+    }
+
+    /// Prepend unmapped text (e.g. an import line)
+    pub fn prepend(&mut self, text: String) {
+        self.chunks.insert(0, Chunk::Inserted { text, origin: None });
+    }
+
+    /// The transformed code
+    pub fn code(&self) -> String {
+        let mut out = String::with_capacity(self.source.len());
+        for chunk in &self.chunks {
+            match chunk {
+                Chunk::Original(start, end) => out.push_str(&self.source[*start..*end]),
+                Chunk::Inserted { text, .. } => out.push_str(text),
+            }
+        }
+        out
+    }
+
+    /// Render a standard v3 source map (JSON) for the transformed code.
+    /// Every edit maps to the start of the span it replaced, and unchanged
+    /// text is mapped at the start of each chunk and each line.
+    pub fn source_map(&self, filename: &str, include_content: bool) -> String {
+        let index = LineIndex::new(self.source);
+        let mut mappings = MappingsEncoder::default();
+
+        for chunk in &self.chunks {
+            match chunk {
+                Chunk::Original(start, end) => {
+                    let text = &self.source[*start..*end];
+                    mappings.add(index.line_col(*start as u32));
+                    for (i, ch) in text.char_indices() {
+                        mappings.advance(ch);
+                        if ch == '\n' && start + i + 1 < *end {
+                            mappings.add(index.line_col((start + i + 1) as u32));
+                        }
+                    }
+                }
+                Chunk::Inserted { text, origin } => {
+                    if let Some(origin) = origin {
+                        mappings.add(index.line_col(*origin));
+                    }
+                    for ch in text.chars() {
+                        mappings.advance(ch);
+                    }
+                }
+            }
+        }
+
+        let mut map = serde_json::json!({
+            "version": 3,
+            "sources": [filename],
+            "names": [],
+            "mappings": mappings.finish(),
+        });
+        if include_content {
+            map["sourcesContent"] = serde_json::json!([self.source]);
+        }
+        map.to_string()
+    }
+}
+
+/// Incrementally builds the VLQ `mappings` string for a single-source map
+#[derive(Default)]
+struct MappingsEncoder {
+    out: String,
+    /// Current generated position (0-based line, UTF-16 column)
+    gen_line: u32,
+    gen_col: u32,
+    /// Values of the previous segment, for delta encoding
+    prev_gen_col: i64,
+    prev_src_line: i64,
+    prev_src_col: i64,
+    /// Whether the current generated line already has a segment
+    line_has_segment: bool,
+    /// Last generated position that received a segment
+    last_segment: Option<(u32, u32)>,
+}
+
+impl MappingsEncoder {
+    /// Map the current generated position to `(1-based line, 0-based column)`
+    /// in the original source.
+    fn add(&mut self, (src_line, src_col): (u32, u32)) {
+        if self.last_segment == Some((self.gen_line, self.gen_col)) {
+            return;
+        }
+        if self.line_has_segment {
+            self.out.push(',');
+        }
+        let src_line = src_line as i64 - 1;
+        let src_col = src_col as i64;
+        encode_vlq(&mut self.out, self.gen_col as i64 - self.prev_gen_col);
+        encode_vlq(&mut self.out, 0); // single source
+        encode_vlq(&mut self.out, src_line - self.prev_src_line);
+        encode_vlq(&mut self.out, src_col - self.prev_src_col);
+        self.prev_gen_col = self.gen_col as i64;
+        self.prev_src_line = src_line;
+        self.prev_src_col = src_col;
+        self.line_has_segment = true;
+        self.last_segment = Some((self.gen_line, self.gen_col));
+    }
+
+    /// Move the generated position past one character of output
+    fn advance(&mut self, ch: char) {
+        if ch == '\n' {
+            self.out.push(';');
+            self.gen_line += 1;
+            self.gen_col = 0;
+            self.prev_gen_col = 0;
+            self.line_has_segment = false;
+        } else {
+            self.gen_col += ch.len_utf16() as u32;
+        }
+    }
+
+    fn finish(self) -> String {
+        self.out
+    }
+}
+
+const BASE64_CHARS: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+fn encode_vlq(out: &mut String, value: i64) {
+    let mut vlq = if value < 0 { ((-value) << 1) | 1 } else { value << 1 };
+    loop {
+        let mut digit = (vlq & 0b11111) as usize;
+        vlq >>= 5;
+        if vlq > 0 {
+            digit |= 0b100000;
+        }
+        out.push(BASE64_CHARS[digit] as char);
+        if vlq == 0 {
+            break;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vlq(value: i64) -> String {
+        let mut s = String::new();
+        encode_vlq(&mut s, value);
+        s
+    }
+
+    #[test]
+    fn test_encode_vlq() {
+        assert_eq!(vlq(0), "A");
+        assert_eq!(vlq(1), "C");
+        assert_eq!(vlq(-1), "D");
+        assert_eq!(vlq(15), "e");
+        assert_eq!(vlq(16), "gB");
+        assert_eq!(vlq(-17), "jB");
+    }
+
+    #[test]
+    fn test_splice_code() {
+        let src = "a = cx(p(4))\nb = tw.p(x)";
+        let edits = vec![(4, 12, "'_x'".to_string()), (24, 24, ".toString()".to_string())];
+        let mut spliced = SplicedSource::new(src, &edits);
+        spliced.prepend("import 'typewritingclass/inject';\n".to_string());
+        assert_eq!(
+            spliced.code(),
+            "import 'typewritingclass/inject';\na = '_x'\nb = tw.p(x).toString()"
+        );
+    }
+
+    #[test]
+    fn test_identity_map() {
+        let spliced = SplicedSource::new("ab\ncd", &[]);
+        let map = spliced.source_map("x.ts", false);
+        assert_eq!(map, r#"{"mappings":"AAAA;AACA","names":[],"sources":["x.ts"],"version":3}"#);
+    }
+
+    #[test]
+    fn test_map_accounts_for_prefix_and_replacements() {
+        let src = "a = cx(p(4))\nb";
+        let mut spliced = SplicedSource::new(src, &[(4, 12, "'_x'".to_string())]);
+        spliced.prepend("import 'x';\n".to_string());
+        let map: serde_json::Value =
+            serde_json::from_str(&spliced.source_map("x.ts", true)).unwrap();
+        // Line 1 unmapped; line 2: col 0 -> 0:0, col 4 -> 0:4, col 8 -> 0:12; line 3 -> 1:0
+        assert_eq!(map["mappings"], "; AAAA,IAAI,IAAQ;AACZ".replace(' ', ""));
+        assert_eq!(map["sourcesContent"][0], src);
+    }
+}
@@ -1,15 +1,25 @@
 /// Mirrors the TypeScript StyleRule interface
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct StyleRule {
     pub declarations: Vec<(String, String)>,
     pub selectors: Vec<String>,
     pub media_queries: Vec<String>,
+    pub supports_queries: Vec<String>,
+    /// `@container` preludes, optionally starting with a container name
+    /// (e.g. "sidebar (min-width: 28rem)")
+    pub container_queries: Vec<String>,
     pub dynamic_bindings: Vec<(String, String)>,
     /// Extra CSS blocks (e.g. @keyframes) emitted alongside this rule
     pub extra_css: Vec<String>,
     /// Selector template for group/peer/direction modifiers (e.g. ".group:hover &")
-    /// When set, `&` is replaced with the generated class name during CSS rendering.
+    /// When set, `&` is replaced with the generated class name and its selectors
+    /// during CSS rendering. Stacked templates are composed into one.
     pub selector_template: Option<String>,
+    /// Named cascade layer set with `layer('overrides')`; `None` puts the rule
+    /// in the default `utilities` layer
+    pub layer: Option<String>,
+    /// Explicit ordering priority set with `layer(0)`, `layer(1000)`...
+    pub priority: Option<u32>,
 }
 
 impl StyleRule {
@@ -21,14 +31,20 @@ impl StyleRule {
                 .collect(),
             selectors: vec![],
             media_queries: vec![],
+            supports_queries: vec![],
+            container_queries: vec![],
             dynamic_bindings: vec![],
             extra_css: vec![],
             selector_template: None,
+            layer: None,
+            priority: None,
         }
     }
 
+    /// Add a compound selector. Pseudo-elements always stay last, since
+    /// nothing may follow them (`:hover::before`, never `::before:hover`).
     pub fn with_selector(mut self, selector: &str) -> Self {
-        self.selectors.push(selector.to_string());
+        insert_selector(&mut self.selectors, selector);
         self
     }
 
@@ -37,6 +53,16 @@ impl StyleRule {
         self
     }
 
+    pub fn with_supports_query(mut self, query: &str) -> Self {
+        self.supports_queries.push(query.to_string());
+        self
+    }
+
+    pub fn with_container_query(mut self, query: &str) -> Self {
+        self.container_queries.push(query.to_string());
+        self
+    }
+
     pub fn with_dynamic_binding(mut self, var_name: &str, expr: &str) -> Self {
         self.dynamic_bindings.push((var_name.to_string(), expr.to_string()));
         self
@@ -47,27 +73,69 @@ impl StyleRule {
         self
     }
 
+    /// Wrap the rule in a selector context. An existing template takes the
+    /// place of `&`, so `rtl` over `groupHover` gives `[dir="rtl"] .group:hover &`.
     pub fn with_selector_template(mut self, tmpl: &str) -> Self {
-        self.selector_template = Some(tmpl.to_string());
+        self.selector_template = Some(match self.selector_template.take() {
+            Some(inner) => tmpl.replace('&', &inner),
+            None => tmpl.to_string(),
+        });
         self
     }
 
-    pub fn has_dynamic(&self) -> bool {
-        !self.dynamic_bindings.is_empty()
+    pub fn with_layer(mut self, layer: &str) -> Self {
+        self.layer = Some(layer.to_string());
+        self
+    }
+
+    pub fn with_priority(mut self, priority: u32) -> Self {
+        self.priority = Some(priority);
+        self
+    }
+
+    /// Combine rules into one per selector context and cascade layer, in the
+    /// order each first appears, as the runtime's `combineRules` does
+    pub fn merge_by_context(rules: &[StyleRule]) -> Vec<Self> {
+        let mut groups: Vec<Vec<StyleRule>> = vec![];
+        for rule in rules {
+            match groups
+                .iter_mut()
+                .find(|group| group[0].selector_template == rule.selector_template && group[0].layer == rule.layer)
+            {
+                Some(group) => group.push(rule.clone()),
+                None => groups.push(vec![rule.clone()]),
+            }
+        }
+        if groups.is_empty() {
+            groups.push(vec![]);
+        }
+        groups.iter().filter_map(|group| Self::merge(group)).collect()
     }
 
-    pub fn merge(rules: &[StyleRule]) -> Self {
+    /// Combine rules into one, or `None` when they sit in different selector
+    /// contexts or cascade layers and can't share a class. Priorities are
+    /// dropped, as by the runtime's `combineRules`.
+    pub fn merge(rules: &[StyleRule]) -> Option<Self> {
         let mut declarations = vec![];
         let mut selectors = vec![];
         let mut media_queries = vec![];
+        let mut supports_queries = vec![];
+        let mut container_queries = vec![];
         let mut dynamic_bindings = vec![];
         let mut extra_css = vec![];
-        let mut selector_template = None;
+        let selector_template = rules.first().and_then(|r| r.selector_template.clone());
+        if rules.iter().any(|r| r.selector_template != selector_template) {
+            return None;
+        }
+        let layer = rules.first().and_then(|r| r.layer.clone());
+        if rules.iter().any(|r| r.layer != layer) {
+            return None;
+        }
         for rule in rules {
             declarations.extend(rule.declarations.clone());
             for s in &rule.selectors {
                 if !selectors.contains(s) {
-                    selectors.push(s.clone());
+                    insert_selector(&mut selectors, s);
                 }
             }
             for mq in &rule.media_queries {
@@ -75,6 +143,16 @@ impl StyleRule {
                     media_queries.push(mq.clone());
                 }
             }
+            for sq in &rule.supports_queries {
+                if !supports_queries.contains(sq) {
+                    supports_queries.push(sq.clone());
+                }
+            }
+            for cq in &rule.container_queries {
+                if !container_queries.contains(cq) {
+                    container_queries.push(cq.clone());
+                }
+            }
             for db in &rule.dynamic_bindings {
                 if !dynamic_bindings.contains(db) {
                     dynamic_bindings.push(db.clone());
@@ -85,19 +163,36 @@ impl StyleRule {
                     extra_css.push(ec.clone());
                 }
             }
-            if selector_template.is_none() {
-                if let Some(ref tmpl) = rule.selector_template {
-                    selector_template = Some(tmpl.clone());
-                }
-            }
         }
-        Self {
+        Some(Self {
             declarations,
             selectors,
             media_queries,
+            supports_queries,
+            container_queries,
             dynamic_bindings,
             extra_css,
             selector_template,
-        }
+            layer,
+            priority: None,
+        })
     }
 }
+
+/// Insert a compound selector ahead of any pseudo-elements
+fn insert_selector(selectors: &mut Vec<String>, selector: &str) {
+    let at = if is_pseudo_element(selector) {
+        selectors.len()
+    } else {
+        selectors
+            .iter()
+            .position(|s| is_pseudo_element(s))
+            .unwrap_or(selectors.len())
+    };
+    selectors.insert(at, selector.to_string());
+}
+
+/// `::before`, `::placeholder`, `::file-selector-button`...
+pub fn is_pseudo_element(selector: &str) -> bool {
+    selector.starts_with("::")
+}
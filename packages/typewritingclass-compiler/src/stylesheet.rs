@@ -0,0 +1,423 @@
+//! The rules extracted from every file of a build, kept per file and
+//! assembled into one stylesheet.
+
+use std::collections::HashMap;
+
+use crate::css::{self, ClassCollision, LayeredRule, RuleOrder, StylesheetOptions};
+use crate::extractor::CssRule;
+
+/// Layers reserved for each file. A file keeps its offset while it's edited,
+/// so its rules keep their order relative to other files' unless it outgrows
+/// the reservation.
+const FILE_LAYERS: u32 = 1000;
+
+pub struct Stylesheet {
+    /// filename -> the rules of its latest transform
+    files: HashMap<String, Vec<CssRule>>,
+    /// filename -> first layer of the file's rules
+    offsets: HashMap<String, u32>,
+    next_layer: u32,
+    options: StylesheetOptions,
+}
+
+impl Stylesheet {
+    pub fn new(options: StylesheetOptions) -> Self {
+        Self {
+            files: HashMap::new(),
+            offsets: HashMap::new(),
+            next_layer: 0,
+            options,
+        }
+    }
+
+    /// The layer a file's rules start at, assigned the first time the file
+    /// is seen
+    pub fn layer_offset(&mut self, filename: &str) -> u32 {
+        if let Some(&offset) = self.offsets.get(filename) {
+            return offset;
+        }
+        let offset = self.next_layer;
+        self.next_layer += FILE_LAYERS;
+        self.offsets.insert(filename.to_string(), offset);
+        offset
+    }
+
+    /// Replace a file's rules with those of its latest transform, which
+    /// used layers up to `next_layer`. Returns whether the rules changed.
+    pub fn set_rules(&mut self, filename: &str, rules: Vec<CssRule>, next_layer: u32) -> bool {
+        self.next_layer = self.next_layer.max(next_layer);
+        let previous = if rules.is_empty() {
+            self.files.remove(filename)
+        } else {
+            self.files.insert(filename.to_string(), rules)
+        };
+        previous.as_ref() != self.files.get(filename)
+    }
+
+    /// Drop a file's rules. Its layer offset is kept in case it comes back.
+    pub fn remove(&mut self, filename: &str) -> bool {
+        self.files.remove(filename).is_some()
+    }
+
+    /// Every file's rules in their cascade layers, each class once
+    pub fn render(&self) -> String {
+        self.build().0
+    }
+
+    /// The stylesheet, and the class names shared by rules with different
+    /// CSS. The rule of the lowest layer is kept, or in deterministic order
+    /// the one earliest in its file.
+    pub fn build(&self) -> (String, Vec<ClassCollision>) {
+        let mut rules: Vec<(&CssRule, u32)> = self
+            .files
+            .iter()
+            .flat_map(|(filename, rules)| {
+                let offset = self.offsets.get(filename).copied().unwrap_or(0);
+                rules.iter().map(move |rule| (rule, offset))
+            })
+            .collect();
+        rules.sort_by(|(a, _), (b, _)| (a.2, &a.0, &a.1).cmp(&(b.2, &b.0, &b.1)));
+        build(rules, &self.options)
+    }
+}
+
+/// Deduplicate `rules`, each given with the layer offset of its file, by
+/// class name and render them. In deterministic order the copy kept of a
+/// class is the one earliest in its file, whatever order the files came in.
+pub fn build<'a>(
+    rules: impl IntoIterator<Item = (&'a CssRule, u32)>,
+    options: &StylesheetOptions,
+) -> (String, Vec<ClassCollision>) {
+    let mut rules: Vec<LayeredRule> = rules
+        .into_iter()
+        .map(|((class_name, css, layer, rule), offset)| LayeredRule {
+            class_name,
+            css,
+            layer: *layer,
+            position: layer.saturating_sub(offset),
+            rule,
+        })
+        .collect();
+    if options.order == RuleOrder::Deterministic {
+        rules.sort_by(|a, b| (a.position, a.class_name, a.css).cmp(&(b.position, b.class_name, b.css)));
+    }
+    let (rules, collisions) = css::dedupe_rules(rules);
+    (css::render_stylesheet(&rules, options), collisions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::style_rule::StyleRule;
+
+    fn styled(class_name: &str, layer: u32, rule: StyleRule) -> CssRule {
+        (class_name.to_string(), format!(".{} {{}}", class_name), layer, rule)
+    }
+
+    fn rule(class_name: &str, layer: u32) -> CssRule {
+        styled(class_name, layer, StyleRule::new(vec![("color", "red")]))
+    }
+
+    fn layered(class_name: &str, layer: u32, cascade_layer: &str) -> CssRule {
+        styled(class_name, layer, StyleRule::new(vec![("color", "red")]).with_layer(cascade_layer))
+    }
+
+    fn unlayered() -> StylesheetOptions {
+        StylesheetOptions {
+            layer_order: vec![],
+            ..StylesheetOptions::default()
+        }
+    }
+
+    #[test]
+    fn test_layer_offsets_are_stable() {
+        let mut sheet = Stylesheet::new(unlayered());
+        assert_eq!(sheet.layer_offset("a.tsx"), 0);
+        assert_eq!(sheet.layer_offset("b.tsx"), 1000);
+        assert_eq!(sheet.layer_offset("a.tsx"), 0);
+        // A file that outgrows its reservation pushes later files back
+        sheet.set_rules("b.tsx", vec![rule("b", 1000)], 2500);
+        assert_eq!(sheet.layer_offset("c.tsx"), 2500);
+        sheet.remove("a.tsx");
+        assert_eq!(sheet.layer_offset("a.tsx"), 0);
+    }
+
+    #[test]
+    fn test_render_orders_and_dedupes() {
+        let mut sheet = Stylesheet::new(unlayered());
+        sheet.set_rules("b.tsx", vec![rule("b1", 1000), rule("shared", 1001)], 1002);
+        sheet.set_rules("a.tsx", vec![rule("a1", 0), rule("a2", 1)], 2);
+        sheet.set_rules("c.tsx", vec![rule("shared", 1001)], 0);
+        assert_eq!(sheet.render(), ".a1 {}\n\n.a2 {}\n\n.b1 {}\n\n.shared {}");
+
+        // A new version of a file replaces its rules
+        assert!(sheet.set_rules("a.tsx", vec![rule("a3", 0)], 1));
+        assert!(!sheet.set_rules("a.tsx", vec![rule("a3", 0)], 1));
+        assert!(!sheet.set_rules("d.tsx", vec![], 0));
+        assert!(sheet.remove("b.tsx"));
+        assert!(!sheet.remove("b.tsx"));
+        assert_eq!(sheet.render(), ".a3 {}\n\n.shared {}");
+    }
+
+    #[test]
+    fn test_render_cascade_layers() {
+        let mut sheet = Stylesheet::new(StylesheetOptions::default());
+        sheet.set_rules(
+            "a.tsx",
+            vec![rule("p4", 2), layered("card", 0, "components"), layered("fix", 1, "overrides"), rule("m2", 1)],
+            3,
+        );
+        sheet.set_rules("b.tsx", vec![layered("legacy", 1000, "vendor")], 1001);
+        assert_eq!(
+            sheet.render(),
+            "@layer reset, base, components, utilities, overrides, vendor;\n\n\
+             @layer components {\n  .card {}\n}\n\n\
+             @layer utilities {\n  .m2 {}\n\n  .p4 {}\n}\n\n\
+             @layer overrides {\n  .fix {}\n}\n\n\
+             @layer vendor {\n  .legacy {}\n}"
+        );
+        assert_eq!(Stylesheet::new(StylesheetOptions::default()).render(), "");
+
+        // Without a named layer the stylesheet stays unlayered, as at runtime
+        let mut plain = Stylesheet::new(StylesheetOptions::default());
+        plain.set_rules("a.tsx", vec![rule("p4", 0), rule("m2", 1)], 2);
+        assert_eq!(plain.render(), ".p4 {}\n\n.m2 {}");
+    }
+
+    #[test]
+    fn test_render_deterministic_order() {
+        let options = StylesheetOptions {
+            order: RuleOrder::Deterministic,
+            ..unlayered()
+        };
+        let files = [
+            ("a.tsx", vec![
+                styled("md", 0, StyleRule::new(vec![("padding", "2rem")]).with_media_query("(min-width: 768px)")),
+                styled("pt", 1, StyleRule::new(vec![("padding-top", "0")])),
+                styled("hover", 2, StyleRule::new(vec![("color", "red")]).with_selector(":hover")),
+            ]),
+            ("b.tsx", vec![
+                styled("sm", 1000, StyleRule::new(vec![("padding", "1rem")]).with_media_query("(min-width: 40rem)")),
+                styled("p", 1001, StyleRule::new(vec![("padding", "1rem")])),
+                styled("max", 1002, StyleRule::new(vec![("margin", "0")]).with_media_query("(max-width: 767px)")),
+            ]),
+        ];
+        let expected = ".p {}\n\n.pt {}\n\n.hover {}\n\n.max {}\n\n.sm {}\n\n.md {}";
+
+        // The order files are compiled in doesn't matter
+        let mut sheet = Stylesheet::new(options);
+        for (filename, rules) in files.iter().cloned() {
+            sheet.set_rules(filename, rules, 0);
+        }
+        assert_eq!(sheet.render(), expected);
+        let mut reversed = Stylesheet::new(StylesheetOptions {
+            order: RuleOrder::Deterministic,
+            ..unlayered()
+        });
+        for (filename, mut rules) in files.iter().rev().cloned() {
+            rules.iter_mut().for_each(|rule| rule.2 = 5000 - rule.2);
+            reversed.set_rules(filename, rules, 0);
+        }
+        assert_eq!(reversed.render(), expected);
+    }
+
+    #[test]
+    fn test_deterministic_ties_keep_file_order() {
+        let mut sheet = Stylesheet::new(StylesheetOptions {
+            order: RuleOrder::Deterministic,
+            ..unlayered()
+        });
+        let color = |class_name: &str, layer: u32, value: &str| {
+            styled(class_name, layer, StyleRule::new(vec![("color", value)]))
+        };
+        // `b.tsx` is compiled first, so its rules sit at the lower layers
+        assert_eq!(sheet.layer_offset("b.tsx"), 0);
+        assert_eq!(sheet.layer_offset("a.tsx"), 1000);
+        sheet.set_rules("b.tsx", vec![color("x", 0, "green")], 1);
+        sheet.set_rules(
+            "a.tsx",
+            vec![
+                // The later of two same-shape rules wins, whatever its class name
+                color("z", 1000, "red"),
+                color("y", 1001, "blue"),
+                // Explicit priorities rank before everything else
+                styled("o", 1002, StyleRule::new(vec![("color", "black")]).with_priority(5)),
+                styled("r", 1003, StyleRule::new(vec![("color", "white")]).with_priority(0)),
+            ],
+            1004,
+        );
+        assert_eq!(sheet.render(), ".r {}\n\n.x {}\n\n.z {}\n\n.y {}\n\n.o {}");
+    }
+
+    #[test]
+    fn test_deterministic_dedupe_ignores_file_order() {
+        let color = |class_name: &str, layer: u32, value: &str| {
+            styled(class_name, layer, StyleRule::new(vec![("color", value)]))
+        };
+        let render = |order: [&str; 2]| {
+            let mut sheet = Stylesheet::new(StylesheetOptions {
+                order: RuleOrder::Deterministic,
+                ..unlayered()
+            });
+            for filename in order {
+                let offset = sheet.layer_offset(filename);
+                // `shared` is the second rule of a.tsx but the first of b.tsx
+                let rules = match filename {
+                    "a.tsx" => vec![color("z", offset, "red"), color("shared", offset + 1, "blue")],
+                    _ => vec![color("shared", offset, "blue")],
+                };
+                sheet.set_rules(filename, rules, offset + 2);
+            }
+            sheet.render()
+        };
+        assert_eq!(render(["a.tsx", "b.tsx"]), ".shared {}\n\n.z {}");
+        assert_eq!(render(["b.tsx", "a.tsx"]), ".shared {}\n\n.z {}");
+    }
+
+    #[test]
+    fn test_build_reports_collisions() {
+        let mut sheet = Stylesheet::new(unlayered());
+        let other = ("shared".to_string(), ".shared { color: blue; }".to_string(), 1001, StyleRule::new(vec![("color", "blue")]));
+        sheet.set_rules("a.tsx", vec![rule("shared", 0)], 1);
+        sheet.set_rules("b.tsx", vec![rule("b", 1000), other.clone()], 1002);
+        sheet.set_rules("c.tsx", vec![other, rule("shared", 2000)], 2001);
+        let (css, collisions) = sheet.build();
+        assert_eq!(css, ".shared {}\n\n.b {}");
+        assert_eq!(
+            collisions,
+            vec![ClassCollision {
+                class_name: "shared".to_string(),
+                css: ".shared {}".to_string(),
+                other_css: ".shared { color: blue; }".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_group_at_rules_mobile_first() {
+        let mut sheet = Stylesheet::new(StylesheetOptions {
+            group_at_rules: true,
+            ..unlayered()
+        });
+        let md = "(min-width: 768px)";
+        sheet.set_rules(
+            "a.tsx",
+            vec![
+                styled("p", 0, StyleRule::new(vec![("padding", "1rem")])),
+                styled("md_p", 1, StyleRule::new(vec![("padding", "2rem")]).with_media_query(md)),
+                styled("m", 2, StyleRule::new(vec![("margin", "0")])),
+                styled("md_m", 3, StyleRule::new(vec![("margin", "1rem")]).with_media_query(md)),
+                styled("sm", 4, StyleRule::new(vec![("color", "red")]).with_media_query("(min-width: 640px)")),
+                styled("max", 5, StyleRule::new(vec![("display", "none")]).with_media_query("(max-width: 767px)")),
+            ],
+            6,
+        );
+        assert_eq!(
+            sheet.render(),
+            ".p {\n  padding: 1rem;\n}\n\n.m {\n  margin: 0;\n}\n\n\
+             @media (max-width: 767px) {\n.max {\n  display: none;\n}\n}\n\n\
+             @media (min-width: 640px) {\n.sm {\n  color: red;\n}\n}\n\n\
+             @media (min-width: 768px) {\n.md_p {\n  padding: 2rem;\n}\n\n.md_m {\n  margin: 1rem;\n}\n}"
+        );
+    }
+
+    #[test]
+    fn test_group_at_rules_keeps_cascade() {
+        let mut sheet = Stylesheet::new(StylesheetOptions {
+            group_at_rules: true,
+            ..unlayered()
+        });
+        let spin = StyleRule::new(vec![("animation", "spin 1s")]).with_extra_css("@keyframes spin {}".to_string());
+        sheet.set_rules(
+            "a.tsx",
+            vec![
+                // The later padding must keep overriding the breakpoint's
+                styled("md_p", 0, StyleRule::new(vec![("padding", "2rem")]).with_media_query("(min-width: 768px)")),
+                styled("pt", 1, StyleRule::new(vec![("padding-top", "1rem")])),
+                styled("spin", 2, spin.clone()),
+                styled("spin2", 3, spin.with_selector(":hover")),
+            ],
+            4,
+        );
+        assert_eq!(
+            sheet.render(),
+            "@keyframes spin {}\n\n\
+             .spin {\n  animation: spin 1s;\n}\n\n.spin2:hover {\n  animation: spin 1s;\n}\n\n\
+             @media (min-width: 768px) {\n.md_p {\n  padding: 2rem;\n}\n}\n\n\
+             .pt {\n  padding-top: 1rem;\n}"
+        );
+    }
+
+    #[test]
+    fn test_minify() {
+        let minified = || StylesheetOptions {
+            minify: true,
+            ..StylesheetOptions::default()
+        };
+        let md = "(min-width: 768px)";
+        let rules = vec![
+            styled("a", 0, StyleRule::new(vec![("color", "#ffffff")])),
+            styled("pt", 1, StyleRule::new(vec![("padding-top", "0px")])),
+            styled("b", 2, StyleRule::new(vec![("color", "#FFF")])),
+            styled("c", 3, StyleRule::new(vec![("padding", "1rem")])),
+            // `c` sets a related property, so `e` can't join `pt`
+            styled("e", 4, StyleRule::new(vec![("padding-top", "0")])),
+            styled("md", 5, StyleRule::new(vec![("margin", "0px")]).with_media_query(md)),
+            styled("md2", 6, StyleRule::new(vec![("margin-top", "1rem")]).with_media_query(md)),
+            styled("s", 7, StyleRule::new(vec![("color", "#fff")]).with_selector("::-webkit-scrollbar")),
+            styled("f", 8, StyleRule::new(vec![("color", "#fff")]).with_layer("overrides")),
+            ("raw".to_string(), ".raw {\n  color: #000000;\n}".to_string(), 9, StyleRule::new(vec![])),
+        ];
+
+        let mut sheet = Stylesheet::new(minified());
+        sheet.set_rules("a.tsx", rules.clone(), 10);
+        assert_eq!(
+            sheet.render(),
+            "@layer reset,base,components,utilities,overrides;\
+             @layer utilities{.a,.b{color:#fff}.pt{padding-top:0}.c{padding:1rem}.e{padding-top:0}\
+             @media (min-width:768px){.md{margin:0}}@media (min-width:768px){.md2{margin-top:1rem}}\
+             .s::-webkit-scrollbar{color:#fff}.raw{color:#000}}\
+             @layer overrides{.f{color:#fff}}"
+        );
+
+        let mut grouped = Stylesheet::new(StylesheetOptions {
+            group_at_rules: true,
+            layer_order: vec![],
+            ..minified()
+        });
+        grouped.set_rules("a.tsx", rules[..7].to_vec(), 7);
+        assert_eq!(
+            grouped.render(),
+            ".a,.b{color:#fff}.pt{padding-top:0}.c{padding:1rem}.e{padding-top:0}\
+             @media (min-width:768px){.md{margin:0}.md2{margin-top:1rem}}"
+        );
+    }
+
+    #[test]
+    fn test_minify_shares_only_supported_selectors() {
+        let white = || StyleRule::new(vec![("color", "#fff")]);
+        let mut sheet = Stylesheet::new(StylesheetOptions {
+            minify: true,
+            ..unlayered()
+        });
+        sheet.set_rules(
+            "a.tsx",
+            vec![
+                styled("a", 0, white()),
+                styled("hover", 1, white().with_selector(":hover")),
+                styled("odd", 2, white().with_selector(":nth-child(odd)")),
+                styled("g", 3, white().with_selector_template(".group:hover &")),
+                // A browser without these would drop the whole list
+                styled("fv", 4, white().with_selector(":focus-visible")),
+                styled("has", 5, white().with_selector(":has(img)")),
+                styled("file", 6, white().with_selector("::file-selector-button")),
+            ],
+            7,
+        );
+        assert_eq!(
+            sheet.render(),
+            ".a,.hover:hover,.odd:nth-child(odd),.group:hover .g{color:#fff}\
+             .fv:focus-visible{color:#fff}.has:has(img){color:#fff}.file::file-selector-button{color:#fff}"
+        );
+    }
+}
@@ -1,4 +1,5 @@
 use std::collections::HashMap;
+use std::hash::{DefaultHasher, Hash, Hasher};
 
 /// Theme data loaded from the TypeScript source package at plugin init time.
 /// This is the single source of truth — the compiler never hardcodes token values.
@@ -16,6 +17,10 @@ pub struct ThemeData {
     pub font_weights: HashMap<String, String>,
     /// font family tokens: name -> CSS font-family value
     pub font_families: HashMap<String, String>,
+    /// letter-spacing tokens: name -> CSS value (e.g. "tight" -> "-0.025em")
+    pub tracking: HashMap<String, String>,
+    /// line-height tokens: name -> CSS value (e.g. "snug" -> "1.375")
+    pub leading: HashMap<String, String>,
     /// border radius tokens: name -> CSS value
     pub radii: HashMap<String, String>,
     /// shadow tokens: name -> CSS value
@@ -24,6 +29,12 @@ pub struct ThemeData {
     pub sizes: HashMap<String, String>,
     /// animation tokens: name -> full animation shorthand (e.g. "spin" -> "spin 1s linear infinite")
     pub animations: HashMap<String, String>,
+    /// container query breakpoints: name -> width (e.g. "md" -> "28rem")
+    pub containers: HashMap<String, String>,
+    /// responsive breakpoints: name -> min width (e.g. "md" -> "768px")
+    pub breakpoints: HashMap<String, String>,
+    /// how the `dark` modifier is applied
+    pub dark_mode: DarkMode,
     /// keyframes: name -> @keyframes CSS block
     pub keyframes: HashMap<String, String>,
     /// default border radius (when rounded() called with no args)
@@ -32,6 +43,38 @@ pub struct ThemeData {
     pub default_shadow: String,
 }
 
+/// How the `dark` modifier is applied
+#[derive(Debug, Clone, Default, Hash, PartialEq)]
+pub enum DarkMode {
+    /// `@media (prefers-color-scheme: dark)`
+    #[default]
+    Media,
+    /// Under an ancestor matching a selector, e.g. `.dark` or `[data-theme="dark"]`
+    Selector(String),
+}
+
+impl DarkMode {
+    /// Parse the `darkMode` theme input: `media`, `class` (default selector
+    /// `.dark`) or `attr` (default selector `[data-theme="dark"]`)
+    pub fn parse(strategy: &str, selector: Option<&str>) -> Option<Self> {
+        match strategy {
+            "media" => Some(DarkMode::Media),
+            "class" => Some(DarkMode::Selector(selector.unwrap_or(".dark").to_string())),
+            "attr" => Some(DarkMode::Selector(selector.unwrap_or("[data-theme=\"dark\"]").to_string())),
+            _ => None,
+        }
+    }
+}
+
+/// The breakpoints used when the theme input doesn't provide any, matching
+/// the runtime's `breakpoints` in `theme/sizes`
+pub fn default_breakpoints() -> HashMap<String, String> {
+    [("sm", "640px"), ("md", "768px"), ("lg", "1024px"), ("xl", "1280px"), ("2xl", "1536px")]
+        .into_iter()
+        .map(|(name, width)| (name.to_string(), width.to_string()))
+        .collect()
+}
+
 impl ThemeData {
     /// Resolve a color: color_name[shade] -> hex
     pub fn resolve_color(&self, color_name: &str, shade: &str) -> Option<&str> {
@@ -43,8 +86,12 @@ impl ThemeData {
         self.named_colors.get(name).map(|s| s.as_str())
     }
 
-    /// Resolve a spacing value from the scale
+    /// Resolve a spacing value from the scale. Negative values resolve the
+    /// positive key and negate its token, so `-4` is `-1rem` on the default scale.
     pub fn resolve_spacing_num(&self, value: f64) -> Option<String> {
+        if value < 0.0 {
+            return self.resolve_spacing_num(-value).map(|v| negate_length(&v));
+        }
         // Try exact match first, using string representation matching TS behavior
         // The TS scale uses keys like "0", "0.5", "1", "1.5", etc.
         let key = format_spacing_key(value);
@@ -52,7 +99,7 @@ impl ThemeData {
             return Some(v.clone());
         }
         // Fallback: compute from value
-        Some(format!("{}rem", value * 0.25))
+        Some(format!("{}rem", format_spacing_key(value * 0.25)))
     }
 
     /// Resolve a text size token to (font_size, line_height)
@@ -72,6 +119,16 @@ impl ThemeData {
         self.font_families.get(name).map(|s| s.as_str())
     }
 
+    /// Resolve a letter-spacing token
+    pub fn resolve_tracking(&self, name: &str) -> Option<&str> {
+        self.tracking.get(name).map(|s| s.as_str())
+    }
+
+    /// Resolve a line-height token
+    pub fn resolve_leading(&self, name: &str) -> Option<&str> {
+        self.leading.get(name).map(|s| s.as_str())
+    }
+
     /// Resolve a border radius token
     pub fn resolve_radius(&self, name: &str) -> Option<&str> {
         self.radii.get(name).map(|s| s.as_str())
@@ -87,6 +144,16 @@ impl ThemeData {
         self.sizes.get(name).map(|s| s.as_str())
     }
 
+    /// Resolve a container breakpoint name to its width
+    pub fn resolve_container(&self, name: &str) -> Option<&str> {
+        self.containers.get(name).map(|s| s.as_str())
+    }
+
+    /// Resolve a responsive breakpoint name to its min width
+    pub fn resolve_breakpoint(&self, name: &str) -> Option<&str> {
+        self.breakpoints.get(name).map(|s| s.as_str())
+    }
+
     /// Resolve an animation name to the full shorthand value
     pub fn resolve_animation(&self, name: &str) -> Option<&str> {
         self.animations.get(name).map(|s| s.as_str())
@@ -96,6 +163,63 @@ impl ThemeData {
     pub fn resolve_keyframes(&self, name: &str) -> Option<&str> {
         self.keyframes.get(name).map(|s| s.as_str())
     }
+
+    /// Stable hash of every token, used to invalidate values cached under a
+    /// different theme
+    pub fn fingerprint(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        let mut colors: Vec<_> = self.colors.iter().collect();
+        colors.sort_by_key(|(name, _)| *name);
+        for (name, shades) in colors {
+            name.hash(&mut hasher);
+            hash_sorted(shades, &mut hasher);
+        }
+        hash_sorted(&self.named_colors, &mut hasher);
+        hash_sorted(&self.spacing, &mut hasher);
+        hash_sorted(&self.text_sizes, &mut hasher);
+        hash_sorted(&self.font_weights, &mut hasher);
+        hash_sorted(&self.font_families, &mut hasher);
+        hash_sorted(&self.tracking, &mut hasher);
+        hash_sorted(&self.leading, &mut hasher);
+        hash_sorted(&self.radii, &mut hasher);
+        hash_sorted(&self.shadows, &mut hasher);
+        hash_sorted(&self.sizes, &mut hasher);
+        hash_sorted(&self.animations, &mut hasher);
+        hash_sorted(&self.keyframes, &mut hasher);
+        hash_sorted(&self.containers, &mut hasher);
+        hash_sorted(&self.breakpoints, &mut hasher);
+        self.dark_mode.hash(&mut hasher);
+        self.default_radius.hash(&mut hasher);
+        self.default_shadow.hash(&mut hasher);
+        hasher.finish()
+    }
+}
+
+fn hash_sorted<V: Hash>(map: &HashMap<String, V>, hasher: &mut DefaultHasher) {
+    let mut entries: Vec<_> = map.iter().collect();
+    entries.sort_by_key(|(key, _)| *key);
+    entries.hash(hasher);
+}
+
+/// Negate a CSS length: `1rem` → `-1rem`, `-2px` → `2px`, zero stays as is,
+/// and anything else (`var(--x)`, `calc(...)`) becomes `calc(... * -1)`
+fn negate_length(value: &str) -> String {
+    let value = value.trim();
+    let (negative, magnitude) = match value.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, value),
+    };
+    let number = magnitude.trim_end_matches(|c: char| c.is_ascii_alphabetic() || c == '%');
+    match number.parse::<f64>() {
+        Ok(n) if !number.starts_with(['+', '-']) => {
+            if n == 0.0 || negative {
+                magnitude.to_string()
+            } else {
+                format!("-{}", magnitude)
+            }
+        }
+        _ => format!("calc({} * -1)", value),
+    }
 }
 
 fn format_spacing_key(value: f64) -> String {
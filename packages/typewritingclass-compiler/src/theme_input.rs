@@ -0,0 +1,328 @@
+//! Parsing and validation of the theme JSON passed from the Vite plugin.
+//! Every problem is reported with the input field and the JSON path of the
+//! offending value; the entries that do parse are kept.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use serde_json::{Map, Value as Json};
+
+use crate::theme::{self, DarkMode, ThemeData};
+use crate::ThemeInput;
+
+/// A problem with one value of the theme input
+#[derive(Debug, Clone, PartialEq)]
+pub struct ThemeIssue {
+    /// The `ThemeInput` field, as named in JavaScript (e.g. `textSizes`)
+    pub field: String,
+    /// JSON path of the value within the field (e.g. `$.blue["500"]`)
+    pub path: String,
+    pub reason: String,
+}
+
+impl fmt::Display for ThemeIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}: {}", self.field, self.path, self.reason)
+    }
+}
+
+/// Parse the theme input, collecting a `ThemeIssue` for every value that is
+/// malformed. Invalid values are left out of the theme.
+pub fn parse_theme(input: &ThemeInput) -> (ThemeData, Vec<ThemeIssue>) {
+    let mut parser = Parser::default();
+
+    let colors = parser.color_scales("colors", &input.colors);
+    let named_colors = parser.colors("namedColors", &input.named_colors);
+    let spacing = parser.spacing("spacing", &input.spacing);
+    let text_sizes = parser.text_sizes("textSizes", &input.text_sizes);
+    let font_weights = parser.strings("fontWeights", &input.font_weights);
+    let font_families = parser.strings("fontFamilies", &input.font_families);
+    let tracking = parser.optional_strings("tracking", input.tracking.as_deref());
+    let leading = parser.optional_strings("leading", input.leading.as_deref());
+    let radii = parser.strings("radii", &input.radii);
+    let shadows = parser.strings("shadows", &input.shadows);
+    let sizes = parser.strings("sizes", &input.sizes);
+    let animations = parser.strings("animations", &input.animations);
+    let keyframes = parser.strings("keyframes", &input.keyframes);
+    let containers = parser.optional_strings("containers", input.containers.as_deref());
+    let mut breakpoints = parser.optional_strings("breakpoints", input.breakpoints.as_deref());
+    if breakpoints.is_empty() {
+        breakpoints = theme::default_breakpoints();
+    }
+    let dark_mode = match input.dark_mode.as_deref() {
+        None => DarkMode::default(),
+        Some(strategy) => DarkMode::parse(strategy, input.dark_selector.as_deref()).unwrap_or_else(|| {
+            parser.issue("darkMode", "$", format!("expected \"media\", \"class\" or \"attr\", found \"{}\"", strategy));
+            DarkMode::default()
+        }),
+    };
+
+    let theme = ThemeData {
+        colors,
+        named_colors,
+        spacing,
+        text_sizes,
+        font_weights,
+        font_families,
+        tracking,
+        leading,
+        radii,
+        shadows,
+        sizes,
+        animations,
+        keyframes,
+        containers,
+        breakpoints,
+        dark_mode,
+        default_radius: input.default_radius.clone(),
+        default_shadow: input.default_shadow.clone(),
+    };
+    (theme, parser.issues)
+}
+
+#[derive(Default)]
+struct Parser {
+    issues: Vec<ThemeIssue>,
+}
+
+impl Parser {
+    fn issue(&mut self, field: &str, path: &str, reason: impl Into<String>) {
+        self.issues.push(ThemeIssue {
+            field: field.to_string(),
+            path: path.to_string(),
+            reason: reason.into(),
+        });
+    }
+
+    /// The top-level object of a field
+    fn object(&mut self, field: &str, json: &str) -> Map<String, Json> {
+        match serde_json::from_str::<Json>(json) {
+            Ok(Json::Object(map)) => map,
+            Ok(other) => {
+                self.issue(field, "$", format!("expected an object, found {}", kind(&other)));
+                Map::new()
+            }
+            Err(err) => {
+                self.issue(field, "$", format!("invalid JSON: {}", err));
+                Map::new()
+            }
+        }
+    }
+
+    /// `{ name: string }`
+    fn strings(&mut self, field: &str, json: &str) -> HashMap<String, String> {
+        let object = self.object(field, json);
+        self.string_entries(field, "$", object)
+    }
+
+    fn optional_strings(&mut self, field: &str, json: Option<&str>) -> HashMap<String, String> {
+        json.map(|json| self.strings(field, json)).unwrap_or_default()
+    }
+
+    fn string_entries(&mut self, field: &str, path: &str, object: Map<String, Json>) -> HashMap<String, String> {
+        let mut out = HashMap::new();
+        for (key, value) in object {
+            match value {
+                Json::String(s) => {
+                    out.insert(key, s);
+                }
+                other => self.issue(field, &child_path(path, &key), format!("expected a string, found {}", kind(&other))),
+            }
+        }
+        out
+    }
+
+    /// `{ name: color }`, where hex colors must parse
+    fn colors(&mut self, field: &str, json: &str) -> HashMap<String, String> {
+        let mut colors = self.strings(field, json);
+        self.drop_bad_hex(field, "$", &mut colors);
+        colors
+    }
+
+    /// `{ scale: { shade: color } }`
+    fn color_scales(&mut self, field: &str, json: &str) -> HashMap<String, HashMap<String, String>> {
+        let mut out = HashMap::new();
+        for (name, value) in self.object(field, json) {
+            let path = child_path("$", &name);
+            let Json::Object(shades) = value else {
+                self.issue(field, &path, format!("expected an object of shades, found {}", kind(&value)));
+                continue;
+            };
+            let mut shades = self.string_entries(field, &path, shades);
+            self.drop_bad_hex(field, &path, &mut shades);
+            out.insert(name, shades);
+        }
+        out
+    }
+
+    fn drop_bad_hex(&mut self, field: &str, path: &str, colors: &mut HashMap<String, String>) {
+        let mut bad: Vec<String> = colors
+            .iter()
+            .filter(|(_, color)| color.starts_with('#') && !is_hex_color(color))
+            .map(|(key, _)| key.clone())
+            .collect();
+        bad.sort();
+        for key in bad {
+            let color = colors.remove(&key).unwrap_or_default();
+            self.issue(field, &child_path(path, &key), format!("invalid hex color \"{}\"", color));
+        }
+    }
+
+    /// `{ number: length }`
+    fn spacing(&mut self, field: &str, json: &str) -> HashMap<String, String> {
+        let mut spacing = self.strings(field, json);
+        let mut bad: Vec<String> = spacing
+            .keys()
+            .filter(|key| !key.parse::<f64>().is_ok_and(f64::is_finite))
+            .cloned()
+            .collect();
+        bad.sort();
+        for key in bad {
+            spacing.remove(&key);
+            self.issue(field, &child_path("$", &key), "spacing keys must be numbers");
+        }
+        spacing
+    }
+
+    /// `{ name: { fontSize, lineHeight } }`
+    fn text_sizes(&mut self, field: &str, json: &str) -> HashMap<String, (String, String)> {
+        let mut out = HashMap::new();
+        for (name, value) in self.object(field, json) {
+            let path = child_path("$", &name);
+            let Json::Object(entry) = value else {
+                self.issue(field, &path, format!("expected {{ fontSize, lineHeight }}, found {}", kind(&value)));
+                continue;
+            };
+            let mut part = |key: &str| match entry.get(key) {
+                Some(Json::String(s)) => Some(s.clone()),
+                Some(other) => {
+                    self.issue(field, &child_path(&path, key), format!("expected a string, found {}", kind(other)));
+                    None
+                }
+                None => {
+                    self.issue(field, &path, format!("missing \"{}\"", key));
+                    None
+                }
+            };
+            let font_size = part("fontSize");
+            let line_height = part("lineHeight");
+            if let (Some(font_size), Some(line_height)) = (font_size, line_height) {
+                out.insert(name, (font_size, line_height));
+            }
+        }
+        out
+    }
+}
+
+/// `#rgb`, `#rgba`, `#rrggbb` or `#rrggbbaa`
+fn is_hex_color(color: &str) -> bool {
+    color.strip_prefix('#').is_some_and(|hex| {
+        matches!(hex.len(), 3 | 4 | 6 | 8) && hex.chars().all(|c| c.is_ascii_hexdigit())
+    })
+}
+
+/// `$.blue` for identifier-like keys, `$.blue["500"]` otherwise
+fn child_path(path: &str, key: &str) -> String {
+    let identifier = key.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_' || c == '$')
+        && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$');
+    if identifier {
+        format!("{}.{}", path, key)
+    } else {
+        format!("{}[{}]", path, Json::String(key.to_string()))
+    }
+}
+
+fn kind(value: &Json) -> &'static str {
+    match value {
+        Json::Null => "null",
+        Json::Bool(_) => "a boolean",
+        Json::Number(_) => "a number",
+        Json::String(_) => "a string",
+        Json::Array(_) => "an array",
+        Json::Object(_) => "an object",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn input() -> ThemeInput {
+        ThemeInput {
+            colors: r##"{ "blue": { "500": "#3b82f6" } }"##.to_string(),
+            named_colors: r##"{ "white": "#fff", "current": "currentColor" }"##.to_string(),
+            spacing: r#"{ "0.5": "0.125rem", "4": "1rem" }"#.to_string(),
+            text_sizes: r#"{ "lg": { "fontSize": "1.125rem", "lineHeight": "1.75rem" } }"#.to_string(),
+            font_weights: r#"{ "bold": "700" }"#.to_string(),
+            font_families: "{}".to_string(),
+            tracking: None,
+            leading: None,
+            radii: r#"{ "lg": "0.5rem" }"#.to_string(),
+            shadows: "{}".to_string(),
+            sizes: "{}".to_string(),
+            animations: "{}".to_string(),
+            keyframes: "{}".to_string(),
+            containers: None,
+            breakpoints: None,
+            dark_mode: None,
+            dark_selector: None,
+            default_radius: "0.25rem".to_string(),
+            default_shadow: "none".to_string(),
+        }
+    }
+
+    fn issues(input: &ThemeInput) -> Vec<String> {
+        parse_theme(input).1.iter().map(|issue| issue.to_string()).collect()
+    }
+
+    #[test]
+    fn test_valid_theme() {
+        let (theme, issues) = parse_theme(&input());
+        assert!(issues.is_empty(), "{:?}", issues);
+        assert_eq!(theme.resolve_color("blue", "500"), Some("#3b82f6"));
+        assert_eq!(theme.resolve_named_color("current"), Some("currentColor"));
+        assert_eq!(theme.resolve_text_size("lg"), Some(("1.125rem", "1.75rem")));
+        assert_eq!(theme.resolve_breakpoint("md"), Some("768px"));
+    }
+
+    #[test]
+    fn test_malformed_json() {
+        let mut input = input();
+        input.colors = "{ \"blue\": ".to_string();
+        input.radii = "[]".to_string();
+        let issues = issues(&input);
+        assert_eq!(issues.len(), 2);
+        assert!(issues[0].starts_with("colors $: invalid JSON: EOF while parsing"), "{}", issues[0]);
+        assert_eq!(issues[1], "radii $: expected an object, found an array");
+    }
+
+    #[test]
+    fn test_invalid_values() {
+        let mut input = input();
+        input.colors = r##"{ "blue": { "500": "#3b82f6", "600": "#zz0000" }, "red": "#f00" }"##.to_string();
+        input.named_colors = r##"{ "white": "#ffff0" }"##.to_string();
+        input.spacing = r#"{ "4": "1rem", "px": "1px" }"#.to_string();
+        input.text_sizes = r#"{ "lg": { "fontSize": "1.125rem" }, "xl": { "fontSize": "1.25rem", "lineHeight": 2 } }"#.to_string();
+        input.dark_mode = Some("toggle".to_string());
+        let (theme, _) = parse_theme(&input);
+        let mut issues = issues(&input);
+        issues.sort();
+        assert_eq!(
+            issues,
+            vec![
+                "colors $.blue[\"600\"]: invalid hex color \"#zz0000\"",
+                "colors $.red: expected an object of shades, found a string",
+                "darkMode $: expected \"media\", \"class\" or \"attr\", found \"toggle\"",
+                "namedColors $.white: invalid hex color \"#ffff0\"",
+                "spacing $.px: spacing keys must be numbers",
+                "textSizes $.lg: missing \"lineHeight\"",
+                "textSizes $.xl.lineHeight: expected a string, found a number",
+            ]
+        );
+        // The valid entries are kept
+        assert_eq!(theme.resolve_color("blue", "500"), Some("#3b82f6"));
+        assert_eq!(theme.resolve_color("blue", "600"), None);
+        assert_eq!(theme.spacing.len(), 1);
+        assert!(theme.text_sizes.is_empty());
+    }
+}
@@ -41,35 +41,19 @@ pub fn supports_opacity(utility: &str) -> bool {
 /// Resolve a camelCase property token to a utility argument string.
 /// E.g., resolve_token("bg", "blue500", theme) -> Some("blue-500")
 ///       resolve_token("items", "center", theme) -> Some("center")
-pub fn resolve_token(utility: &str, prop: &str, _theme: &ThemeData) -> Option<String> {
+pub fn resolve_token(utility: &str, prop: &str, theme: &ThemeData) -> Option<String> {
+    // Radius, shadow and typography tokens are the keys of their theme scale
+    if let Some(keys) = token_scale(utility, theme) {
+        return find_token_key(keys.into_iter(), prop).map(|key| key.to_string());
+    }
+
     match utility {
         // Color utilities -> color tokens
-        "bg" | "textColor" | "borderColor" | "shadowColor" | "ringColor"
-        | "outlineColor" | "accentColor" | "caretColor" | "divideColor"
-        | "textDecorationColor" | "gradientFrom" | "gradientVia" | "gradientTo" => {
-            resolve_color_token(prop)
-        }
+        _ if is_color_utility(utility) => resolve_color_token(prop, theme),
 
         // Gradient direction
         "bgGradient" => resolve_gradient_direction(prop),
 
-        // Radius utilities
-        "rounded" | "roundedT" | "roundedB" | "roundedL" | "roundedR"
-        | "roundedTL" | "roundedTR" | "roundedBR" | "roundedBL"
-        | "roundedSS" | "roundedSE" | "roundedEE" | "roundedES" => {
-            resolve_radius_token(prop)
-        }
-
-        // Shadow
-        "shadow" => resolve_shadow_token(prop),
-
-        // Typography
-        "text" => resolve_text_size_token(prop),
-        "font" => resolve_font_weight_token(prop),
-        "tracking" => resolve_tracking_token(prop),
-        "leading" => resolve_leading_token(prop),
-        "fontFamily" => resolve_font_family_token(prop),
-
         // Layout enums
         "items" => resolve_align_items_token(prop),
         "justify" => resolve_justify_token(prop),
@@ -87,6 +71,53 @@ pub fn resolve_token(utility: &str, prop: &str, _theme: &ThemeData) -> Option<St
     }
 }
 
+/// The token names a theme-backed utility accepts, sorted, for diagnostics.
+/// Color scales are listed as `blue<shade>`. None for utilities whose tokens
+/// are fixed CSS keywords.
+pub fn token_names(utility: &str, theme: &ThemeData) -> Option<Vec<String>> {
+    let mut names: Vec<String> = if is_color_utility(utility) {
+        let scales = theme.colors.keys().map(|name| format!("{}<shade>", token_name(name)));
+        ["transparent", "current"]
+            .into_iter()
+            .map(str::to_string)
+            .chain(theme.named_colors.keys().map(|name| token_name(name)))
+            .chain(scales)
+            .collect()
+    } else {
+        token_scale(utility, theme)?.into_iter().map(|key| token_name(key)).collect()
+    };
+    names.sort();
+    names.dedup();
+    Some(names)
+}
+
+fn is_color_utility(utility: &str) -> bool {
+    matches!(
+        utility,
+        "bg" | "textColor" | "borderColor" | "shadowColor" | "ringColor"
+        | "outlineColor" | "accentColor" | "caretColor" | "divideColor"
+        | "textDecorationColor" | "gradientFrom" | "gradientVia" | "gradientTo"
+    )
+}
+
+/// The keys of the theme scale a radius, shadow or typography utility takes
+/// its tokens from. `DEFAULT` is the value of the bare call, not a token.
+fn token_scale<'a>(utility: &str, theme: &'a ThemeData) -> Option<Vec<&'a String>> {
+    let keys: Vec<&String> = match utility {
+        "rounded" | "roundedT" | "roundedB" | "roundedL" | "roundedR"
+        | "roundedTL" | "roundedTR" | "roundedBR" | "roundedBL"
+        | "roundedSS" | "roundedSE" | "roundedEE" | "roundedES" => theme.radii.keys().collect(),
+        "shadow" => theme.shadows.keys().collect(),
+        "text" => theme.text_sizes.keys().collect(),
+        "font" => theme.font_weights.keys().collect(),
+        "tracking" => theme.tracking.keys().collect(),
+        "leading" => theme.leading.keys().collect(),
+        "fontFamily" => theme.font_families.keys().collect(),
+        _ => return None,
+    };
+    Some(keys.into_iter().filter(|key| key.as_str() != "DEFAULT").collect())
+}
+
 /// Resolve a color token with opacity applied.
 /// E.g., ("blue-500", 50, theme) -> "rgb(59 130 246 / 0.5)"
 pub fn resolve_color_with_opacity(color_str: &str, opacity: f64, theme: &ThemeData) -> Option<String> {
@@ -99,69 +130,82 @@ pub fn resolve_color_with_opacity(color_str: &str, opacity: f64, theme: &ThemeDa
 
 // ─── Color token parsing ─────────────────────────────────────────────────────
 
-fn resolve_color_token(prop: &str) -> Option<String> {
-    // Named colors
-    match prop {
-        "white" | "black" | "transparent" | "current" => return Some(prop.to_string()),
-        _ => {}
+/// Resolve a color token against the theme: a named color (`white`,
+/// `primary`), a scale with a shade (`blue500` -> `blue-500`,
+/// `brandAccent500` -> `brandAccent-500`), or a bare scale name (`red`).
+/// Theme keys may be camelCase or kebab-case (`brand-accent`).
+fn resolve_color_token(prop: &str, theme: &ThemeData) -> Option<String> {
+    // CSS keywords that are always valid
+    if matches!(prop, "transparent" | "current") {
+        return Some(prop.to_string());
+    }
+    if let Some(name) = find_token_key(theme.named_colors.keys(), prop) {
+        return Some(name.to_string());
     }
 
-    // Prefixed leading underscore for numeric start not applicable for colors
-    // Parse camelCase color tokens: "blue500" -> "blue-500", "slate950" -> "slate-950"
-    if let Some((color, shade)) = parse_color_token(prop) {
-        if shade.is_empty() {
-            Some(color.to_string())
-        } else {
-            Some(format!("{}-{}", color, shade))
+    match split_shade(prop) {
+        Some((color, shade)) => {
+            let name = find_token_key(theme.colors.keys(), color)?;
+            theme.resolve_color(name, shade)?;
+            Some(format!("{}-{}", name, shade))
         }
-    } else {
-        None
+        // No shade — might be a single scale name used as a CSS color
+        None => find_token_key(theme.colors.keys(), prop).map(|name| name.to_string()),
     }
 }
 
-/// Parse a camelCase color token into (color_name, shade).
-/// "blue500" -> ("blue", "500"), "slate950" -> ("slate", "950")
-/// "red" -> ("red", "")
-fn parse_color_token(prop: &str) -> Option<(&str, &str)> {
+/// Split a camelCase color token at its last letter-to-digit boundary.
+/// "blue500" -> ("blue", "500"), "brandAccent500" -> ("brandAccent", "500")
+fn split_shade(prop: &str) -> Option<(&str, &str)> {
     let bytes = prop.as_bytes();
-    // Find the last transition point where a letter is followed by a digit
-    let mut split = None;
-    for i in 0..bytes.len() - 1 {
-        if bytes[i].is_ascii_lowercase() && bytes[i + 1].is_ascii_digit() {
-            split = Some(i + 1);
-        }
-    }
-    match split {
-        Some(pos) => {
-            let color = &prop[..pos];
-            let shade = &prop[pos..];
-            // Validate that the color name is a known color scale
-            if is_known_color(color) {
-                Some((color, shade))
-            } else {
-                None
-            }
+    let pos = (1..bytes.len())
+        .rev()
+        .find(|&i| bytes[i - 1].is_ascii_alphabetic() && bytes[i].is_ascii_digit())?;
+    Some((&prop[..pos], &prop[pos..]))
+}
+
+/// The theme key a camelCase token refers to, matching keys written either
+/// as the token itself, in kebab-case (`brandAccent` matches `brand-accent`)
+/// or starting with a digit (`_2xl` matches `2xl`)
+fn find_token_key<'a>(keys: impl Iterator<Item = &'a String>, token: &str) -> Option<&'a str> {
+    let mut found = None;
+    for key in keys {
+        if key == token {
+            return Some(key);
         }
-        None => {
-            // No digit boundary found — might be a single-word color name
-            if is_known_color(prop) {
-                Some((prop, ""))
-            } else {
-                None
-            }
+        if found.is_none() && token_name(key) == token {
+            found = Some(key.as_str());
         }
     }
+    found
 }
 
-fn is_known_color(name: &str) -> bool {
-    matches!(
-        name,
-        "slate" | "gray" | "zinc" | "neutral" | "stone"
-        | "red" | "orange" | "amber" | "yellow" | "lime"
-        | "green" | "emerald" | "teal" | "cyan" | "sky"
-        | "blue" | "indigo" | "violet" | "purple" | "fuchsia"
-        | "pink" | "rose"
-    )
+/// The property name that refers to a theme key: `brand-accent` ->
+/// `brandAccent`, `2xl` -> `_2xl` (identifiers can't start with a digit)
+fn token_name(key: &str) -> String {
+    let name = camel_case(key);
+    if name.starts_with(|c: char| c.is_ascii_digit()) {
+        format!("_{}", name)
+    } else {
+        name
+    }
+}
+
+/// `brand-accent` -> `brandAccent`
+fn camel_case(name: &str) -> String {
+    let mut out = String::with_capacity(name.len());
+    let mut upper = false;
+    for c in name.chars() {
+        if c == '-' || c == '_' {
+            upper = true;
+        } else if upper {
+            out.push(c.to_ascii_uppercase());
+            upper = false;
+        } else {
+            out.push(c);
+        }
+    }
+    out
 }
 
 fn resolve_color_to_hex(color_str: &str, theme: &ThemeData) -> Option<String> {
@@ -226,90 +270,6 @@ fn resolve_gradient_direction(prop: &str) -> Option<String> {
     }
 }
 
-// ─── Radius tokens ──────────────────────────────────────────────────────────
-
-fn resolve_radius_token(prop: &str) -> Option<String> {
-    match prop {
-        "sm" | "md" | "lg" | "xl" | "full" | "none" => Some(prop.to_string()),
-        "_2xl" => Some("2xl".to_string()),
-        "_3xl" => Some("3xl".to_string()),
-        _ => None,
-    }
-}
-
-// ─── Shadow tokens ──────────────────────────────────────────────────────────
-
-fn resolve_shadow_token(prop: &str) -> Option<String> {
-    match prop {
-        "sm" | "md" | "lg" | "xl" | "inner" | "none" => Some(prop.to_string()),
-        "_2xl" => Some("2xl".to_string()),
-        _ => None,
-    }
-}
-
-// ─── Text size tokens ───────────────────────────────────────────────────────
-
-fn resolve_text_size_token(prop: &str) -> Option<String> {
-    match prop {
-        "xs" | "sm" | "base" | "lg" | "xl" => Some(prop.to_string()),
-        "_2xl" => Some("2xl".to_string()),
-        "_3xl" => Some("3xl".to_string()),
-        "_4xl" => Some("4xl".to_string()),
-        "_5xl" => Some("5xl".to_string()),
-        "_6xl" => Some("6xl".to_string()),
-        "_7xl" => Some("7xl".to_string()),
-        "_8xl" => Some("8xl".to_string()),
-        "_9xl" => Some("9xl".to_string()),
-        _ => None,
-    }
-}
-
-// ─── Font weight tokens ─────────────────────────────────────────────────────
-
-fn resolve_font_weight_token(prop: &str) -> Option<String> {
-    match prop {
-        "thin" | "extralight" | "light" | "normal" | "medium"
-        | "semibold" | "bold" | "extrabold" | "black" => Some(prop.to_string()),
-        _ => None,
-    }
-}
-
-// ─── Tracking (letter-spacing) tokens ───────────────────────────────────────
-
-fn resolve_tracking_token(prop: &str) -> Option<String> {
-    match prop {
-        "tighter" | "tight" | "normal" | "wide" | "wider" | "widest" => Some(prop.to_string()),
-        _ => None,
-    }
-}
-
-// ─── Leading (line-height) tokens ───────────────────────────────────────────
-
-fn resolve_leading_token(prop: &str) -> Option<String> {
-    match prop {
-        "none" | "tight" | "snug" | "normal" | "relaxed" | "loose" => Some(prop.to_string()),
-        // Numeric leading: _3 -> "3", _4 -> "4", etc.
-        s if s.starts_with('_') => {
-            let num = &s[1..];
-            if num.chars().all(|c| c.is_ascii_digit()) {
-                Some(num.to_string())
-            } else {
-                None
-            }
-        }
-        _ => None,
-    }
-}
-
-// ─── Font family tokens ─────────────────────────────────────────────────────
-
-fn resolve_font_family_token(prop: &str) -> Option<String> {
-    match prop {
-        "sans" | "serif" | "mono" => Some(prop.to_string()),
-        _ => None,
-    }
-}
-
 // ─── Layout enum tokens ─────────────────────────────────────────────────────
 
 fn resolve_align_items_token(prop: &str) -> Option<String> {
@@ -433,9 +393,24 @@ mod tests {
         named_colors.insert("white".to_string(), "#ffffff".to_string());
         named_colors.insert("black".to_string(), "#000000".to_string());
 
+        let scale = |pairs: &[(&str, &str)]| -> HashMap<String, String> {
+            pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+        };
+        let text_sizes = [("lg", "1.125rem"), ("2xl", "1.5rem")]
+            .into_iter()
+            .map(|(name, size)| (name.to_string(), (size.to_string(), "2rem".to_string())))
+            .collect();
+
         ThemeData {
             colors,
             named_colors,
+            text_sizes,
+            font_weights: scale(&[("semibold", "600"), ("bold", "700")]),
+            font_families: scale(&[("sans", "ui-sans-serif"), ("mono", "ui-monospace")]),
+            tracking: scale(&[("tight", "-0.025em"), ("wide", "0.025em")]),
+            leading: scale(&[("snug", "1.375"), ("6", "1.5rem")]),
+            radii: scale(&[("DEFAULT", "0.25rem"), ("lg", "0.5rem"), ("2xl", "1rem"), ("full", "9999px")]),
+            shadows: scale(&[("DEFAULT", "0 1px 3px"), ("md", "0 4px 6px"), ("inner", "inset 0 2px 4px")]),
             ..Default::default()
         }
     }
@@ -455,6 +430,26 @@ mod tests {
         assert_eq!(resolve_token("bg", "transparent", &theme), Some("transparent".to_string()));
     }
 
+    #[test]
+    fn test_color_tokens_from_theme() {
+        let mut theme = test_theme();
+        let shades = |pairs: &[(&str, &str)]| -> HashMap<String, String> {
+            pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+        };
+        theme.colors.insert("brand".to_string(), shades(&[("500", "#6d28d9")]));
+        theme.colors.insert("brand-accent".to_string(), shades(&[("500", "#f59e0b")]));
+        theme.named_colors.insert("primary".to_string(), "#6d28d9".to_string());
+        theme.named_colors.insert("surface-muted".to_string(), "#f4f4f5".to_string());
+
+        assert_eq!(resolve_token("bg", "brand500", &theme), Some("brand-500".to_string()));
+        assert_eq!(resolve_token("bg", "brandAccent500", &theme), Some("brand-accent-500".to_string()));
+        assert_eq!(resolve_token("textColor", "primary", &theme), Some("primary".to_string()));
+        assert_eq!(resolve_token("bg", "surfaceMuted", &theme), Some("surface-muted".to_string()));
+        // Unknown scales and shades the scale doesn't define are not tokens
+        assert_eq!(resolve_token("bg", "teal500", &theme), None);
+        assert_eq!(resolve_token("bg", "brand900", &theme), None);
+    }
+
     #[test]
     fn test_radius_tokens() {
         let theme = test_theme();
@@ -484,6 +479,35 @@ mod tests {
         assert_eq!(resolve_token("font", "semibold", &theme), Some("semibold".to_string()));
     }
 
+    #[test]
+    fn test_scale_tokens_from_theme() {
+        let mut theme = test_theme();
+        theme.radii.insert("pill".to_string(), "999px".to_string());
+        theme.tracking.insert("extra-wide".to_string(), "0.2em".to_string());
+        assert_eq!(resolve_token("roundedT", "pill", &theme), Some("pill".to_string()));
+        assert_eq!(resolve_token("tracking", "extraWide", &theme), Some("extra-wide".to_string()));
+        assert_eq!(resolve_token("tracking", "tight", &theme), Some("tight".to_string()));
+        assert_eq!(resolve_token("leading", "_6", &theme), Some("6".to_string()));
+        assert_eq!(resolve_token("fontFamily", "mono", &theme), Some("mono".to_string()));
+        // Only what the theme defines: the defaults are gone once replaced
+        assert_eq!(resolve_token("rounded", "xl", &theme), None);
+        assert_eq!(resolve_token("font", "black", &theme), None);
+        assert_eq!(resolve_token("leading", "_7", &theme), None);
+        assert_eq!(resolve_token("shadow", "DEFAULT", &theme), None);
+    }
+
+    #[test]
+    fn test_token_names() {
+        let theme = test_theme();
+        assert_eq!(token_names("rounded", &theme).unwrap(), vec!["_2xl", "full", "lg"]);
+        assert_eq!(token_names("leading", &theme).unwrap(), vec!["_6", "snug"]);
+        assert_eq!(
+            token_names("bg", &theme).unwrap(),
+            vec!["black", "blue<shade>", "current", "slate<shade>", "transparent", "white"]
+        );
+        assert_eq!(token_names("cursor", &theme), None);
+    }
+
     #[test]
     fn test_layout_tokens() {
         let theme = test_theme();
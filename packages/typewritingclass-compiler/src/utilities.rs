@@ -24,10 +24,6 @@ impl Value {
             _ => None,
         }
     }
-
-    pub fn is_dynamic(&self) -> bool {
-        matches!(self, Value::Dynamic(_, _))
-    }
 }
 
 /// Resolve a spacing argument using the theme scale
@@ -301,8 +297,22 @@ pub fn evaluate(name: &str, args: &[Value], theme: &ThemeData) -> Option<StyleRu
                     .with_dynamic_binding(id, expr))
             }
         },
-        "tracking" => single_prop_rule("letter-spacing", args.first()?, theme),
-        "leading" => single_prop_rule("line-height", args.first()?, theme),
+        "tracking" => match args.first()? {
+            // Theme letter-spacing first (e.g., "tight" → "-0.025em")
+            Value::Str(s) => {
+                let resolved = theme.resolve_tracking(s).unwrap_or(s);
+                Some(StyleRule::new(vec![("letter-spacing", resolved)]))
+            }
+            other => single_prop_rule("letter-spacing", other, theme),
+        },
+        "leading" => match args.first()? {
+            // Theme line-height first (e.g., "snug" → "1.375")
+            Value::Str(s) => {
+                let resolved = theme.resolve_leading(s).unwrap_or(s);
+                Some(StyleRule::new(vec![("line-height", resolved)]))
+            }
+            other => single_prop_rule("line-height", other, theme),
+        },
         "textAlign" => {
             let v = args.first()?.as_str()?;
             Some(StyleRule::new(vec![("text-align", v)]))
@@ -633,6 +643,9 @@ pub fn evaluate(name: &str, args: &[Value], theme: &ThemeData) -> Option<StyleRu
         "flexWrapReverse" if args.is_empty() => Some(StyleRule::new(vec![("flex-wrap", "wrap-reverse")])),
         "flexNowrap" if args.is_empty() => Some(StyleRule::new(vec![("flex-wrap", "nowrap")])),
         "container" if args.is_empty() => Some(StyleRule::new(vec![("width", "100%")])),
+        "containerType" if args.is_empty() => Some(StyleRule::new(vec![("container-type", "inline-size")])),
+        "containerType" => single_prop_rule("container-type", args.first()?, theme),
+        "containerName" => single_prop_rule("container-name", args.first()?, theme),
 
         // --- Layout (with args) ---
         "aspectRatio" => {
@@ -0,0 +1,23 @@
+//! Pre-order traversal of every expression in a module, built on oxc's
+//! generated visitor so new syntax is covered as soon as oxc supports it.
+
+use oxc_ast::ast::*;
+use oxc_ast_visit::{walk, Visit};
+
+struct ExpressionVisitor<F>(F);
+
+impl<'a, F: FnMut(&'a Expression<'a>)> Visit<'a> for ExpressionVisitor<F> {
+    fn visit_expression(&mut self, expr: &Expression<'a>) {
+        // Every node lives in the program's arena, so it can be handed out
+        // for the program's lifetime
+        let expr = self.alloc(expr);
+        (self.0)(expr);
+        walk::walk_expression(self, expr);
+    }
+}
+
+/// Call `visitor` on every expression in the program, outer expressions
+/// before the expressions nested inside them.
+pub fn visit_expressions<'a>(program: &'a Program<'a>, visitor: &mut dyn FnMut(&'a Expression<'a>)) {
+    ExpressionVisitor(visitor).visit_program(program);
+}
@@ -89,6 +89,16 @@ describe('loadTheme', () => {
     expect(typeof fontFamilies).toBe('object')
   })
 
+  it('tracking and leading contain the typography scales', async () => {
+    const theme = await loadTheme()
+    const tracking = JSON.parse(theme.tracking!)
+    const leading = JSON.parse(theme.leading!)
+    expect(tracking.tight).toBe('-0.025em')
+    expect(tracking).toHaveProperty('widest')
+    expect(leading.snug).toBe('1.375')
+    expect(leading['6']).toBe('1.5rem')
+  })
+
   it('radii contains valid JSON with standard values', async () => {
     const theme = await loadTheme()
     const radii = JSON.parse(theme.radii)
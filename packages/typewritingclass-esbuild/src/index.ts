@@ -1,5 +1,5 @@
 import type { Plugin, OnLoadArgs, OnLoadResult, OnResolveArgs, OnResolveResult } from 'esbuild'
-import type { ThemeInput, TransformOutput } from 'typewritingclass-compiler'
+import type { Compiler, CompilerOptions, ThemeInput } from 'typewritingclass-compiler'
 import { createRequire } from 'module'
 import { resolve, dirname } from 'path'
 import { fileURLToPath } from 'url'
@@ -10,14 +10,7 @@ import { loadTheme } from 'typewritingclass-compiler/loadTheme'
 const require = createRequire(import.meta.url)
 const compilerDir = dirname(require.resolve('typewritingclass-compiler/package.json'))
 const native: {
-  transform(
-    code: string,
-    filename: string,
-    layerOffset: number,
-    themeInput: ThemeInput,
-    strict?: boolean | null,
-  ): TransformOutput
-  generateCss(rulesJson: string): string
+  Compiler: new (themeInput: ThemeInput, options?: CompilerOptions | null) => Compiler
 } = require(
   resolve(compilerDir, 'index.node'),
 )
@@ -34,26 +27,25 @@ const VIRTUAL_CSS_NAMESPACE = 'twc-virtual-css'
 export default function twcEsbuildPlugin(options?: TwcEsbuildPluginOptions): Plugin {
   const strict = options?.strict ?? true
 
-  let themeInput: ThemeInput
-  let layer = 0
-  // file path -> extracted CSS rules (css text strings ordered by layer)
-  const fileRules = new Map<string, string[]>()
+  // Keeps the extracted rules of every file
+  let compiler: Compiler | undefined
 
   return {
     name: 'typewritingclass',
 
     setup(build) {
       // ------------------------------------------------------------------
-      // 1. Load theme once before the build starts
+      // 1. Load theme and create the compiler once before the build starts
       // ------------------------------------------------------------------
-      let themeReady: Promise<void> | undefined
-      const ensureTheme = () => {
-        if (!themeReady) {
-          themeReady = loadTheme().then((t) => {
-            themeInput = t
+      let compilerReady: Promise<Compiler> | undefined
+      const ensureCompiler = () => {
+        if (!compilerReady) {
+          compilerReady = loadTheme().then((themeInput) => {
+            compiler = new native.Compiler(themeInput, { strict })
+            return compiler
           })
         }
-        return themeReady
+        return compilerReady
       }
 
       // ------------------------------------------------------------------
@@ -75,7 +67,7 @@ export default function twcEsbuildPlugin(options?: TwcEsbuildPluginOptions): Plu
       build.onLoad(
         { filter: /.*/, namespace: VIRTUAL_CSS_NAMESPACE },
         async (_args: OnLoadArgs): Promise<OnLoadResult> => {
-          await ensureTheme()
+          await ensureCompiler()
           const css = generateAllCss()
           return {
             contents: css,
@@ -93,7 +85,7 @@ export default function twcEsbuildPlugin(options?: TwcEsbuildPluginOptions): Plu
           // Skip node_modules
           if (args.path.includes('node_modules')) return undefined
 
-          await ensureTheme()
+          const compiler = await ensureCompiler()
 
           const source = await readFile(args.path, 'utf8')
 
@@ -104,8 +96,7 @@ export default function twcEsbuildPlugin(options?: TwcEsbuildPluginOptions): Plu
           const loader = getLoader(args.path)
 
           try {
-            const result = native.transform(source, args.path, layer, themeInput, strict)
-            layer = result.nextLayer
+            const result = compiler.transform(source, args.path)
 
             // Surface diagnostics as esbuild warnings/errors
             const warnings: Array<{ text: string; location: { file: string; line: number; column: number } }> = []
@@ -127,14 +118,6 @@ export default function twcEsbuildPlugin(options?: TwcEsbuildPluginOptions): Plu
               }
             }
 
-            // Store extracted CSS rules for this file
-            if (result.rules.length > 0) {
-              fileRules.set(
-                args.path,
-                result.rules.map((r) => r.cssText),
-              )
-            }
-
             // Inject virtual CSS import so styles are included in the bundle
             let transformed = result.code
             if (!transformed.includes(VIRTUAL_CSS_ID)) {
@@ -185,12 +168,7 @@ export default function twcEsbuildPlugin(options?: TwcEsbuildPluginOptions): Plu
   // --------------------------------------------------------------------
 
   function generateAllCss(): string {
-    const allRules: string[] = []
-    for (const rules of fileRules.values()) {
-      allRules.push(...rules)
-    }
-    if (allRules.length === 0) return ''
-    return native.generateCss(JSON.stringify(allRules))
+    return compiler?.stylesheet() ?? ''
   }
 }
 
@@ -2,7 +2,7 @@ import { createRequire } from 'module'
 import { resolve, dirname } from 'path'
 import { fileURLToPath } from 'url'
 import { writeFileSync, readFileSync, mkdirSync, existsSync } from 'fs'
-import type { ThemeInput, TransformOutput, Diagnostic } from 'typewritingclass-compiler'
+import type { ThemeInput, TransformOutput, ExtractedRule, StylesheetOptions, StylesheetOutput } from 'typewritingclass-compiler'
 import { loadThemeSync } from 'typewritingclass-compiler/loadTheme'
 
 const require = createRequire(import.meta.url)
@@ -18,19 +18,27 @@ const native: {
     themeInput: ThemeInput,
     strict?: boolean | null,
   ): TransformOutput
-  generateCss(rulesJson: string): string
+  buildStylesheet(rules: ExtractedRule[], options?: StylesheetOptions): StylesheetOutput
 } = require(resolve(compilerDir, 'index.node'))
 
-// Module-level state shared across all loader invocations
+// Module-level state shared across all loader invocations. Unlike the Vite
+// plugin this can't live in a native `Compiler`: Next.js runs the server,
+// client and edge compilations in separate build workers, and webpack skips
+// the loader for cached modules, so rules are merged through the on-disk
+// cache below. The stylesheet itself is assembled by the same native builder.
 let themeInput: ThemeInput | null = null
 let nextLayer = 0
-const fileRules = new Map<string, string[]>()
+const fileRules = new Map<string, ExtractedRule[]>()
 // Cache the starting layer for each file so HMR reprocessing produces the same class names
 const fileLayers = new Map<string, number>()
+// The stylesheet is rebuilt after every file; report each collision once
+const reportedCollisions = new Set<string>()
 
 export interface TwcLoaderOptions {
   outputFile?: string
   strict?: boolean
+  /** Rule order within a layer; `'deterministic'` makes the CSS independent of build order */
+  order?: 'source' | 'deterministic'
 }
 
 /**
@@ -62,7 +70,12 @@ export default function twcLoader(this: any, source: string): string {
       try {
         const cached = JSON.parse(readFileSync(cacheFile, 'utf-8'))
         for (const [key, value] of Object.entries(cached.fileRules)) {
-          fileRules.set(key, value as string[])
+          // Caches written by older versions hold plain CSS strings, which
+          // can't be ordered or deduplicated; those files are rebuilt
+          const rules = value as unknown[]
+          if (rules.every((rule) => typeof rule === 'object')) {
+            fileRules.set(key, rules as ExtractedRule[])
+          }
         }
         for (const [key, value] of Object.entries(cached.fileLayers)) {
           fileLayers.set(key, value as number)
@@ -99,14 +112,24 @@ export default function twcLoader(this: any, source: string): string {
 
     // Collect CSS rules
     if (result.rules.length > 0) {
-      fileRules.set(filename, result.rules.map((r) => r.cssText))
+      fileRules.set(filename, result.rules)
 
       // Write combined CSS after each file (last file wins with complete set)
-      const allRules: string[] = []
+      const allRules: ExtractedRule[] = []
       for (const rules of fileRules.values()) {
         allRules.push(...rules)
       }
-      const css = native.generateCss(JSON.stringify(allRules))
+      const { css, collisions } = native.buildStylesheet(allRules, { order: options.order })
+      for (const collision of collisions) {
+        if (reportedCollisions.has(collision.className)) continue
+        reportedCollisions.add(collision.className)
+        this.emitWarning(
+          new Error(
+            `[typewritingclass] Class ${collision.className} is generated for two different rules; keeping the first.\n` +
+              `${collision.cssText}\n${collision.otherCssText}`,
+          ),
+        )
+      }
       if (css) {
         const outputDir = dirname(resolve(outputFile))
         mkdirSync(outputDir, { recursive: true })
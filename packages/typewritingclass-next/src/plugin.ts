@@ -13,6 +13,12 @@ export interface TwcNextPluginOptions {
   outputFile?: string
   /** Enable strict mode for the compiler. Default: true */
   strict?: boolean
+  /**
+   * Rule order within a cascade layer. `'deterministic'` sorts rules by their
+   * shape so the CSS doesn't depend on the order webpack builds files in.
+   * Default: "source"
+   */
+  order?: 'source' | 'deterministic'
 }
 
 /**
@@ -35,6 +41,7 @@ export function withTwc(nextConfig: NextConfig = {}, options: TwcNextPluginOptio
   const loaderOptions = {
     outputFile: options.outputFile ?? '.next/twc.css',
     strict: options.strict ?? true,
+    order: options.order ?? 'source',
   }
 
   // Resolve loader path relative to this module (works in both source and dist)
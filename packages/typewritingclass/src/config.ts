@@ -0,0 +1,61 @@
+import { breakpoints as defaultBreakpoints } from './theme/sizes.ts'
+
+/**
+ * Theme settings that change how modifiers build rules. The compiler reads
+ * them from its theme; the runtime needs the same values so styles it
+ * resolves (uncompiled files, runtime fallbacks) match the compiled CSS.
+ */
+export interface RuntimeConfig {
+  /** Breakpoint name → min width, replacing the default `breakpoints` */
+  breakpoints?: Record<string, string>
+  /** How `dark` applies: `'media'` (default), `'class'` or `'attr'` */
+  darkMode?: 'media' | 'class' | 'attr'
+  /** Selector for the `'class'` and `'attr'` dark modes (default: `.dark` / `[data-theme="dark"]`) */
+  darkSelector?: string
+}
+
+let config: RuntimeConfig = {}
+
+/**
+ * Sets the breakpoints and dark-mode strategy used by the runtime modifiers.
+ *
+ * The Vite plugin calls this with its `theme` option before any file that
+ * needs the runtime runs. Call it yourself, as early as possible, when
+ * compiling another way with custom `breakpoints` or `darkMode`.
+ *
+ * @example
+ * ```ts
+ * import { configureRuntime } from 'typewritingclass'
+ *
+ * configureRuntime({ breakpoints: { sm: '480px', tablet: '960px' }, darkMode: 'class' })
+ * ```
+ */
+export function configureRuntime(next: RuntimeConfig): void {
+  config = { ...config, ...next }
+}
+
+/** The configured breakpoints, or the default ones. @internal */
+export function getBreakpoints(): Record<string, string> {
+  return config.breakpoints ?? defaultBreakpoints
+}
+
+/** The ancestor selector `dark` applies under, or `undefined` for the media query. @internal */
+export function getDarkSelector(): string | undefined {
+  switch (config.darkMode) {
+    case 'class':
+      return config.darkSelector ?? '.dark'
+    case 'attr':
+      return config.darkSelector ?? '[data-theme="dark"]'
+    default:
+      return undefined
+  }
+}
+
+/**
+ * Restores the default breakpoints and dark mode.
+ *
+ * @internal Exposed for testing only. Do not call in production code.
+ */
+export function _resetRuntimeConfig(): void {
+  config = {}
+}
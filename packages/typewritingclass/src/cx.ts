@@ -1,4 +1,4 @@
-import type { StyleRule } from './types.ts'
+import type { StyleRule, ClassValue } from './types.ts'
 import { generateHash } from './hash.ts'
 import { register } from './registry.ts'
 import { nextLayer, _resetLayer } from './layer.ts'
@@ -11,9 +11,12 @@ import { nextLayer, _resetLayer } from './layer.ts'
  * CSS properties conflict -- order is your specificity.
  *
  * Plain strings are passed through unchanged, so you can mix generated rules
- * with external or hand-written class names.
+ * with external or hand-written class names. Like `clsx`, falsy values are
+ * skipped, arrays are flattened and objects contribute the keys whose values
+ * are truthy.
  *
- * @param args - Style rules from utility functions, or plain class name strings.
+ * @param args - Style rules from utility functions, class name strings, or
+ *               any other {@link ClassValue}.
  * @returns A space-separated class string ready for `className` or `class`.
  *
  * @example Basic composition
@@ -45,6 +48,13 @@ import { nextLayer, _resetLayer } from './layer.ts'
  * // => "my-component _a1b2c _d3e4f"
  * ```
  *
+ * @example Conditional arguments
+ * ```ts
+ * import { cx, p, bg } from 'typewritingclass'
+ *
+ * cx(p(4), isActive ? bg('blue-500') : bg('white'), disabled && 'opacity-50', { selected })
+ * ```
+ *
  * @example With modifiers
  * ```ts
  * import { cx, p, bg, when, hover, md } from 'typewritingclass'
@@ -61,11 +71,12 @@ import { nextLayer, _resetLayer } from './layer.ts'
  * //   @media (min-width: 768px) { .cls3 { padding: 2rem; } }
  * ```
  */
-export function cx(...args: (StyleRule | string)[]): string {
+export function cx(...args: ClassValue[]): string {
+  const flat = flattenArgs(args)
   if (process.env.NODE_ENV !== 'production') {
-    warnConflicts(args)
+    warnConflicts(flat)
   }
-  return _cxCore(args)
+  return _cxCore(flat)
 }
 
 /**
@@ -75,8 +86,8 @@ export function cx(...args: (StyleRule | string)[]): string {
  *
  * @internal
  */
-export function _cxCore(args: (StyleRule | string)[]): string {
-  return args
+export function _cxCore(args: ClassValue[]): string {
+  return flattenArgs(args)
     .map((arg) => {
       if (typeof arg === 'string') return arg
       // TwChain proxies are typeof 'function' — coerce to string,
@@ -92,6 +103,33 @@ export function _cxCore(args: (StyleRule | string)[]): string {
     .join(' ')
 }
 
+/**
+ * Reduces `clsx`-style arguments to the rules and class names they contain,
+ * in order.
+ *
+ * @internal
+ */
+function flattenArgs(args: ClassValue[], out: (StyleRule | string)[] = []): (StyleRule | string)[] {
+  for (const arg of args) {
+    if (!arg || arg === true) continue
+    if (typeof arg === 'string') {
+      out.push(arg)
+    } else if (typeof arg === 'number' || typeof arg === 'bigint') {
+      out.push(String(arg))
+    } else if (Array.isArray(arg)) {
+      flattenArgs(arg, out)
+    } else if (typeof arg === 'function' || (arg as StyleRule)._tag === 'StyleRule') {
+      // Style rules, and tw chains which become their class string
+      out.push(arg as StyleRule)
+    } else {
+      for (const key of Object.keys(arg)) {
+        if ((arg as Record<string, unknown>)[key]) out.push(key)
+      }
+    }
+  }
+  return out
+}
+
 /**
  * In development mode, warns when multiple StyleRules in a single cx() call
  * declare the same CSS property without being an obvious intentional override.
@@ -16,7 +16,9 @@ import { nextLayer } from './layer.ts'
  * Use `dcx` instead of `cx` whenever at least one of your style rules was
  * built with {@link dynamic}.
  *
- * @param args - Style rules from utility functions, or plain class name strings.
+ * @param args - Style rules from utility functions (or arrays of them, as
+ *   returned by {@link when} for rules in several selector contexts), or
+ *   plain class name strings.
  * @returns A {@link DynamicResult} with `className` (space-separated class string)
  *          and `style` (CSS custom property assignments for inline styles).
  *
@@ -56,11 +58,11 @@ import { nextLayer } from './layer.ts'
  * // style     => {}
  * ```
  */
-export function dcx(...args: (StyleRule | string)[]): DynamicResult {
+export function dcx(...args: (StyleRule | StyleRule[] | string)[]): DynamicResult {
   const classNames: string[] = []
   const style: Record<string, string> = {}
 
-  for (const arg of args) {
+  for (const arg of args.flat()) {
     if (typeof arg === 'string') {
       classNames.push(arg)
       continue
@@ -49,6 +49,8 @@ export function generateHash(rule: StyleRule, _layer?: number): string {
     JSON.stringify(rule.selectors) +
     JSON.stringify(rule.mediaQueries) +
     JSON.stringify(rule.supportsQueries) +
-    (rule.selectorTemplate ?? '')
+    (rule.containerQueries?.length ? JSON.stringify(rule.containerQueries) : '') +
+    (rule.selectorTemplate ?? '') +
+    (rule.cascadeLayer ? '@layer ' + rule.cascadeLayer : '')
   return '_' + djb2(input).toString(36)
 }
@@ -8,7 +8,7 @@ export { generateCSS } from './registry.ts'
 export { tw } from './tw.ts'
 
 // Types
-export type { StyleRule, Utility, Modifier, DynamicResult } from './types.ts'
+export type { StyleRule, ClassValue, Utility, Modifier, NamedModifier, DynamicResult } from './types.ts'
 export type { TwChain } from './tw.ts'
 export type {
   Brand, CSSColor, CSSLength, CSSShadow, CSSFontWeight,
@@ -28,6 +28,10 @@ export { createTheme } from './theme/createTheme.ts'
 export type { ThemeConfig, ThemeResult, ThemeVars } from './theme/createTheme.ts'
 export { injectTheme, setTheme } from './theme/inject-theme.ts'
 
+// Runtime configuration (breakpoints, dark mode)
+export { configureRuntime } from './config.ts'
+export type { RuntimeConfig } from './config.ts'
+
 // ---------------------------------------------------------------------------
 // Utilities — token-aware (wrapped with standalone proxy)
 // ---------------------------------------------------------------------------
@@ -149,7 +153,7 @@ export {
   colSpan, colStart, colEnd, rowSpan, rowStart, rowEnd,
   gridFlow, autoCols, autoRows,
   justifyItems, justifySelf, alignContent, placeContent, placeItems, placeSelf,
-  container,
+  container, containerType, containerName,
   // Borders (non-token)
   border, borderT, borderR, borderB, borderL,
   borderX, borderY, borderS, borderE, borderStyle,
@@ -192,10 +196,10 @@ export {
   visited, checked, indeterminate, default_, required_, valid, invalid,
   inRange, outOfRange, placeholderShown, autofill, readOnly, empty,
   even, odd, firstOfType, lastOfType, onlyChild, onlyOfType, target, open_,
-  has_,
+  has_, not_, nth,
   // Responsive
   sm, md, lg, xl, _2xl,
-  maxSm, maxMd, maxLg, maxXl, max2xl,
+  maxSm, maxMd, maxLg, maxXl, max2xl, minWidth, maxWidth, screen, maxScreen,
   // Color scheme
   dark,
   // Media
@@ -208,14 +212,16 @@ export {
   data,
   // Supports
   supports,
+  // Container queries
+  containerMin, containerMax,
   // Group
   groupHover, groupFocus, groupActive, groupFocusVisible, groupFocusWithin,
   groupDisabled, groupChecked, groupEmpty, groupFirst, groupLast, groupOdd, groupEven,
-  groupOpen, groupVisited, groupHas,
+  groupOpen, groupVisited, groupHas, namedGroup,
   // Peer
   peerHover, peerFocus, peerActive, peerFocusVisible, peerDisabled,
   peerChecked, peerInvalid, peerRequired, peerPlaceholderShown,
-  peerFocusWithin, peerEmpty, peerFirst, peerLast, peerOdd, peerEven, peerOpen, peerVisited, peerHas,
+  peerFocusWithin, peerEmpty, peerFirst, peerLast, peerOdd, peerEven, peerOpen, peerVisited, peerHas, namedPeer,
   // Direction
   rtl, ltr,
 } from './modifiers/index.ts'
@@ -1,3 +1,6 @@
+import type { StyleRule } from './types.ts'
+import { combineRules } from './rule.ts'
+
 /**
  * Monotonically increasing counter used to assign a unique ordering layer
  * to each style rule. This controls the order rules appear in the generated
@@ -29,15 +32,20 @@ export function nextLayer(): number {
 }
 
 /**
- * Assigns an explicit layer priority to a style rule.
+ * Assigns an explicit layer priority or a named cascade layer to a style rule.
  *
  * By default, rules receive auto-incremented layer numbers based on
- * declaration order. Use `layer()` to override this and force a rule
- * to a specific priority level. Higher numbers = higher priority
+ * declaration order. Use `layer()` with a number to override this and force
+ * a rule to a specific priority level. Higher numbers = higher priority
  * (overrides lower).
  *
- * @param priority - The explicit layer number to assign.
- * @returns A function that accepts style rules and wraps them with the given layer.
+ * With a name, the rule is emitted inside that CSS `@layer` instead of the
+ * default `utilities` layer. Layers later in the order (`reset, base,
+ * components, utilities, overrides`) win regardless of specificity.
+ *
+ * @param priority - The explicit layer number, or the cascade layer name, to assign.
+ * @returns A function that accepts style rules, merges them as `when()`
+ *   does, and wraps the result with the given layer.
  *
  * @example Force a reset to lowest priority
  * ```ts
@@ -53,21 +61,20 @@ export function nextLayer(): number {
  * cx(p(4), layer(1000)(p(8)))
  * // p(8) at layer 1000 always overrides p(4)
  * ```
+ *
+ * @example Beat third-party component CSS
+ * ```ts
+ * cx(layer('overrides')(bg('white')))
+ * // @layer overrides { ._x { background-color: white; } }
+ * ```
  */
-export function layer(priority: number): (...rules: import('./types.ts').StyleRule[]) => import('./types.ts').StyleRule {
+export function layer(priority: number | string): (...rules: (StyleRule | StyleRule[])[]) => StyleRule | StyleRule[] {
   return (...rules) => {
-    const combined = rules.length === 1
-      ? rules[0]
-      : {
-          _tag: 'StyleRule' as const,
-          declarations: Object.assign({}, ...rules.map(r => r.declarations)),
-          selectors: [...new Set(rules.flatMap(r => r.selectors))],
-          mediaQueries: [...new Set(rules.flatMap(r => r.mediaQueries))],
-          ...(rules.some(r => r.dynamicBindings) ? {
-            dynamicBindings: Object.assign({}, ...rules.filter(r => r.dynamicBindings).map(r => r.dynamicBindings)),
-          } : {}),
-        }
-    return { ...combined, _layer: priority } as any
+    const flat = rules.flat()
+    const combined = (flat.length === 1 ? flat : combineRules(flat)).map((rule): StyleRule =>
+      typeof priority === 'string' ? { ...rule, cascadeLayer: priority } : ({ ...rule, _layer: priority } as any),
+    )
+    return combined.length === 1 ? combined[0] : combined
   }
 }
 
@@ -1,5 +1,6 @@
 import type { StyleRule, Modifier } from '../types.ts'
-import { wrapWithMediaQuery } from '../rule.ts'
+import { wrapWithMediaQuery, wrapWithSelectorTemplate } from '../rule.ts'
+import { getDarkSelector } from '../config.ts'
 
 /**
  * Applies styles only when the user's operating system or browser is set to a dark color scheme.
@@ -7,7 +8,9 @@ import { wrapWithMediaQuery } from '../rule.ts'
  * Use with {@link when} to conditionally apply style rules inside a
  * `@media (prefers-color-scheme: dark)` query. This responds to the user's
  * system-level preference, not a manual theme toggle. For manual theme switching,
- * see {@link setTheme} and the theme API.
+ * see {@link setTheme} and the theme API, or set the compiler's `darkMode` theme
+ * option to `'class'` (`.dark &`) or `'attr'` (`[data-theme="dark"] &`). The
+ * runtime follows the same setting through {@link configureRuntime}.
  *
  * @param rule - The style rule to apply in dark mode.
  * @returns A new {@link StyleRule} wrapped in the `prefers-color-scheme: dark` media query.
@@ -28,5 +31,9 @@ import { wrapWithMediaQuery } from '../rule.ts'
  * //      @media (prefers-color-scheme: dark) { .jkl { color: #f9fafb; } }
  * ```
  */
-export const dark: Modifier = (rule: StyleRule) =>
-  wrapWithMediaQuery(rule, '(prefers-color-scheme: dark)')
+export const dark: Modifier = (rule: StyleRule) => {
+  const selector = getDarkSelector()
+  return selector
+    ? wrapWithSelectorTemplate(rule, `${selector} &`)
+    : wrapWithMediaQuery(rule, '(prefers-color-scheme: dark)')
+}
@@ -0,0 +1,37 @@
+import type { StyleRule, Modifier } from '../types.ts'
+import { wrapWithContainerQuery } from '../rule.ts'
+import { containers } from '../theme/sizes.ts'
+
+function containerQuery(feature: 'min-width' | 'max-width', size: string | number, name?: string): string {
+  const width = typeof size === 'number'
+    ? `${size}px`
+    : (containers as Record<string, string>)[size] ?? size
+  const condition = `(${feature}: ${width})`
+  return name ? `${name} ${condition}` : condition
+}
+
+/**
+ * Applies styles when the nearest container (or the container called `name`)
+ * is at least `size` wide. Sizes are container breakpoints (`'md'` → `28rem`),
+ * CSS lengths, or numbers in pixels.
+ *
+ * @example
+ * ```ts
+ * cx(containerType(), containerName('sidebar'))
+ * cx(when(containerMin('md', 'sidebar'))(flexRow()))
+ * // CSS: @container sidebar (min-width: 28rem) { .abc { flex-direction: row; } }
+ * ```
+ */
+export function containerMin(size: string | number, name?: string): Modifier {
+  const query = containerQuery('min-width', size, name)
+  return (rule: StyleRule) => wrapWithContainerQuery(rule, query)
+}
+
+/**
+ * Applies styles when the nearest container (or the container called `name`)
+ * is at most `size` wide.
+ */
+export function containerMax(size: string | number, name?: string): Modifier {
+  const query = containerQuery('max-width', size, name)
+  return (rule: StyleRule) => wrapWithContainerQuery(rule, query)
+}
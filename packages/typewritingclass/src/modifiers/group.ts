@@ -1,21 +1,37 @@
-import type { StyleRule, Modifier } from '../types.ts'
-import { wrapWithSelectorTemplate } from '../rule.ts'
+import type { StyleRule, Modifier, NamedModifier } from '../types.ts'
+import { escapeIdentifier, wrapWithSelectorTemplate } from '../rule.ts'
 
-export const groupHover: Modifier = (rule: StyleRule) => wrapWithSelectorTemplate(rule, '.group:hover &')
-export const groupFocus: Modifier = (rule: StyleRule) => wrapWithSelectorTemplate(rule, '.group:focus &')
-export const groupActive: Modifier = (rule: StyleRule) => wrapWithSelectorTemplate(rule, '.group:active &')
-export const groupFocusVisible: Modifier = (rule: StyleRule) => wrapWithSelectorTemplate(rule, '.group:focus-visible &')
-export const groupFocusWithin: Modifier = (rule: StyleRule) => wrapWithSelectorTemplate(rule, '.group:focus-within &')
-export const groupDisabled: Modifier = (rule: StyleRule) => wrapWithSelectorTemplate(rule, '.group:disabled &')
-export const groupChecked: Modifier = (rule: StyleRule) => wrapWithSelectorTemplate(rule, '.group:checked &')
-export const groupEmpty: Modifier = (rule: StyleRule) => wrapWithSelectorTemplate(rule, '.group:empty &')
-export const groupFirst: Modifier = (rule: StyleRule) => wrapWithSelectorTemplate(rule, '.group:first-child &')
-export const groupLast: Modifier = (rule: StyleRule) => wrapWithSelectorTemplate(rule, '.group:last-child &')
-export const groupOdd: Modifier = (rule: StyleRule) => wrapWithSelectorTemplate(rule, '.group:nth-child(odd) &')
-export const groupEven: Modifier = (rule: StyleRule) => wrapWithSelectorTemplate(rule, '.group:nth-child(even) &')
-export const groupOpen: Modifier = (rule: StyleRule) => wrapWithSelectorTemplate(rule, '.group[open] &')
-export const groupVisited: Modifier = (rule: StyleRule) => wrapWithSelectorTemplate(rule, '.group:visited &')
+/** `.group` followed by `state`, or `.group\/name` when called with a group name */
+function groupModifier(state: string): NamedModifier {
+  return ((arg: StyleRule | string) => {
+    if (typeof arg === 'string') {
+      return (rule: StyleRule) => wrapWithSelectorTemplate(rule, `.group\\/${escapeIdentifier(arg)}${state} &`)
+    }
+    return wrapWithSelectorTemplate(arg, `.group${state} &`)
+  }) as NamedModifier
+}
+
+export const groupHover = groupModifier(':hover')
+export const groupFocus = groupModifier(':focus')
+export const groupActive = groupModifier(':active')
+export const groupFocusVisible = groupModifier(':focus-visible')
+export const groupFocusWithin = groupModifier(':focus-within')
+export const groupDisabled = groupModifier(':disabled')
+export const groupChecked = groupModifier(':checked')
+export const groupEmpty = groupModifier(':empty')
+export const groupFirst = groupModifier(':first-child')
+export const groupLast = groupModifier(':last-child')
+export const groupOdd = groupModifier(':nth-child(odd)')
+export const groupEven = groupModifier(':nth-child(even)')
+export const groupOpen = groupModifier('[open]')
+export const groupVisited = groupModifier(':visited')
+
+export function groupHas(selector: string, name?: string): Modifier {
+  const group = name ? `.group\\/${escapeIdentifier(name)}` : '.group'
+  return (rule: StyleRule) => wrapWithSelectorTemplate(rule, `${group}:has(${selector}) &`)
+}
 
-export function groupHas(selector: string): Modifier {
-  return (rule: StyleRule) => wrapWithSelectorTemplate(rule, `.group:has(${selector}) &`)
+/** The class marking an element as the group `name`, for `groupHover(name)` and friends */
+export function namedGroup(name: string): string {
+  return `group/${name}`
 }
@@ -3,15 +3,16 @@ export {
   visited, checked, indeterminate, default_, required_, valid, invalid,
   inRange, outOfRange, placeholderShown, autofill, readOnly, empty,
   even, odd, firstOfType, lastOfType, onlyChild, onlyOfType, target, open_,
-  has_,
+  has_, not_, nth,
 } from './pseudo.ts'
-export { sm, md, lg, xl, _2xl, maxSm, maxMd, maxLg, maxXl, max2xl } from './responsive.ts'
+export { sm, md, lg, xl, _2xl, maxSm, maxMd, maxLg, maxXl, max2xl, minWidth, maxWidth, screen, maxScreen } from './responsive.ts'
 export { dark } from './colorScheme.ts'
 export { motionReduce, motionSafe, print_, portrait, landscape, contrastMore, contrastLess, forcedColors } from './media.ts'
 export { before, after, placeholder_, file_, marker, selection_, firstLine, firstLetter, backdrop_ } from './pseudoElements.ts'
 export { ariaChecked, ariaDisabled, ariaExpanded, ariaHidden, ariaPressed, ariaReadonly, ariaRequired, ariaSelected, aria } from './aria.ts'
 export { data } from './data.ts'
 export { supports } from './supports.ts'
-export { groupHover, groupFocus, groupActive, groupFocusVisible, groupFocusWithin, groupDisabled, groupChecked, groupEmpty, groupFirst, groupLast, groupOdd, groupEven, groupOpen, groupVisited, groupHas } from './group.ts'
-export { peerHover, peerFocus, peerActive, peerFocusVisible, peerDisabled, peerChecked, peerInvalid, peerRequired, peerPlaceholderShown, peerFocusWithin, peerEmpty, peerFirst, peerLast, peerOdd, peerEven, peerOpen, peerVisited, peerHas } from './peer.ts'
+export { containerMin, containerMax } from './container.ts'
+export { groupHover, groupFocus, groupActive, groupFocusVisible, groupFocusWithin, groupDisabled, groupChecked, groupEmpty, groupFirst, groupLast, groupOdd, groupEven, groupOpen, groupVisited, groupHas, namedGroup } from './group.ts'
+export { peerHover, peerFocus, peerActive, peerFocusVisible, peerDisabled, peerChecked, peerInvalid, peerRequired, peerPlaceholderShown, peerFocusWithin, peerEmpty, peerFirst, peerLast, peerOdd, peerEven, peerOpen, peerVisited, peerHas, namedPeer } from './peer.ts'
 export { rtl, ltr } from './direction.ts'
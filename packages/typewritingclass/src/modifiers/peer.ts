@@ -1,24 +1,40 @@
-import type { StyleRule, Modifier } from '../types.ts'
-import { wrapWithSelectorTemplate } from '../rule.ts'
+import type { StyleRule, Modifier, NamedModifier } from '../types.ts'
+import { escapeIdentifier, wrapWithSelectorTemplate } from '../rule.ts'
 
-export const peerHover: Modifier = (rule: StyleRule) => wrapWithSelectorTemplate(rule, '.peer:hover ~ &')
-export const peerFocus: Modifier = (rule: StyleRule) => wrapWithSelectorTemplate(rule, '.peer:focus ~ &')
-export const peerActive: Modifier = (rule: StyleRule) => wrapWithSelectorTemplate(rule, '.peer:active ~ &')
-export const peerFocusVisible: Modifier = (rule: StyleRule) => wrapWithSelectorTemplate(rule, '.peer:focus-visible ~ &')
-export const peerDisabled: Modifier = (rule: StyleRule) => wrapWithSelectorTemplate(rule, '.peer:disabled ~ &')
-export const peerChecked: Modifier = (rule: StyleRule) => wrapWithSelectorTemplate(rule, '.peer:checked ~ &')
-export const peerInvalid: Modifier = (rule: StyleRule) => wrapWithSelectorTemplate(rule, '.peer:invalid ~ &')
-export const peerRequired: Modifier = (rule: StyleRule) => wrapWithSelectorTemplate(rule, '.peer:required ~ &')
-export const peerPlaceholderShown: Modifier = (rule: StyleRule) => wrapWithSelectorTemplate(rule, '.peer:placeholder-shown ~ &')
-export const peerFocusWithin: Modifier = (rule: StyleRule) => wrapWithSelectorTemplate(rule, '.peer:focus-within ~ &')
-export const peerEmpty: Modifier = (rule: StyleRule) => wrapWithSelectorTemplate(rule, '.peer:empty ~ &')
-export const peerFirst: Modifier = (rule: StyleRule) => wrapWithSelectorTemplate(rule, '.peer:first-child ~ &')
-export const peerLast: Modifier = (rule: StyleRule) => wrapWithSelectorTemplate(rule, '.peer:last-child ~ &')
-export const peerOdd: Modifier = (rule: StyleRule) => wrapWithSelectorTemplate(rule, '.peer:nth-child(odd) ~ &')
-export const peerEven: Modifier = (rule: StyleRule) => wrapWithSelectorTemplate(rule, '.peer:nth-child(even) ~ &')
-export const peerOpen: Modifier = (rule: StyleRule) => wrapWithSelectorTemplate(rule, '.peer[open] ~ &')
-export const peerVisited: Modifier = (rule: StyleRule) => wrapWithSelectorTemplate(rule, '.peer:visited ~ &')
+/** `.peer` followed by `state`, or `.peer\/name` when called with a peer name */
+function peerModifier(state: string): NamedModifier {
+  return ((arg: StyleRule | string) => {
+    if (typeof arg === 'string') {
+      return (rule: StyleRule) => wrapWithSelectorTemplate(rule, `.peer\\/${escapeIdentifier(arg)}${state} ~ &`)
+    }
+    return wrapWithSelectorTemplate(arg, `.peer${state} ~ &`)
+  }) as NamedModifier
+}
+
+export const peerHover = peerModifier(':hover')
+export const peerFocus = peerModifier(':focus')
+export const peerActive = peerModifier(':active')
+export const peerFocusVisible = peerModifier(':focus-visible')
+export const peerDisabled = peerModifier(':disabled')
+export const peerChecked = peerModifier(':checked')
+export const peerInvalid = peerModifier(':invalid')
+export const peerRequired = peerModifier(':required')
+export const peerPlaceholderShown = peerModifier(':placeholder-shown')
+export const peerFocusWithin = peerModifier(':focus-within')
+export const peerEmpty = peerModifier(':empty')
+export const peerFirst = peerModifier(':first-child')
+export const peerLast = peerModifier(':last-child')
+export const peerOdd = peerModifier(':nth-child(odd)')
+export const peerEven = peerModifier(':nth-child(even)')
+export const peerOpen = peerModifier('[open]')
+export const peerVisited = peerModifier(':visited')
+
+export function peerHas(selector: string, name?: string): Modifier {
+  const peer = name ? `.peer\\/${escapeIdentifier(name)}` : '.peer'
+  return (rule: StyleRule) => wrapWithSelectorTemplate(rule, `${peer}:has(${selector}) ~ &`)
+}
 
-export function peerHas(selector: string): Modifier {
-  return (rule: StyleRule) => wrapWithSelectorTemplate(rule, `.peer:has(${selector}) ~ &`)
+/** The class marking an element as the peer `name`, for `peerChecked(name)` and friends */
+export function namedPeer(name: string): string {
+  return `peer/${name}`
 }
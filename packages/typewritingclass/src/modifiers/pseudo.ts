@@ -181,3 +181,11 @@ export const open_: Modifier = (rule: StyleRule) => wrapWithSelector(rule, '[ope
 export function has_(selector: string): Modifier {
   return (rule: StyleRule) => wrapWithSelector(rule, `:has(${selector})`)
 }
+
+export function not_(selector: string): Modifier {
+  return (rule: StyleRule) => wrapWithSelector(rule, `:not(${selector})`)
+}
+
+export function nth(n: number | string): Modifier {
+  return (rule: StyleRule) => wrapWithSelector(rule, `:nth-child(${n})`)
+}
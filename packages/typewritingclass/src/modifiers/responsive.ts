@@ -1,8 +1,54 @@
 import type { StyleRule, Modifier } from '../types.ts'
 import { wrapWithMediaQuery } from '../rule.ts'
+import { getBreakpoints } from '../config.ts'
+
+/** The largest width below a breakpoint: one pixel less for pixel widths, `calc()` otherwise */
+function below(width: string): string {
+  const px = /^(\d*\.?\d+)px$/.exec(width)
+  return px ? `${Number(px[1]) - 1}px` : `calc(${width} - 1px)`
+}
+
+/**
+ * A breakpoint name (`'md'`), a CSS length, or a number of pixels. Names
+ * come from the configured breakpoints (see {@link configureRuntime}).
+ */
+function screenWidth(size: string | number): string {
+  if (typeof size === 'number') return `${size}px`
+  const breakpoints = getBreakpoints()
+  const width = breakpoints[size]
+  if (width) return width
+  if (/^[\d.]/.test(size)) return size
+  throw new Error(`Unknown breakpoint '${size}'. Expected one of: ${Object.keys(breakpoints).join(', ')}`)
+}
+
+/** `(min-width: <breakpoint>)`, resolved when the modifier is applied */
+function above(name: string): Modifier {
+  return (rule: StyleRule) => wrapWithMediaQuery(rule, `(min-width: ${screenWidth(name)})`)
+}
+
+/** `(max-width: <just below breakpoint>)`, resolved when the modifier is applied */
+function beneath(name: string): Modifier {
+  return (rule: StyleRule) => wrapWithMediaQuery(rule, `(max-width: ${below(screenWidth(name))})`)
+}
+
+/**
+ * The modifier a `tw` chain property names when it's a configured
+ * breakpoint: `tablet` is `screen('tablet')`, `maxTablet` is
+ * `maxScreen('tablet')`.
+ *
+ * @internal
+ */
+export function breakpointModifier(name: string): Modifier | undefined {
+  const breakpoints = getBreakpoints()
+  if (name in breakpoints) return above(name)
+  const rest = name.startsWith('max') ? name.slice(3) : ''
+  const breakpoint = rest.charAt(0).toLowerCase() + rest.slice(1)
+  if (breakpoint && breakpoint in breakpoints) return beneath(breakpoint)
+  return undefined
+}
 
 /**
- * Applies styles at the **small** breakpoint and above (`min-width: 640px`).
+ * Applies styles at the **small** breakpoint and above (`min-width: 640px` by default).
  *
  * Use with {@link when} to make style rules responsive. Wraps the rule in a
  * `@media (min-width: 640px)` query.
@@ -19,10 +65,10 @@ import { wrapWithMediaQuery } from '../rule.ts'
  * //      @media (min-width: 640px) { .def { padding: 2rem; } }
  * ```
  */
-export const sm: Modifier = (rule: StyleRule) => wrapWithMediaQuery(rule, '(min-width: 640px)')
+export const sm: Modifier = above('sm')
 
 /**
- * Applies styles at the **medium** breakpoint and above (`min-width: 768px`).
+ * Applies styles at the **medium** breakpoint and above (`min-width: 768px` by default).
  *
  * Use with {@link when} to make style rules responsive. Wraps the rule in a
  * `@media (min-width: 768px)` query.
@@ -39,10 +85,10 @@ export const sm: Modifier = (rule: StyleRule) => wrapWithMediaQuery(rule, '(min-
  * //      @media (min-width: 768px) { .def { grid-template-columns: 2; } }
  * ```
  */
-export const md: Modifier = (rule: StyleRule) => wrapWithMediaQuery(rule, '(min-width: 768px)')
+export const md: Modifier = above('md')
 
 /**
- * Applies styles at the **large** breakpoint and above (`min-width: 1024px`).
+ * Applies styles at the **large** breakpoint and above (`min-width: 1024px` by default).
  *
  * Use with {@link when} to make style rules responsive. Wraps the rule in a
  * `@media (min-width: 1024px)` query.
@@ -59,10 +105,10 @@ export const md: Modifier = (rule: StyleRule) => wrapWithMediaQuery(rule, '(min-
  * //      @media (min-width: 1024px) { .def { max-width: 1024px; } }
  * ```
  */
-export const lg: Modifier = (rule: StyleRule) => wrapWithMediaQuery(rule, '(min-width: 1024px)')
+export const lg: Modifier = above('lg')
 
 /**
- * Applies styles at the **extra-large** breakpoint and above (`min-width: 1280px`).
+ * Applies styles at the **extra-large** breakpoint and above (`min-width: 1280px` by default).
  *
  * Use with {@link when} to make style rules responsive. Wraps the rule in a
  * `@media (min-width: 1280px)` query.
@@ -79,10 +125,10 @@ export const lg: Modifier = (rule: StyleRule) => wrapWithMediaQuery(rule, '(min-
  * //      @media (min-width: 1280px) { .def { max-width: 1280px; } }
  * ```
  */
-export const xl: Modifier = (rule: StyleRule) => wrapWithMediaQuery(rule, '(min-width: 1280px)')
+export const xl: Modifier = above('xl')
 
 /**
- * Applies styles at the **2x-large** breakpoint and above (`min-width: 1536px`).
+ * Applies styles at the **2x-large** breakpoint and above (`min-width: 1536px` by default).
  *
  * Use with {@link when} to make style rules responsive. Wraps the rule in a
  * `@media (min-width: 1536px)` query.
@@ -101,10 +147,68 @@ export const xl: Modifier = (rule: StyleRule) => wrapWithMediaQuery(rule, '(min-
  * //      @media (min-width: 1536px) { .def { max-width: 1536px; } }
  * ```
  */
-export const _2xl: Modifier = (rule: StyleRule) => wrapWithMediaQuery(rule, '(min-width: 1536px)')
+export const _2xl: Modifier = above('2xl')
+
+export const maxSm: Modifier = beneath('sm')
+export const maxMd: Modifier = beneath('md')
+export const maxLg: Modifier = beneath('lg')
+export const maxXl: Modifier = beneath('xl')
+export const max2xl: Modifier = beneath('2xl')
+
+/**
+ * Applies styles at an arbitrary width and above. Numbers are pixels.
+ *
+ * @example
+ * ```ts
+ * cx(when(minWidth(900))(p(8)))
+ * // CSS: @media (min-width: 900px) { .abc { padding: 2rem; } }
+ * ```
+ */
+export function minWidth(width: number | string): Modifier {
+  const w = typeof width === 'number' ? `${width}px` : width
+  return (rule: StyleRule) => wrapWithMediaQuery(rule, `(min-width: ${w})`)
+}
+
+/**
+ * Applies styles below an arbitrary width. Numbers are pixels.
+ *
+ * @example
+ * ```ts
+ * cx(when(maxWidth('40em'))(p(2)))
+ * // CSS: @media (max-width: 40em) { .abc { padding: 0.5rem; } }
+ * ```
+ */
+export function maxWidth(width: number | string): Modifier {
+  const w = typeof width === 'number' ? `${width}px` : width
+  return (rule: StyleRule) => wrapWithMediaQuery(rule, `(max-width: ${w})`)
+}
 
-export const maxSm: Modifier = (rule: StyleRule) => wrapWithMediaQuery(rule, '(max-width: 639px)')
-export const maxMd: Modifier = (rule: StyleRule) => wrapWithMediaQuery(rule, '(max-width: 767px)')
-export const maxLg: Modifier = (rule: StyleRule) => wrapWithMediaQuery(rule, '(max-width: 1023px)')
-export const maxXl: Modifier = (rule: StyleRule) => wrapWithMediaQuery(rule, '(max-width: 1279px)')
-export const max2xl: Modifier = (rule: StyleRule) => wrapWithMediaQuery(rule, '(max-width: 1535px)')
+/**
+ * Applies styles at a named breakpoint and above. Breakpoints come from the
+ * theme, so names it defines are accepted too (`screen('tablet')`); at
+ * runtime they come from {@link configureRuntime}.
+ *
+ * @example
+ * ```ts
+ * cx(when(screen('md'))(p(8)))
+ * // CSS: @media (min-width: 768px) { .abc { padding: 2rem; } }
+ * ```
+ */
+export function screen(size: string | number): Modifier {
+  const query = `(min-width: ${screenWidth(size)})`
+  return (rule: StyleRule) => wrapWithMediaQuery(rule, query)
+}
+
+/**
+ * Applies styles below a named breakpoint.
+ *
+ * @example
+ * ```ts
+ * cx(when(maxScreen('md'))(p(2)))
+ * // CSS: @media (max-width: 767px) { .abc { padding: 0.5rem; } }
+ * ```
+ */
+export function maxScreen(size: string | number): Modifier {
+  const query = `(max-width: ${below(screenWidth(size))})`
+  return (rule: StyleRule) => wrapWithMediaQuery(rule, query)
+}
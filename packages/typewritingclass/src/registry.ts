@@ -1,4 +1,5 @@
 import type { StyleRule } from './types.ts'
+import { isPseudoElement } from './rule.ts'
 
 /**
  * An entry in the style registry, pairing a {@link StyleRule} with its
@@ -21,6 +22,21 @@ interface RegistryEntry {
  */
 const registry = new Map<string, RegistryEntry>()
 
+/**
+ * Order of the named cascade layers in the generated stylesheet. Later layers
+ * override earlier ones regardless of specificity.
+ *
+ * @internal
+ */
+export const LAYER_ORDER = ['reset', 'base', 'components', 'utilities', 'overrides']
+
+/**
+ * The layer of rules that weren't given a name with `layer('name')`.
+ *
+ * @internal
+ */
+const DEFAULT_LAYER = 'utilities'
+
 /**
  * List of callbacks invoked whenever a new rule is registered.
  *
@@ -96,18 +112,18 @@ function renderRule(className: string, rule: StyleRule): string {
     .map(([prop, val]) => `  ${prop}: ${val};`)
     .join('\n')
 
-  let selector: string
-  if (rule.selectorTemplate) {
-    selector = rule.selectorTemplate.replace(/&/g, `.${className}`)
-  } else {
-    selector = `.${className}`
-    if (rule.selectors.length > 0) {
-      selector += rule.selectors.join('')
-    }
-  }
+  // Compound selectors attach to the class; the template supplies the
+  // surrounding context; pseudo-elements end the whole selector
+  const subject = `.${className}` + rule.selectors.filter(s => !isPseudoElement(s)).join('')
+  let selector = rule.selectorTemplate ? rule.selectorTemplate.replace(/&/g, subject) : subject
+  selector += rule.selectors.filter(isPseudoElement).join('')
 
   let css = `${selector} {\n${decls}\n}`
 
+  for (const cq of rule.containerQueries ?? []) {
+    css = `@container ${cq} {\n${css}\n}`
+  }
+
   for (const sq of rule.supportsQueries) {
     css = `@supports ${sq} {\n${css}\n}`
   }
@@ -126,6 +142,12 @@ function renderRule(className: string, rule: StyleRule): string {
  * utilities naturally override earlier ones in the cascade. Rules are
  * separated by blank lines.
  *
+ * Once any rule has been assigned a named cascade layer with `layer('name')`,
+ * the stylesheet opens with an `@layer` statement fixing the order
+ * {@link LAYER_ORDER} (plus any other named layer, alphabetically) and each
+ * rule is emitted inside its layer's block; rules without a name go in
+ * `utilities`.
+ *
  * @internal
  * @returns The full CSS string for all registered style rules, or an empty
  *   string if no rules have been registered.
@@ -141,9 +163,27 @@ function renderRule(className: string, rule: StyleRule): string {
  */
 export function generateCSS(): string {
   const entries = [...registry.entries()].sort((a, b) => a[1].layer - b[1].layer)
-  const rules = entries.map(([className, { rule }]) => renderRule(className, rule))
-  if (rules.length === 0) return ''
-  return rules.join('\n\n')
+  if (!entries.some(([, { rule }]) => rule.cascadeLayer)) {
+    const rules = entries.map(([className, { rule }]) => renderRule(className, rule))
+    if (rules.length === 0) return ''
+    return rules.join('\n\n')
+  }
+
+  const layerOf = (rule: StyleRule) => rule.cascadeLayer ?? DEFAULT_LAYER
+  const extra = [...new Set(entries.map(([, { rule }]) => layerOf(rule)))]
+    .filter((name) => !LAYER_ORDER.includes(name))
+    .sort()
+  const order = [...LAYER_ORDER, ...extra]
+  const blocks = [`@layer ${order.join(', ')};`]
+  for (const name of order) {
+    const rules = entries
+      .filter(([, { rule }]) => layerOf(rule) === name)
+      .map(([className, { rule }]) => renderRule(className, rule))
+    if (rules.length === 0) continue
+    const body = rules.join('\n\n').split('\n').map((line) => (line ? '  ' + line : line)).join('\n')
+    blocks.push(`@layer ${name} {\n${body}\n}`)
+  }
+  return blocks.join('\n\n')
 }
 
 /**
@@ -67,36 +67,61 @@ export function createDynamicRule(
 }
 
 /**
- * Merges multiple {@link StyleRule} objects into a single combined rule.
+ * Merges multiple {@link StyleRule} objects into as few rules as possible.
  *
  * Declarations are merged left-to-right (later rules override earlier ones for
  * the same property). Selectors and media queries are de-duplicated and
  * concatenated. Dynamic bindings from all rules are merged together.
  *
+ * Rules with different selector templates (e.g. `groupHover(...)` next to a
+ * plain rule) or in different named cascade layers can't share a class, so
+ * each such context gets its own merged rule, in the order its first rule
+ * appears. The compiler splits them the same way.
+ *
  * @internal
  * @param rules - An array of {@link StyleRule} objects to merge.
- * @returns A single {@link StyleRule} containing the merged declarations,
- *   selectors, media queries, and dynamic bindings from all input rules.
+ * @returns One {@link StyleRule} per selector context and cascade layer,
+ *   containing the merged declarations, selectors, media queries, and dynamic bindings of its
+ *   rules; a single empty rule when `rules` is empty.
  *
  * @example
  * ```ts
  * const a = createRule({ padding: '1rem' })
  * const b = createRule({ margin: '0' })
- * const combined = combineRules([a, b])
+ * const [combined] = combineRules([a, b])
  * // combined.declarations === { padding: '1rem', margin: '0' }
  * ```
  */
-export function combineRules(rules: StyleRule[]): StyleRule {
+export function combineRules(rules: StyleRule[]): StyleRule[] {
+  const groups: StyleRule[][] = []
+  for (const rule of rules) {
+    const group = groups.find(([first]) =>
+      first.selectorTemplate === rule.selectorTemplate && first.cascadeLayer === rule.cascadeLayer,
+    )
+    if (group) group.push(rule)
+    else groups.push([rule])
+  }
+  return groups.length === 0 ? [mergeRules([])] : groups.map(mergeRules)
+}
+
+/** Merges rules sharing one selector context and cascade layer into one */
+function mergeRules(rules: StyleRule[]): StyleRule {
   const merged: Record<string, string> = {}
-  const selectorSet = new Set<string>()
+  let selectors: string[] = []
   const mediaQuerySet = new Set<string>()
   const supportsQuerySet = new Set<string>()
+  const containerQuerySet = new Set<string>()
   let dynamicBindings: Record<string, string> | undefined
+  const template = rules[0]?.selectorTemplate
+  const cascadeLayer = rules[0]?.cascadeLayer
   for (const rule of rules) {
     Object.assign(merged, rule.declarations)
-    for (const s of rule.selectors) selectorSet.add(s)
+    for (const s of rule.selectors) {
+      if (!selectors.includes(s)) selectors = insertSelector(selectors, s)
+    }
     for (const mq of rule.mediaQueries) mediaQuerySet.add(mq)
     for (const sq of rule.supportsQueries) supportsQuerySet.add(sq)
+    for (const cq of rule.containerQueries ?? []) containerQuerySet.add(cq)
     if (rule.dynamicBindings) {
       if (!dynamicBindings) dynamicBindings = {}
       Object.assign(dynamicBindings, rule.dynamicBindings)
@@ -105,10 +130,13 @@ export function combineRules(rules: StyleRule[]): StyleRule {
   const result: StyleRule = {
     _tag: 'StyleRule',
     declarations: merged,
-    selectors: [...selectorSet],
+    selectors,
     mediaQueries: [...mediaQuerySet],
     supportsQueries: [...supportsQuerySet],
   }
+  if (template) result.selectorTemplate = template
+  if (cascadeLayer) result.cascadeLayer = cascadeLayer
+  if (containerQuerySet.size > 0) result.containerQueries = [...containerQuerySet]
   if (dynamicBindings) result.dynamicBindings = dynamicBindings
   return result
 }
@@ -135,10 +163,22 @@ export function combineRules(rules: StyleRule[]): StyleRule {
 export function wrapWithSelector(rule: StyleRule, selector: string): StyleRule {
   return {
     ...rule,
-    selectors: [...rule.selectors, selector],
+    selectors: insertSelector(rule.selectors, selector),
   }
 }
 
+/** `::before`, `::placeholder`, `::file-selector-button`... */
+export function isPseudoElement(selector: string): boolean {
+  return selector.startsWith('::')
+}
+
+/** Pseudo-elements always stay last, since nothing may follow them. */
+function insertSelector(selectors: string[], selector: string): string[] {
+  const firstPseudoElement = selectors.findIndex(isPseudoElement)
+  const at = isPseudoElement(selector) || firstPseudoElement === -1 ? selectors.length : firstPseudoElement
+  return [...selectors.slice(0, at), selector, ...selectors.slice(at)]
+}
+
 /**
  * Returns a copy of the given rule wrapped in an additional CSS media query.
  *
@@ -169,7 +209,9 @@ export function wrapWithMediaQuery(rule: StyleRule, query: string): StyleRule {
  * Returns a copy of the given rule with a selector template applied.
  *
  * The template uses `&` as a placeholder for the generated class name.
- * At render time, `&` is replaced with `.className`.
+ * At render time, `&` is replaced with `.className` and its selectors.
+ * An existing template takes the place of `&`, so stacked contexts compose:
+ * `rtl` over `groupHover` gives `[dir="rtl"] .group:hover &`.
  *
  * @internal
  * @param rule - The source {@link StyleRule} to wrap.
@@ -179,10 +221,25 @@ export function wrapWithMediaQuery(rule: StyleRule, query: string): StyleRule {
 export function wrapWithSelectorTemplate(rule: StyleRule, template: string): StyleRule {
   return {
     ...rule,
-    selectorTemplate: template,
+    selectorTemplate: rule.selectorTemplate ? template.replace(/&/g, () => rule.selectorTemplate!) : template,
   }
 }
 
+/**
+ * Escapes a name so it can continue a class selector, e.g. the group name
+ * `main.nav` in `.group\/main\.nav`. The compiler escapes names the same way.
+ *
+ * @internal
+ * @param name - The name to escape.
+ * @returns The name with every ASCII character other than letters, digits, `_`
+ *   and `-` escaped.
+ */
+export function escapeIdentifier(name: string): string {
+  return name.replace(/[^\w\-\u0080-\uffff]/g, (c) =>
+    /[\x00-\x1f\x7f]/.test(c) ? `\\${c.charCodeAt(0).toString(16)} ` : `\\${c}`,
+  )
+}
+
 /**
  * Returns a copy of the given rule wrapped in an additional CSS `@supports` query.
  *
@@ -200,3 +257,22 @@ export function wrapWithSupportsQuery(rule: StyleRule, query: string): StyleRule
     supportsQueries: [...rule.supportsQueries, query],
   }
 }
+
+/**
+ * Returns a copy of the given rule wrapped in an additional CSS `@container` query.
+ *
+ * When rendered, the rule's CSS block is nested inside a `@container` at-rule,
+ * which sits inside any `@supports` and `@media` wrappers.
+ *
+ * @internal
+ * @param rule - The source {@link StyleRule} to wrap.
+ * @param query - The container query prelude, optionally starting with a
+ *   container name (e.g., `'(min-width: 28rem)'`, `'sidebar (min-width: 28rem)'`).
+ * @returns A new {@link StyleRule} with the query added to its `containerQueries` array.
+ */
+export function wrapWithContainerQuery(rule: StyleRule, query: string): StyleRule {
+  return {
+    ...rule,
+    containerQueries: [...(rule.containerQueries ?? []), query],
+  }
+}
@@ -35,3 +35,35 @@ export const maxWidths = {
   screenXl: '1280px',
   screen2xl: '1536px',
 } as const
+
+// ---------------------------------------------------------------------------
+// Responsive breakpoints (min widths for `sm`, `md`, ... and `screen()`)
+// ---------------------------------------------------------------------------
+
+export const breakpoints = {
+  sm: '640px',
+  md: '768px',
+  lg: '1024px',
+  xl: '1280px',
+  '2xl': '1536px',
+} as const
+
+// ---------------------------------------------------------------------------
+// Container query breakpoints (Tailwind v4 defaults)
+// ---------------------------------------------------------------------------
+
+export const containers = {
+  '3xs': '16rem',
+  '2xs': '18rem',
+  xs: '20rem',
+  sm: '24rem',
+  md: '28rem',
+  lg: '32rem',
+  xl: '36rem',
+  '2xl': '42rem',
+  '3xl': '48rem',
+  '4xl': '56rem',
+  '5xl': '64rem',
+  '6xl': '72rem',
+  '7xl': '80rem',
+} as const
@@ -35,8 +35,21 @@ const spacingScale: Record<number, string> = {
   96: '24rem',
 }
 
+/**
+ * Negates a CSS length: `1rem` → `-1rem`, `-2px` → `2px`, zero is left as is,
+ * and anything else (`var(--x)`, `calc(...)`) becomes `calc(... * -1)`.
+ */
+function negateLength(value: string): string {
+  const match = /^(-?)((\d*\.?\d+)([a-z%]*))$/i.exec(value.trim())
+  if (!match) return `calc(${value} * -1)`
+  const [, sign, magnitude, number] = match
+  if (Number(number) === 0 || sign) return magnitude
+  return `-${magnitude}`
+}
+
 export function resolveSpacing(value: number | string): string {
   if (typeof value === 'string') return value
+  if (value < 0) return negateLength(resolveSpacing(-value))
   if (value in spacingScale) return spacingScale[value]
   return `${value * 0.25}rem`
 }
@@ -1,4 +1,4 @@
-import type { StyleRule, Modifier } from './types.ts'
+import type { StyleRule, Modifier, NamedModifier } from './types.ts'
 import type {
   ColorTokenKey, RadiusTokens, ShadowTokens, TextSizeTokens, FontWeightTokens,
   TrackingTokens, LeadingTokens, FontFamilyTokens, AlignItemsTokens, JustifyTokens,
@@ -51,6 +51,7 @@ import {
   colSpan, colStart, colEnd, rowSpan, rowStart, rowEnd,
   gridFlow, autoCols, autoRows,
   justifyItems, justifySelf, alignContent, placeContent, placeItems, placeSelf,
+  containerType, containerName,
   container,
 } from './utilities/layout.ts'
 import {
@@ -100,24 +101,25 @@ import {
   visited, checked, indeterminate, default_, required_, valid, invalid,
   inRange, outOfRange, placeholderShown, autofill, readOnly, empty,
   even, odd, firstOfType, lastOfType, onlyChild, onlyOfType, target, open_,
-  has_,
+  has_, not_, nth,
 } from './modifiers/pseudo.ts'
-import { sm, md, lg, xl, _2xl, maxSm, maxMd, maxLg, maxXl, max2xl } from './modifiers/responsive.ts'
+import { sm, md, lg, xl, _2xl, maxSm, maxMd, maxLg, maxXl, max2xl, minWidth, maxWidth, screen, maxScreen, breakpointModifier } from './modifiers/responsive.ts'
 import { dark } from './modifiers/colorScheme.ts'
 import { motionReduce, motionSafe, print_, portrait, landscape, contrastMore, contrastLess, forcedColors } from './modifiers/media.ts'
 import { before, after, placeholder_, file_, marker, selection_, firstLine, firstLetter, backdrop_ } from './modifiers/pseudoElements.ts'
 import { ariaChecked, ariaDisabled, ariaExpanded, ariaHidden, ariaPressed, ariaReadonly, ariaRequired, ariaSelected, aria } from './modifiers/aria.ts'
 import { data } from './modifiers/data.ts'
 import { supports } from './modifiers/supports.ts'
+import { containerMin, containerMax } from './modifiers/container.ts'
 import {
   groupHover, groupFocus, groupActive, groupFocusVisible, groupFocusWithin,
   groupDisabled, groupChecked, groupEmpty, groupFirst, groupLast, groupOdd, groupEven,
-  groupOpen, groupVisited, groupHas,
+  groupOpen, groupVisited, groupHas, namedGroup,
 } from './modifiers/group.ts'
 import {
   peerHover, peerFocus, peerActive, peerFocusVisible, peerDisabled,
   peerChecked, peerInvalid, peerRequired, peerPlaceholderShown,
-  peerFocusWithin, peerEmpty, peerFirst, peerLast, peerOdd, peerEven, peerOpen, peerVisited, peerHas,
+  peerFocusWithin, peerEmpty, peerFirst, peerLast, peerOdd, peerEven, peerOpen, peerVisited, peerHas, namedPeer,
 } from './modifiers/peer.ts'
 import { rtl, ltr } from './modifiers/direction.ts'
 
@@ -160,6 +162,7 @@ const UTILS: Record<string, (...args: any[]) => StyleRule> = {
   colSpan, colStart, colEnd, rowSpan, rowStart, rowEnd,
   gridFlow, autoCols, autoRows,
   justifyItems, justifySelf, alignContent, placeContent, placeItems, placeSelf,
+  containerType, containerName,
   // Borders
   rounded, roundedT, roundedB, roundedL, roundedR,
   roundedTL, roundedTR, roundedBR, roundedBL,
@@ -230,6 +233,22 @@ const VALUELESS: Record<string, (() => StyleRule) | string> = {
   peer: 'peer',
 }
 
+/** Utilities returning a raw class name — mapped name → function */
+const MARKERS: Record<string, (name: string) => string> = {
+  namedGroup,
+  namedPeer,
+}
+
+/** Group/peer modifiers, also callable with a group name (`tw.group('sidebar').hover`) */
+const NAMED_MODS: Record<string, NamedModifier> = {
+  groupHover, groupFocus, groupActive, groupFocusVisible, groupFocusWithin,
+  groupDisabled, groupChecked, groupEmpty, groupFirst, groupLast, groupOdd, groupEven,
+  groupOpen, groupVisited,
+  peerHover, peerFocus, peerActive, peerFocusVisible, peerDisabled,
+  peerChecked, peerInvalid, peerRequired, peerPlaceholderShown,
+  peerFocusWithin, peerEmpty, peerFirst, peerLast, peerOdd, peerEven, peerOpen, peerVisited,
+}
+
 /** Simple modifiers (no arguments) — mapped name → Modifier */
 const MODS: Record<string, Modifier> = {
   // Pseudo-classes
@@ -257,14 +276,8 @@ const MODS: Record<string, Modifier> = {
   backdropEl: backdrop_,
   // ARIA
   ariaChecked, ariaDisabled, ariaExpanded, ariaHidden, ariaPressed, ariaReadonly, ariaRequired, ariaSelected,
-  // Group
-  groupHover, groupFocus, groupActive, groupFocusVisible, groupFocusWithin,
-  groupDisabled, groupChecked, groupEmpty, groupFirst, groupLast, groupOdd, groupEven,
-  groupOpen, groupVisited,
-  // Peer
-  peerHover, peerFocus, peerActive, peerFocusVisible, peerDisabled,
-  peerChecked, peerInvalid, peerRequired, peerPlaceholderShown,
-  peerFocusWithin, peerEmpty, peerFirst, peerLast, peerOdd, peerEven, peerOpen, peerVisited,
+  // Group / peer
+  ...NAMED_MODS,
   // Direction
   rtl, ltr,
 }
@@ -272,11 +285,20 @@ const MODS: Record<string, Modifier> = {
 /** Parameterized modifiers (take arguments, return Modifier) */
 const PARAM_MODS: Record<string, (...args: any[]) => Modifier> = {
   has: has_,
+  not: not_,
+  nth,
+  minWidth,
+  maxWidth,
+  screen,
+  maxScreen,
   aria,
   data,
   supports,
+  containerMin,
+  containerMax,
   groupHas,
   peerHas,
+  layer: (name: string): Modifier => (rule) => ({ ...rule, cascadeLayer: name }),
 }
 
 // ---------------------------------------------------------------------------
@@ -333,6 +355,18 @@ type TwModifier = TwChainString & ((...chains: (TwChain | string)[]) => TwChainS
 /** @internal A parameterized modifier that requires arguments before it becomes a modifier. */
 type TwParamModifier = (...args: any[]) => TwChainString
 
+/** @internal States available on a named group (`tw.group('sidebar').hover`) */
+type TwGroupState =
+  | 'hover' | 'focus' | 'active' | 'focusVisible' | 'focusWithin' | 'disabled' | 'checked'
+  | 'empty' | 'first' | 'last' | 'odd' | 'even' | 'open' | 'visited'
+
+/** @internal States available on a named peer (`tw.peer('email').checked`) */
+type TwPeerState = TwGroupState | 'invalid' | 'required' | 'placeholderShown'
+
+/** @internal `tw.group` as a class name, or `tw.group(name)` selecting the group the next modifier targets */
+type TwGroup<S extends string> = TwChainString
+  & ((name: string) => { readonly [K in S]: TwModifier } & { readonly has: TwParamModifier })
+
 // ---------------------------------------------------------------------------
 // TwChain type
 // ---------------------------------------------------------------------------
@@ -480,6 +514,8 @@ export interface TwChain {
   readonly placeContent: TwUtility
   readonly placeItems: TwUtility
   readonly placeSelf: TwUtility
+  readonly containerType: TwUtility
+  readonly containerName: TwUtility
   // Borders (token-aware for radius)
   readonly rounded: TwRadiusUtility
   readonly roundedT: TwRadiusUtility
@@ -679,8 +715,10 @@ export interface TwChain {
   readonly srOnly: TwChainString
   readonly notSrOnly: TwChainString
   // Raw class names
-  readonly group: TwChainString
-  readonly peer: TwChainString
+  readonly group: TwGroup<TwGroupState>
+  readonly peer: TwGroup<TwPeerState>
+  readonly namedGroup: (name: string) => TwChainString
+  readonly namedPeer: (name: string) => TwChainString
 
   // ---- Simple modifiers (no arguments) — keep in sync with MODS ----
 
@@ -795,11 +833,21 @@ export interface TwChain {
   // ---- Parameterized modifiers — keep in sync with PARAM_MODS ----
 
   readonly has: TwParamModifier
+  readonly not: TwParamModifier
+  readonly nth: TwParamModifier
+  readonly minWidth: TwParamModifier
+  readonly maxWidth: TwParamModifier
+  readonly screen: TwParamModifier
+  readonly maxScreen: TwParamModifier
   readonly aria: TwParamModifier
   readonly data: TwParamModifier
   readonly supports: TwParamModifier
+  readonly containerMin: TwParamModifier
+  readonly containerMax: TwParamModifier
   readonly groupHas: TwParamModifier
   readonly peerHas: TwParamModifier
+  /** Emit the following utilities in a named cascade layer, e.g. `tw.layer('overrides').p(4)` */
+  readonly layer: TwParamModifier
 
   /** Fallback: unknown properties are treated as raw class names */
   [key: string]: any
@@ -811,7 +859,8 @@ export interface TwChain {
 
 function applyMods(rule: StyleRule, mods: Modifier[]): StyleRule {
   if (mods.length === 0) return rule
-  return when(...mods)(rule)
+  // One rule has one selector context, so it stays one rule
+  return when(...mods)(rule) as StyleRule
 }
 
 function applyModsToRules(rules: (StyleRule | string)[], mods: Modifier[]): (StyleRule | string)[] {
@@ -819,6 +868,57 @@ function applyModsToRules(rules: (StyleRule | string)[], mods: Modifier[]): (Sty
   return rules.map(r => typeof r === 'string' ? r : applyMods(r, mods))
 }
 
+/** A modifier usable as a property (`tw.hover.bg(…)`) or function (`tw.hover(tw.bg(…))`) */
+function createModifier(rules: (StyleRule | string)[], pendingMods: Modifier[], mod: Modifier): any {
+  // Return a proxy that:
+  // 1. As property chain: adds modifier to pending (get trap delegates)
+  // 2. As function call with TwChain arg: applies modifier to group
+  const innerChain = createChain(rules, [...pendingMods, mod])
+
+  return new Proxy(function () {} as any, {
+    get(_t, innerProp: string | symbol) {
+      return (innerChain as any)[innerProp]
+    },
+    apply(_t, _this, args) {
+      // hover(tw.bg('red').textColor('white')) — group modifier
+      if (args.length > 0 && isTwChain(args[0])) {
+        const childRules = args[0]._rules as (StyleRule | string)[]
+        const modified = applyModsToRules(childRules, [...pendingMods, mod])
+        return createChain([...rules, ...modified], [])
+      }
+      // hover(tw.bg('red'), tw.p(4)) — multiple chain args
+      // Collect all TwChain args
+      const allChildRules: (StyleRule | string)[] = []
+      for (const arg of args) {
+        if (isTwChain(arg)) {
+          allChildRules.push(...(arg._rules as (StyleRule | string)[]))
+        }
+      }
+      if (allChildRules.length > 0) {
+        const modified = applyModsToRules(allChildRules, [...pendingMods, mod])
+        return createChain([...rules, ...modified], [])
+      }
+      // Fallback: shouldn't normally happen
+      return innerChain
+    },
+  })
+}
+
+/** The modifiers after `tw.group(name)` / `tw.peer(name)`, targeting that named group or peer */
+function createNamedContext(rules: (StyleRule | string)[], pendingMods: Modifier[], kind: string, groupName: string): any {
+  return new Proxy({} as any, {
+    get(_t, prop: string | symbol) {
+      if (typeof prop === 'symbol') return undefined
+      if (prop === 'has') {
+        const has = kind === 'group' ? groupHas : peerHas
+        return (selector: string) => createChain(rules, [...pendingMods, has(selector, groupName)])
+      }
+      const mod = NAMED_MODS[kind + prop.charAt(0).toUpperCase() + prop.slice(1)]
+      return mod ? createModifier(rules, pendingMods, mod(groupName)) : undefined
+    },
+  })
+}
+
 function createChain(rules: (StyleRule | string)[], pendingMods: Modifier[]): TwChain {
   // The proxy target must be a function so `apply` trap works
   const target = function () {} as any
@@ -848,7 +948,7 @@ function createChain(rules: (StyleRule | string)[], pendingMods: Modifier[]): Tw
 
       const name = prop as string
 
-      // --- Parameterized modifiers (has, aria, data, supports, groupHas, peerHas) ---
+      // --- Parameterized modifiers (has, not, nth, minWidth, maxWidth, aria, data, supports, groupHas, peerHas) ---
       if (name in PARAM_MODS) {
         const paramModFn = PARAM_MODS[name]
         // Return a function that takes args and returns a new chain or modifier proxy
@@ -860,41 +960,28 @@ function createChain(rules: (StyleRule | string)[], pendingMods: Modifier[]): Tw
 
       // --- Simple modifiers ---
       if (name in MODS) {
-        const mod = MODS[name]
-        // Return a proxy that:
-        // 1. As property chain: adds modifier to pending (get trap delegates)
-        // 2. As function call with TwChain arg: applies modifier to group
-        const innerChain = createChain(rules, [...pendingMods, mod])
+        return createModifier(rules, pendingMods, MODS[name])
+      }
 
+      // --- Named groups / peers: tw.group('sidebar').hover ---
+      if (name === 'group' || name === 'peer') {
+        const chain = createChain([...rules, name], [])
         return new Proxy(function () {} as any, {
           get(_t, innerProp: string | symbol) {
-            return (innerChain as any)[innerProp]
+            return (chain as any)[innerProp]
           },
           apply(_t, _this, args) {
-            // hover(tw.bg('red').textColor('white')) — group modifier
-            if (args.length > 0 && isTwChain(args[0])) {
-              const childRules = args[0]._rules as (StyleRule | string)[]
-              const modified = applyModsToRules(childRules, [...pendingMods, mod])
-              return createChain([...rules, ...modified], [])
-            }
-            // hover(tw.bg('red'), tw.p(4)) — multiple chain args
-            // Collect all TwChain args
-            const allChildRules: (StyleRule | string)[] = []
-            for (const arg of args) {
-              if (isTwChain(arg)) {
-                allChildRules.push(...(arg._rules as (StyleRule | string)[]))
-              }
-            }
-            if (allChildRules.length > 0) {
-              const modified = applyModsToRules(allChildRules, [...pendingMods, mod])
-              return createChain([...rules, ...modified], [])
-            }
-            // Fallback: shouldn't normally happen
-            return innerChain
+            return createNamedContext(rules, pendingMods, name, args[0])
           },
         })
       }
 
+      // --- Raw class name markers: tw.namedGroup('sidebar') ---
+      if (name in MARKERS) {
+        const marker = MARKERS[name]
+        return (arg: string) => createChain([...rules, marker(arg)], [])
+      }
+
       // --- Value-less utilities ---
       if (name in VALUELESS) {
         const entry = VALUELESS[name]
@@ -963,6 +1050,12 @@ function createChain(rules: (StyleRule | string)[], pendingMods: Modifier[]): Tw
         })
       }
 
+      // --- Custom breakpoints: tw.tablet / tw.maxTablet ---
+      const breakpointMod = breakpointModifier(name)
+      if (breakpointMod) {
+        return createModifier(rules, pendingMods, breakpointMod)
+      }
+
       // --- Unknown prop: treat as raw class name ---
       return createChain([...rules, name], [])
     },
@@ -290,6 +290,13 @@ export interface StyleRule {
    * An empty array means no `@supports` wrapper is emitted.
    */
   supportsQueries: string[]
+  /**
+   * `@container` query preludes that wrap the generated rule
+   * (e.g. `['(min-width: 28rem)']`, `['sidebar (min-width: 28rem)']`).
+   *
+   * Omitted or empty means no `@container` wrapper is emitted.
+   */
+  containerQueries?: string[]
   /**
    * Optional selector template that wraps the generated class name in a
    * more complex selector pattern. The `&` character is replaced with the
@@ -305,8 +312,35 @@ export interface StyleRule {
    * ```
    */
   selectorTemplate?: string
+  /**
+   * Named CSS cascade layer the rule is emitted in (e.g. `'components'`),
+   * set with `layer('name')`. Omitted means the default `utilities` layer.
+   */
+  cascadeLayer?: string
 }
 
+/**
+ * Anything {@link cx} accepts, following `clsx`: style rules, class name
+ * strings and numbers, arrays of these, and objects whose keys are class
+ * names included when their value is truthy. Falsy values and `true` add
+ * nothing, so `cond && p(4)` and `cond ? a : b` can be passed directly.
+ *
+ * @example
+ * ```ts
+ * cx(p(4), isActive && bg('blue-500'), ['card', { selected: isSelected }])
+ * ```
+ */
+export type ClassValue =
+  | StyleRule
+  | string
+  | number
+  | bigint
+  | boolean
+  | null
+  | undefined
+  | ClassValue[]
+  | { readonly [className: string]: unknown }
+
 /**
  * A function that accepts a design-token value and returns a {@link StyleRule}.
  *
@@ -353,6 +387,25 @@ export type Utility = (value: any) => StyleRule
  */
 export type Modifier = (rule: StyleRule) => StyleRule
 
+/**
+ * A group or peer {@link Modifier} that can also be called with the name of
+ * the group it refers to, targeting the element marked with `namedGroup(name)`
+ * or `namedPeer(name)`.
+ *
+ * @example
+ * ```ts
+ * import { cx, when, groupHover, namedGroup, bg } from 'typewritingclass'
+ *
+ * cx(namedGroup('sidebar'))
+ * cx(when(groupHover('sidebar'))(bg('blue-50')))
+ * // CSS: .group\/sidebar:hover ._abc { background-color: #eff6ff; }
+ * ```
+ */
+export interface NamedModifier {
+  (rule: StyleRule): StyleRule
+  (name: string): Modifier
+}
+
 /**
  * The return value of {@link dcx}, containing both a class string and an
  * inline `style` object for dynamic CSS custom-property bindings.
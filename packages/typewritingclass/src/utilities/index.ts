@@ -21,7 +21,7 @@ export {
   colSpan, colStart, colEnd, rowSpan, rowStart, rowEnd,
   gridFlow, autoCols, autoRows,
   justifyItems, justifySelf, alignContent, placeContent, placeItems, placeSelf,
-  container,
+  container, containerType, containerName,
 } from './layout.ts'
 export {
   rounded, roundedT, roundedB, roundedL, roundedR, border, borderT, borderR, borderB, borderL, ring,
@@ -1192,3 +1192,23 @@ export function placeSelf(value: string): StyleRule {
 export function container(): StyleRule {
   return createRule({ width: '100%' })
 }
+
+/**
+ * Makes the element a query container. Defaults to `inline-size`.
+ *
+ * @example
+ * ```ts
+ * containerType()       // container-type: inline-size
+ * containerType('size') // container-type: size
+ * ```
+ */
+export function containerType(value: string = 'inline-size'): StyleRule {
+  return createRule({ 'container-type': value })
+}
+
+/**
+ * Names a query container so `containerMin(size, name)` can target it.
+ */
+export function containerName(name: string): StyleRule {
+  return createRule({ 'container-name': name })
+}
@@ -11,12 +11,15 @@ import { combineRules } from './rule.ts'
  *
  * Multiple style rules passed to the returned function are merged into a single
  * combined rule before modifiers are applied, so selectors and media queries
- * are shared across all declarations.
+ * are shared across all declarations. Rules in different selector contexts
+ * (e.g. a `groupHover` rule next to a plain one) or cascade layers can't
+ * share a class, so each context is merged and modified separately and an
+ * array of rules is returned; {@link cx} accepts either.
  *
  * @param modifiers - One or more {@link Modifier} functions (e.g. `hover`, `md`, `dark`)
  *                    applied right-to-left around the inner rules.
  * @returns A function that accepts {@link StyleRule}s and returns a single
- *          modifier-wrapped `StyleRule`.
+ *          modifier-wrapped `StyleRule`, or one per selector context.
  *
  * @example Single modifier -- hover state
  * ```ts
@@ -56,8 +59,8 @@ import { combineRules } from './rule.ts'
  * ```
  */
 export function when(...modifiers: Modifier[]) {
-  return (...rules: StyleRule[]): StyleRule => {
-    const combined = combineRules(rules)
-    return modifiers.reduceRight((acc, mod) => mod(acc), combined)
+  return (...rules: (StyleRule | StyleRule[])[]): StyleRule | StyleRule[] => {
+    const combined = combineRules(rules.flat()).map(rule => modifiers.reduceRight((acc, mod) => mod(acc), rule))
+    return combined.length === 1 ? combined[0] : combined
   }
 }
@@ -47,6 +47,35 @@ describe('cx', () => {
     expect(classes[2]).toBe('another-class')
   })
 
+  it('skips falsy values like clsx', () => {
+    const rule = createRule({ color: 'red' })
+    const expected = cx(rule)
+    clearRegistry()
+    _resetLayer()
+    expect(cx(false, null, undefined, '', 0, true, rule)).toBe(expected)
+    expect(generateCSS()).toContain('color: red')
+  })
+
+  it('supports conditional arguments', () => {
+    const active = false
+    const red = createRule({ color: 'red' })
+    const blue = createRule({ color: 'blue' })
+    const result = cx(active ? red : blue, active && 'is-active')
+    expect(result).toMatch(/^_[a-z0-9]+$/)
+    expect(generateCSS()).toContain('color: blue')
+    expect(generateCSS()).not.toContain('color: red')
+  })
+
+  it('flattens arrays and includes object keys with truthy values', () => {
+    const result = cx('a', ['b', [createRule({ color: 'red' }), null]], { c: true, d: false, e: 1 }, 7)
+    const classes = result.split(' ')
+    expect(classes[0]).toBe('a')
+    expect(classes[1]).toBe('b')
+    expect(classes[2]).toMatch(/^_/)
+    expect(classes.slice(3)).toEqual(['c', 'e', '7'])
+    expect(generateCSS()).toContain('color: red')
+  })
+
   it('registers rules in the CSS registry', () => {
     cx(createRule({ color: 'red' }))
     const css = generateCSS()
@@ -3,6 +3,13 @@ import { cx, _resetLayer } from '../src/cx.ts'
 import { clearRegistry, generateCSS } from '../src/registry.ts'
 import { createRule } from '../src/rule.ts'
 import { layer } from '../src/layer.ts'
+import { when } from '../src/when.ts'
+import { hover } from '../src/modifiers/pseudo.ts'
+import { supports } from '../src/modifiers/supports.ts'
+import { grid } from '../src/utilities/layout.ts'
+import { p } from '../src/utilities/spacing.ts'
+import { generateHash } from '../src/hash.ts'
+import type { StyleRule } from '../src/types.ts'
 
 describe('layer()', () => {
   beforeEach(() => {
@@ -102,4 +109,48 @@ describe('layer()', () => {
     expect(result.declarations).toEqual({ color: 'red', padding: '1rem' })
     expect(result.dynamicBindings).toBeUndefined()
   })
+
+  it('assigns a named cascade layer', () => {
+    const rule = layer('overrides')(createRule({ color: 'red' })) as StyleRule
+    expect(rule.cascadeLayer).toBe('overrides')
+    expect((rule as any)._layer).toBeUndefined()
+  })
+
+  it('keeps a named layer through when() and splits rules from different layers', () => {
+    const card = layer('components')(createRule({ padding: '1rem' })) as StyleRule
+    const rounded = layer('components')(createRule({ borderRadius: '0.5rem' })) as StyleRule
+    expect((when(hover)(card, rounded) as StyleRule).cascadeLayer).toBe('components')
+    const split = when(hover)(card, createRule({ color: 'red' })) as StyleRule[]
+    expect(split).toHaveLength(2)
+    expect(split.map(rule => rule.cascadeLayer)).toEqual(['components', undefined])
+    expect(split.map(rule => rule.selectors)).toEqual([[':hover'], [':hover']])
+  })
+
+  it('merges several rules the same way the compiler does', () => {
+    const rule = layer(1)(when(supports('(display: grid)'))(grid()), p(4)) as StyleRule
+    expect(rule.supportsQueries).toEqual(['(display: grid)'])
+    expect(generateHash(rule)).toBe('_1ix1xvs')
+  })
+
+  it('emits named layers in order after an @layer statement', () => {
+    cx(
+      createRule({ padding: '1rem' }),
+      layer('overrides')(createRule({ color: 'red' })),
+      layer('components')(createRule({ margin: '0' })),
+      layer('vendor')(createRule({ display: 'block' })),
+    )
+    const css = generateCSS()
+    expect(css.startsWith('@layer reset, base, components, utilities, overrides, vendor;\n\n@layer components {\n  .')).toBe(true)
+    expect(css).toContain('@layer utilities {\n  ._')
+    expect(css.indexOf('@layer components {')).toBeLessThan(css.indexOf('@layer utilities {'))
+    expect(css.indexOf('@layer utilities {')).toBeLessThan(css.indexOf('@layer overrides {'))
+    expect(css.indexOf('@layer overrides {')).toBeLessThan(css.indexOf('@layer vendor {'))
+    expect(css).toContain('    padding: 1rem;')
+  })
+
+  it('gives the same declarations in another layer a different class', () => {
+    const a = cx(createRule({ color: 'red' }))
+    const b = cx(layer('overrides')(createRule({ color: 'red' })))
+    expect(a).not.toBe(b)
+  })
 })
@@ -1,12 +1,28 @@
-import { describe, it, expect } from 'vitest'
+import { describe, it, expect, afterEach } from 'vitest'
 import { dark } from '../../src/modifiers/colorScheme.ts'
 import { createRule } from '../../src/rule.ts'
+import { configureRuntime, _resetRuntimeConfig } from '../../src/config.ts'
 
 describe('color scheme modifiers', () => {
+  afterEach(() => {
+    _resetRuntimeConfig()
+  })
+
   it('dark wraps with prefers-color-scheme media query', () => {
     const rule = createRule({ 'background-color': '#1e293b' })
     const result = dark(rule)
     expect(result.mediaQueries).toEqual(['(prefers-color-scheme: dark)'])
     expect(result.declarations).toEqual({ 'background-color': '#1e293b' })
   })
+
+  it('dark follows the configured class or attribute strategy', () => {
+    const rule = createRule({ 'background-color': '#1e293b' })
+    configureRuntime({ darkMode: 'class' })
+    expect(dark(rule).selectorTemplate).toBe('.dark &')
+    expect(dark(rule).mediaQueries).toEqual([])
+    configureRuntime({ darkMode: 'attr' })
+    expect(dark(rule).selectorTemplate).toBe('[data-theme="dark"] &')
+    configureRuntime({ darkMode: 'class', darkSelector: '.theme-night' })
+    expect(dark(rule).selectorTemplate).toBe('.theme-night &')
+  })
 })
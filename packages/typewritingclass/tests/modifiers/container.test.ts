@@ -0,0 +1,32 @@
+import { describe, it, expect } from 'vitest'
+import { containerMin, containerMax } from '../../src/modifiers/container.ts'
+import { createRule } from '../../src/rule.ts'
+import { generateHash } from '../../src/hash.ts'
+
+describe('container modifiers', () => {
+  const rule = createRule({ display: 'flex' })
+
+  it('containerMin resolves container breakpoints', () => {
+    expect(containerMin('md')(rule).containerQueries).toEqual(['(min-width: 28rem)'])
+  })
+
+  it('containerMin targets a named container', () => {
+    expect(containerMin('md', 'sidebar')(rule).containerQueries).toEqual(['sidebar (min-width: 28rem)'])
+  })
+
+  it('containerMax accepts raw lengths and pixel numbers', () => {
+    expect(containerMax('30em')(rule).containerQueries).toEqual(['(max-width: 30em)'])
+    expect(containerMax(500)(rule).containerQueries).toEqual(['(max-width: 500px)'])
+  })
+
+  it('preserves declarations and media queries', () => {
+    const result = containerMin('sm')({ ...rule, mediaQueries: ['print'] })
+    expect(result.declarations).toEqual({ display: 'flex' })
+    expect(result.mediaQueries).toEqual(['print'])
+  })
+
+  it('changes the hash only for rules with container queries', () => {
+    expect(generateHash({ ...rule, containerQueries: [] })).toBe(generateHash(rule))
+    expect(generateHash(containerMin('md')(rule))).not.toBe(generateHash(rule))
+  })
+})
@@ -1,5 +1,5 @@
 import { describe, it, expect } from 'vitest'
-import { groupHover, groupFocus, groupActive, groupFocusVisible, groupFocusWithin, groupDisabled, groupChecked, groupEmpty, groupFirst, groupLast, groupOdd, groupEven, groupOpen, groupVisited, groupHas } from '../../src/modifiers/group.ts'
+import { groupHover, groupFocus, groupActive, groupFocusVisible, groupFocusWithin, groupDisabled, groupChecked, groupEmpty, groupFirst, groupLast, groupOdd, groupEven, groupOpen, groupVisited, groupHas, namedGroup } from '../../src/modifiers/group.ts'
 import { createRule } from '../../src/rule.ts'
 
 describe('group modifiers', () => {
@@ -66,6 +66,17 @@ describe('group modifiers', () => {
     expect(modifier(rule).selectorTemplate).toBe('.group:has(.child) &')
   })
 
+  it('targets a named group when called with a name', () => {
+    expect(groupHover('sidebar')(rule).selectorTemplate).toBe('.group\\/sidebar:hover &')
+    expect(groupOpen('sidebar')(rule).selectorTemplate).toBe('.group\\/sidebar[open] &')
+    expect(groupHas('img', 'card')(rule).selectorTemplate).toBe('.group\\/card:has(img) &')
+    expect(groupHover('main.nav:1')(rule).selectorTemplate).toBe('.group\\/main\\.nav\\:1:hover &')
+  })
+
+  it('namedGroup returns the marker class', () => {
+    expect(namedGroup('sidebar')).toBe('group/sidebar')
+  })
+
   it('preserves declarations', () => {
     expect(groupHover(rule).declarations).toEqual({ color: 'red' })
   })
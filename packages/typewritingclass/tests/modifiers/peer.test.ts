@@ -1,5 +1,5 @@
 import { describe, it, expect } from 'vitest'
-import { peerHover, peerFocus, peerActive, peerFocusVisible, peerDisabled, peerChecked, peerInvalid, peerRequired, peerPlaceholderShown, peerFocusWithin, peerEmpty, peerFirst, peerLast, peerOdd, peerEven, peerOpen, peerVisited, peerHas } from '../../src/modifiers/peer.ts'
+import { peerHover, peerFocus, peerActive, peerFocusVisible, peerDisabled, peerChecked, peerInvalid, peerRequired, peerPlaceholderShown, peerFocusWithin, peerEmpty, peerFirst, peerLast, peerOdd, peerEven, peerOpen, peerVisited, peerHas, namedPeer } from '../../src/modifiers/peer.ts'
 import { createRule } from '../../src/rule.ts'
 
 describe('peer modifiers', () => {
@@ -78,6 +78,15 @@ describe('peer modifiers', () => {
     expect(modifier(rule).selectorTemplate).toBe('.peer:has(.child) ~ &')
   })
 
+  it('targets a named peer when called with a name', () => {
+    expect(peerChecked('email')(rule).selectorTemplate).toBe('.peer\\/email:checked ~ &')
+    expect(peerHas('.child', 'email')(rule).selectorTemplate).toBe('.peer\\/email:has(.child) ~ &')
+  })
+
+  it('namedPeer returns the marker class', () => {
+    expect(namedPeer('email')).toBe('peer/email')
+  })
+
   it('preserves declarations', () => {
     expect(peerHover(rule).declarations).toEqual({ color: 'red' })
   })
@@ -1,5 +1,5 @@
 import { describe, it, expect } from 'vitest'
-import { hover, focus, active, disabled, focusVisible, focusWithin, firstChild, lastChild, visited, checked, indeterminate, default_, required_, valid, invalid, inRange, outOfRange, placeholderShown, autofill, readOnly, empty, even, odd, firstOfType, lastOfType, onlyChild, onlyOfType, target, open_, has_ } from '../../src/modifiers/pseudo.ts'
+import { hover, focus, active, disabled, focusVisible, focusWithin, firstChild, lastChild, visited, checked, indeterminate, default_, required_, valid, invalid, inRange, outOfRange, placeholderShown, autofill, readOnly, empty, even, odd, firstOfType, lastOfType, onlyChild, onlyOfType, target, open_, has_, not_, nth } from '../../src/modifiers/pseudo.ts'
 import { createRule } from '../../src/rule.ts'
 
 describe('pseudo-class modifiers', () => {
@@ -138,4 +138,13 @@ describe('pseudo-class modifiers', () => {
     const modifier = has_('input:checked')
     expect(modifier(rule).declarations).toEqual({ color: 'red' })
   })
+
+  it('not_ factory creates :not() selector', () => {
+    expect(not_(':first-child')(rule).selectors).toEqual([':not(:first-child)'])
+  })
+
+  it('nth factory creates :nth-child() selector', () => {
+    expect(nth(3)(rule).selectors).toEqual([':nth-child(3)'])
+    expect(nth('2n+1')(rule).selectors).toEqual([':nth-child(2n+1)'])
+  })
 })
@@ -1,10 +1,17 @@
-import { describe, it, expect } from 'vitest'
-import { sm, md, lg, xl, _2xl, maxSm, maxMd, maxLg, maxXl, max2xl } from '../../src/modifiers/responsive.ts'
+import { describe, it, expect, afterEach } from 'vitest'
+import { sm, md, lg, xl, _2xl, maxSm, maxMd, maxLg, maxXl, max2xl, minWidth, maxWidth, screen, maxScreen } from '../../src/modifiers/responsive.ts'
 import { createRule } from '../../src/rule.ts'
+import { configureRuntime, _resetRuntimeConfig } from '../../src/config.ts'
+import { tw } from '../../src/tw.ts'
+import { clearRegistry, generateCSS } from '../../src/registry.ts'
 
 describe('responsive modifiers', () => {
   const rule = createRule({ display: 'flex' })
 
+  afterEach(() => {
+    _resetRuntimeConfig()
+  })
+
   it('sm wraps with 640px media query', () => {
     expect(sm(rule).mediaQueries).toEqual(['(min-width: 640px)'])
   })
@@ -49,4 +56,42 @@ describe('responsive modifiers', () => {
   it('max2xl wraps with max-width: 1535px', () => {
     expect(max2xl(rule).mediaQueries).toEqual(['(max-width: 1535px)'])
   })
+
+  it('minWidth wraps with an arbitrary min-width, numbers in px', () => {
+    expect(minWidth(900)(rule).mediaQueries).toEqual(['(min-width: 900px)'])
+    expect(minWidth('40em')(rule).mediaQueries).toEqual(['(min-width: 40em)'])
+  })
+
+  it('maxWidth wraps with an arbitrary max-width, numbers in px', () => {
+    expect(maxWidth(600)(rule).mediaQueries).toEqual(['(max-width: 600px)'])
+  })
+
+  it('screen and maxScreen resolve breakpoint names', () => {
+    expect(screen('md')(rule).mediaQueries).toEqual(['(min-width: 768px)'])
+    expect(maxScreen('2xl')(rule).mediaQueries).toEqual(['(max-width: 1535px)'])
+    expect(screen(900)(rule).mediaQueries).toEqual(['(min-width: 900px)'])
+    expect(maxScreen('40rem')(rule).mediaQueries).toEqual(['(max-width: calc(40rem - 1px))'])
+  })
+
+  it('screen rejects unknown breakpoint names', () => {
+    expect(() => screen('tablet')).toThrow(/Unknown breakpoint 'tablet'/)
+  })
+
+  it('uses the configured breakpoints', () => {
+    configureRuntime({ breakpoints: { sm: '480px', md: '960px', tablet: '960px' } })
+    expect(md(rule).mediaQueries).toEqual(['(min-width: 960px)'])
+    expect(maxSm(rule).mediaQueries).toEqual(['(max-width: 479px)'])
+    expect(screen('tablet')(rule).mediaQueries).toEqual(['(min-width: 960px)'])
+    expect(() => lg(rule)).toThrow(/Unknown breakpoint 'lg'/)
+  })
+
+  it('resolves custom breakpoint names in tw chains', () => {
+    clearRegistry()
+    configureRuntime({ breakpoints: { tablet: '960px' } })
+    const className = String((tw as any).tablet.p(4).maxTablet.p(2))
+    expect(className.split(' ')).not.toContain('tablet')
+    const css = generateCSS()
+    expect(css).toContain('@media (min-width: 960px)')
+    expect(css).toContain('@media (max-width: 959px)')
+  })
 })
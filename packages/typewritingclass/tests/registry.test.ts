@@ -60,6 +60,19 @@ describe('registry', () => {
     expect(css).toContain('._sq')
   })
 
+  it('generateCSS nests @container inside @supports inside @media', () => {
+    const rule = {
+      ...createRule({ display: 'grid' }),
+      mediaQueries: ['print'],
+      supportsQueries: ['(display: grid)'],
+      containerQueries: ['sidebar (min-width: 28rem)'],
+    }
+    register('_cq', rule, 0)
+    expect(generateCSS()).toBe(
+      '@media print {\n@supports (display: grid) {\n@container sidebar (min-width: 28rem) {\n._cq {\n  display: grid;\n}\n}\n}\n}',
+    )
+  })
+
   it('returns empty string for empty registry', () => {
     expect(generateCSS()).toBe('')
   })
@@ -5,7 +5,7 @@ describe('combineRules', () => {
   it('merges declarations from multiple rules', () => {
     const a = createRule({ color: 'red' })
     const b = createRule({ padding: '1rem' })
-    const result = combineRules([a, b])
+    const [result] = combineRules([a, b])
     expect(result.declarations).toEqual({ color: 'red', padding: '1rem' })
   })
 
@@ -18,7 +18,7 @@ describe('combineRules', () => {
       { padding: 'var(--twc-d1)' },
       { '--twc-d1': '1rem' },
     )
-    const result = combineRules([a, b])
+    const [result] = combineRules([a, b])
     expect(result.declarations).toEqual({
       color: 'var(--twc-d0)',
       padding: 'var(--twc-d1)',
@@ -35,7 +35,7 @@ describe('combineRules', () => {
       { padding: 'var(--twc-d0)' },
       { '--twc-d0': '2rem' },
     )
-    const result = combineRules([a, b])
+    const [result] = combineRules([a, b])
     expect(result.declarations).toEqual({
       color: 'red',
       padding: 'var(--twc-d0)',
@@ -46,14 +46,14 @@ describe('combineRules', () => {
   it('omits dynamicBindings when no rules have them', () => {
     const a = createRule({ color: 'red' })
     const b = createRule({ padding: '1rem' })
-    const result = combineRules([a, b])
+    const [result] = combineRules([a, b])
     expect(result.dynamicBindings).toBeUndefined()
   })
 
   it('merges selectors and media queries', () => {
     const a = { ...createRule({ color: 'red' }), selectors: [':hover'] as string[], mediaQueries: ['(min-width: 768px)'] as string[] }
     const b = { ...createRule({ padding: '1rem' }), selectors: [':focus'] as string[], mediaQueries: ['(min-width: 1024px)'] as string[] }
-    const result = combineRules([a, b])
+    const [result] = combineRules([a, b])
     expect(result.selectors).toEqual([':hover', ':focus'])
     expect(result.mediaQueries).toEqual(['(min-width: 768px)', '(min-width: 1024px)'])
   })
@@ -61,7 +61,7 @@ describe('combineRules', () => {
   it('deduplicates selectors and media queries', () => {
     const a = { ...createRule({ color: 'red' }), selectors: [':hover'] as string[], mediaQueries: ['(min-width: 768px)'] as string[] }
     const b = { ...createRule({ padding: '1rem' }), selectors: [':hover'] as string[], mediaQueries: ['(min-width: 768px)'] as string[] }
-    const result = combineRules([a, b])
+    const [result] = combineRules([a, b])
     expect(result.selectors).toEqual([':hover'])
     expect(result.mediaQueries).toEqual(['(min-width: 768px)'])
   })
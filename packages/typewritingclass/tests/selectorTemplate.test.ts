@@ -1,6 +1,14 @@
 import { describe, it, expect, beforeEach } from 'vitest'
-import { createRule, wrapWithSelectorTemplate } from '../src/rule.ts'
+import { createRule, wrapWithSelectorTemplate, combineRules } from '../src/rule.ts'
+import { when } from '../src/when.ts'
+import { hover } from '../src/modifiers/pseudo.ts'
+import { before } from '../src/modifiers/pseudoElements.ts'
+import { groupHover } from '../src/modifiers/group.ts'
+import { peerChecked } from '../src/modifiers/peer.ts'
+import { rtl } from '../src/modifiers/direction.ts'
+import { md } from '../src/modifiers/responsive.ts'
 import { generateHash } from '../src/hash.ts'
+import { cx } from '../src/cx.ts'
 import { register, generateCSS, clearRegistry } from '../src/registry.ts'
 
 describe('selectorTemplate', () => {
@@ -47,4 +55,46 @@ describe('selectorTemplate', () => {
     const withTemplate = wrapWithSelectorTemplate(rule, '.group:hover &')
     expect(generateHash(rule, 0)).not.toBe(generateHash(withTemplate, 0))
   })
+
+  it('stacked templates compose around each other', () => {
+    const rule = when(rtl, groupHover)(createRule({ color: 'blue' }))
+    expect(rule.selectorTemplate).toBe('[dir="rtl"] .group:hover &')
+  })
+
+  it('renderRule keeps selectors and puts pseudo-elements last', () => {
+    const rule = when(peerChecked, before, hover)(createRule({ color: 'blue' }))
+    register('_c', rule, 0)
+    expect(generateCSS()).toContain('.peer:checked ~ ._c:hover::before {')
+  })
+
+  it('pseudo-element order does not change the hash', () => {
+    const a = when(hover, before)(createRule({ color: 'blue' }))
+    const b = when(before, hover)(createRule({ color: 'blue' }))
+    expect(a.selectors).toEqual([':hover', '::before'])
+    expect(generateHash(a, 0)).toBe(generateHash(b, 0))
+  })
+
+  it('combineRules keeps a template shared by every rule', () => {
+    const a = wrapWithSelectorTemplate(createRule({ color: 'blue' }), '.group:hover &')
+    const b = wrapWithSelectorTemplate(createRule({ margin: '0' }), '.group:hover &')
+    expect(combineRules([a, b])).toHaveLength(1)
+    expect(combineRules([a, b])[0].selectorTemplate).toBe('.group:hover &')
+  })
+
+  it('combineRules gives each template its own rule', () => {
+    const a = wrapWithSelectorTemplate(createRule({ color: 'blue' }), '.group:hover &')
+    const [grouped, plain] = combineRules([a, createRule({ margin: '0' }), createRule({ padding: '0' })])
+    expect(grouped).toMatchObject({ declarations: { color: 'blue' }, selectorTemplate: '.group:hover &' })
+    expect(plain.declarations).toEqual({ margin: '0', padding: '0' })
+    expect(plain.selectorTemplate).toBeUndefined()
+  })
+
+  it('when() renders rules in different selector contexts as separate classes', () => {
+    const styles = when(md)(createRule({ padding: '1rem' }), groupHover(createRule({ color: 'blue' })))
+    const classes = cx(styles).split(' ')
+    expect(classes).toHaveLength(2)
+    const css = generateCSS()
+    expect(css).toContain(`@media (min-width: 768px) {\n.${classes[0]} {\n  padding: 1rem;`)
+    expect(css).toContain(`@media (min-width: 768px) {\n.group:hover .${classes[1]} {\n  color: blue;`)
+  })
 })
@@ -138,6 +138,18 @@ describe('tw proxy', () => {
       expect(css).toContain('color:')
     })
 
+    it('named group and peer modifiers target the named element', () => {
+      tw.group('sidebar').hover.p(4).peer('email').checked(tw.p(2)).group('card').has('img').p(1).toString()
+      const css = generateCSS()
+      expect(css).toContain('.group\\/sidebar:hover ._')
+      expect(css).toContain('.peer\\/email:checked ~ ._')
+      expect(css).toContain('.group\\/card:has(img) ._')
+    })
+
+    it('namedGroup and namedPeer add marker classes', () => {
+      expect(tw.namedGroup('sidebar').namedPeer('email').toString()).toBe('group/sidebar peer/email')
+    })
+
     it('responsive modifier works', () => {
       tw.md.p(8).toString()
       const css = generateCSS()
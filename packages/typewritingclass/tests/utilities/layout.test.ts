@@ -19,7 +19,7 @@ import {
   colSpan, colStart, colEnd, rowSpan, rowStart, rowEnd,
   gridFlow, autoCols, autoRows,
   justifyItems, justifySelf, alignContent, placeContent, placeItems, placeSelf,
-  container,
+  container, containerType, containerName,
   overscrollX, overscrollY,
 } from '../../src/utilities/layout.ts'
 import { dynamic } from '../../src/dynamic.ts'
@@ -382,6 +382,15 @@ describe('layout utilities', () => {
     expect(container().declarations).toEqual({ width: '100%' })
   })
 
+  it('containerType defaults to inline-size', () => {
+    expect(containerType().declarations).toEqual({ 'container-type': 'inline-size' })
+    expect(containerType('size').declarations).toEqual({ 'container-type': 'size' })
+  })
+
+  it('containerName sets container-name', () => {
+    expect(containerName('sidebar').declarations).toEqual({ 'container-name': 'sidebar' })
+  })
+
   it('overscrollX sets overscroll-behavior-x', () => {
     expect(overscrollX('contain').declarations).toEqual({ 'overscroll-behavior-x': 'contain' })
   })
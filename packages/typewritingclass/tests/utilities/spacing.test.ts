@@ -183,4 +183,14 @@ describe('spacing utilities', () => {
     // 15 is not in the spacing scale either
     expect(m(15).declarations).toEqual({ margin: '3.75rem' })
   })
+
+  it('negative values resolve through the scale and negate the token', () => {
+    expect(mt(-4).declarations).toEqual({ 'margin-top': '-1rem' })
+    expect(mx(-0.5).declarations).toEqual({ 'margin-left': '-0.125rem', 'margin-right': '-0.125rem' })
+    expect(m(-0).declarations).toEqual({ margin: '0px' })
+  })
+
+  it('negative non-scale numbers fall back to value * 0.25rem', () => {
+    expect(ml(-13).declarations).toEqual({ 'margin-left': '-3.25rem' })
+  })
 })